@@ -1,7 +1,7 @@
 use std::{fmt, str::FromStr};
 
 use core_plugin_runtime::metadata;
-use darling::{FromAttributes, FromDeriveInput, FromField, FromMeta, ToTokens};
+use darling::{util::Flag, FromAttributes, FromDeriveInput, FromField, FromMeta, ToTokens};
 use proc_macro2::TokenStream;
 use quote::{quote, TokenStreamExt};
 
@@ -52,11 +52,64 @@ impl Type {
 
 impl FromMeta for Type {
     fn from_string(value: &str) -> Result<Self, darling::Error> {
-        match metadata::Type::from_str(value) {
-            Ok(typ) => Ok(Type(typ)),
-            Err(err) => Err(darling::Error::custom(err)),
+        let value = normalize_type_whitespace(value);
+        let typ = match metadata::Type::from_str(&value) {
+            Ok(typ) => typ,
+            Err(err) => return Err(darling::Error::custom(err)),
+        };
+
+        check_enum_members(&typ).map_err(darling::Error::custom)?;
+        Ok(Type(typ))
+    }
+}
+
+// "enum{ off, on }" => "enum{off,on}"
+fn normalize_type_whitespace(value: &str) -> String {
+    let mut result = String::new();
+    let mut segment = String::new();
+
+    for c in value.chars() {
+        if let '{' | '}' | '[' | ']' | ',' | ';' | ':' = c {
+            result.push_str(segment.trim());
+            result.push(c);
+            segment.clear();
+        } else {
+            segment.push(c);
         }
     }
+
+    result.push_str(segment.trim());
+    result
+}
+
+// The runtime parser accepts any member list, but two members with the same name would be
+// indistinguishable once the value is set
+fn check_enum_members(typ: &metadata::Type) -> Result<(), String> {
+    match typ {
+        metadata::Type::Enum(members) => {
+            if members.is_empty() {
+                return Err(String::from("Empty enum"));
+            }
+
+            for (index, member) in members.iter().enumerate() {
+                if member.is_empty() {
+                    return Err(format!("Empty enum member at position {}", index));
+                }
+
+                if members[..index].contains(member) {
+                    return Err(format!("Duplicate enum member '{}'", member));
+                }
+            }
+
+            Ok(())
+        }
+        metadata::Type::List(item_type) => check_enum_members(item_type),
+        metadata::Type::Nullable(value_type) => check_enum_members(value_type),
+        metadata::Type::Object(fields) => fields
+            .iter()
+            .try_for_each(|(_, field_type)| check_enum_members(field_type)),
+        _ => Ok(()),
+    }
 }
 
 impl ToTokens for Type {
@@ -67,17 +120,138 @@ impl ToTokens for Type {
             }
             metadata::Type::Text => quote! { core_plugin_runtime::metadata::Type::Text },
             metadata::Type::Float => quote! { core_plugin_runtime::metadata::Type::Float },
+            metadata::Type::Duration => quote! { core_plugin_runtime::metadata::Type::Duration },
+            metadata::Type::Timestamp => quote! { core_plugin_runtime::metadata::Type::Timestamp },
+            metadata::Type::Char => quote! { core_plugin_runtime::metadata::Type::Char },
+            metadata::Type::Color => quote! { core_plugin_runtime::metadata::Type::Color },
             metadata::Type::Bool => quote! { core_plugin_runtime::metadata::Type::Bool },
             metadata::Type::Enum(vec) => {
                 quote! { core_plugin_runtime::metadata::Type::Enum(vec![#(#vec.to_string()),*]) }
             }
-            metadata::Type::Complex => quote! { core_plugin_runtime::metadata::Type::Complex },
+            metadata::Type::List(item_type) => {
+                let item_type = Type::new(item_type.as_ref().clone());
+                quote! { core_plugin_runtime::metadata::Type::List(std::boxed::Box::new(#item_type)) }
+            }
+            metadata::Type::Nullable(value_type) => {
+                let value_type = Type::new(value_type.as_ref().clone());
+                quote! { core_plugin_runtime::metadata::Type::Nullable(std::boxed::Box::new(#value_type)) }
+            }
+            metadata::Type::Object(fields) => {
+                let fields = fields.iter().map(|(name, field_type)| {
+                    let field_type = Type::new(field_type.clone());
+                    quote! { (#name.to_string(), #field_type) }
+                });
+                quote! { core_plugin_runtime::metadata::Type::Object(vec![#(#fields),*]) }
+            }
+            metadata::Type::Complex(name) => {
+                quote! { core_plugin_runtime::metadata::Type::Complex(#name.to_string()) }
+            }
+        };
+
+        tokens.append_all(gen);
+    }
+}
+
+// c/c from metadata to add FromMeta/ToToken
+#[derive(FromMeta, PartialEq, Eq, Debug)]
+pub enum PluginCategory {
+    Lighting,
+    Heating,
+    Shutters,
+    Security,
+    Energy,
+    Multimedia,
+    Network,
+    Other,
+}
+
+impl ToTokens for PluginCategory {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let gen = match *self {
+            PluginCategory::Lighting => {
+                quote! { core_plugin_runtime::metadata::PluginCategory::Lighting }
+            }
+            PluginCategory::Heating => {
+                quote! { core_plugin_runtime::metadata::PluginCategory::Heating }
+            }
+            PluginCategory::Shutters => {
+                quote! { core_plugin_runtime::metadata::PluginCategory::Shutters }
+            }
+            PluginCategory::Security => {
+                quote! { core_plugin_runtime::metadata::PluginCategory::Security }
+            }
+            PluginCategory::Energy => {
+                quote! { core_plugin_runtime::metadata::PluginCategory::Energy }
+            }
+            PluginCategory::Multimedia => {
+                quote! { core_plugin_runtime::metadata::PluginCategory::Multimedia }
+            }
+            PluginCategory::Network => {
+                quote! { core_plugin_runtime::metadata::PluginCategory::Network }
+            }
+            PluginCategory::Other => {
+                quote! { core_plugin_runtime::metadata::PluginCategory::Other }
+            }
         };
 
         tokens.append_all(gen);
     }
 }
 
+// actions("on", "off")
+#[derive(Default, PartialEq, Eq, Debug)]
+pub struct StringList(Vec<String>);
+
+impl StringList {
+    pub fn value(&self) -> &Vec<String> {
+        &self.0
+    }
+}
+
+impl FromMeta for StringList {
+    // absent list is empty
+    fn from_none() -> Option<Self> {
+        Some(StringList(Vec::new()))
+    }
+
+    fn from_list(items: &[syn::NestedMeta]) -> Result<Self, darling::Error> {
+        let mut list = Vec::new();
+
+        for item in items {
+            match item {
+                syn::NestedMeta::Lit(syn::Lit::Str(value)) => list.push(value.value()),
+                _ => return Err(darling::Error::unexpected_type("non-string").with_span(item)),
+            }
+        }
+
+        Ok(StringList(list))
+    }
+}
+
+// range = "0..=100", inclusive
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct IntegerRange(pub i64, pub i64);
+
+impl FromMeta for IntegerRange {
+    fn from_string(value: &str) -> Result<Self, darling::Error> {
+        let parse = |bound: &str| bound.trim().parse::<i64>().ok();
+
+        match value.split_once("..=") {
+            Some((min, max)) => match (parse(min), parse(max)) {
+                (Some(min), Some(max)) => Ok(IntegerRange(min, max)),
+                _ => Err(darling::Error::custom(format!(
+                    "Invalid range bounds '{}'",
+                    value
+                ))),
+            },
+            None => Err(darling::Error::custom(format!(
+                "Invalid range '{}', expected 'min..=max'",
+                value
+            ))),
+        }
+    }
+}
+
 // c/c from metadata to add FromMeta
 #[derive(FromMeta, PartialEq, Eq, Debug)]
 pub enum ConfigType {
@@ -85,6 +259,7 @@ pub enum ConfigType {
     Bool,
     Integer,
     Float,
+    Duration,
 }
 
 impl ToTokens for ConfigType {
@@ -94,6 +269,9 @@ impl ToTokens for ConfigType {
             ConfigType::Bool => quote! { core_plugin_runtime::metadata::ConfigType::Bool },
             ConfigType::Integer => quote! { core_plugin_runtime::metadata::ConfigType::Integer },
             ConfigType::Float => quote! { core_plugin_runtime::metadata::ConfigType::Float },
+            ConfigType::Duration => {
+                quote! { core_plugin_runtime::metadata::ConfigType::Duration }
+            }
         };
 
         tokens.append_all(gen);
@@ -107,7 +285,11 @@ pub struct ConfigTypeError {
 
 impl fmt::Display for ConfigTypeError {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "Invalid member type provided '{:#?}'", self.r#type)
+        write!(
+            fmt,
+            "Invalid config type provided '{}', supported types: String, bool, f32, f64, i8, i16, i32, i64, u8, u16, u32, u64, Duration",
+            self.r#type.to_token_stream()
+        )
     }
 }
 
@@ -123,12 +305,15 @@ impl TryFrom<&syn::Type> for ConfigType {
                 "bool" => {
                     return Ok(ConfigType::Bool);
                 }
-                "i64" => {
+                "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" => {
                     return Ok(ConfigType::Integer);
                 }
-                "f64" => {
+                "f64" | "f32" => {
                     return Ok(ConfigType::Float);
                 }
+                "Duration" => {
+                    return Ok(ConfigType::Duration);
+                }
                 _ => {}
             }
         }
@@ -147,10 +332,23 @@ pub struct MylifePlugin {
     #[darling(default)]
     pub name: Option<String>,
 
+    // stable id, persisted references should not depend on file or struct names
+    pub id: Option<String>,
+
+    // plugin schema version, the module version is used if not set
+    pub version: Option<String>,
+
     #[darling(default)]
     pub description: Option<String>,
 
     pub usage: PluginUsage,
+
+    pub category: Option<PluginCategory>,
+
+    pub tags: StringList,
+
+    // message, eg: the replacement to use
+    pub deprecated: Option<String>,
 }
 
 #[derive(Debug, FromField)]
@@ -166,6 +364,26 @@ pub struct MylifeConfig {
     pub description: Option<String>,
 
     pub r#type: Option<ConfigType>,
+
+    pub pattern: Option<String>,
+
+    pub range: Option<IntegerRange>,
+
+    // out of range values are clamped instead of rejected
+    pub clamp: Flag,
+
+    pub unit: Option<String>,
+
+    // HashMap<String, ConfigValue> receiving config keys not declared by other fields
+    pub extra: Flag,
+
+    // also published as a read-only state with the same name
+    pub expose_as_state: Flag,
+
+    // credential: redacted by the runtime, rendered as a password field by the UI
+    pub secret: Flag,
+
+    pub deprecated: Option<String>,
 }
 
 #[derive(Debug, FromField)]
@@ -181,6 +399,58 @@ pub struct MylifeState {
     pub description: Option<String>,
 
     pub r#type: Option<Type>,
+
+    pub derived: Flag,
+
+    pub actions: StringList,
+
+    // for range types: State::set clamps instead of emitting out of range values
+    pub clamp: Flag,
+
+    pub unit: Option<String>,
+
+    // number of changes recorded for diagnostics
+    pub history: Option<usize>,
+
+    // "push" (default): each change calls the host, "polled": the host drains changes (fast-updating states)
+    pub mode: Option<String>,
+
+    // bool states: stays true until acknowledged with the generated clear<Name> action
+    pub latched: Flag,
+
+    pub deprecated: Option<String>,
+}
+
+#[derive(Debug, FromField)]
+#[darling(attributes(mylife_event))]
+pub struct MylifeEvent {
+    pub ident: Option<syn::Ident>,
+    pub ty: syn::Type,
+
+    pub name: Option<String>,
+
+    pub description: Option<String>,
+
+    pub r#type: Option<Type>,
+
+    pub deprecated: Option<String>,
+}
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(mylife_value), supports(struct_named))]
+pub struct MylifeValue {
+    pub ident: syn::Ident,
+}
+
+#[derive(Debug, FromField)]
+#[darling(attributes(mylife_value))]
+pub struct MylifeValueField {
+    pub ident: Option<syn::Ident>,
+    pub ty: syn::Type,
+
+    pub name: Option<String>,
+
+    pub r#type: Option<Type>,
 }
 
 #[derive(Debug, FromAttributes)]
@@ -193,4 +463,21 @@ pub struct MylifeAction {
     pub description: Option<String>,
 
     pub r#type: Option<Type>,
+
+    pub result_type: Option<Type>,
+
+    pub retries: Option<u32>,
+
+    pub backoff: Option<String>,
+
+    pub default_arg: Option<String>,
+
+    // name of a `fn(&self) -> bool` method, the action is rejected while it returns false
+    pub guard: Option<String>,
+
+    // eg: "10/s", executions above it are dropped
+    pub rate_limit: Option<String>,
+
+    // logged on the first execution
+    pub deprecated: Option<String>,
 }
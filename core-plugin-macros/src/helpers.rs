@@ -2,68 +2,295 @@ use std::env;
 
 use crate::attributes;
 use core_plugin_runtime::metadata;
+use darling::ToTokens;
 use proc_macro2::TokenStream;
-use proc_macro_error::abort_call_site;
+use proc_macro_error::{abort, abort_call_site};
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+
+// Returns an expression which evaluates to the runtime metadata::Type
+pub fn get_type(native_type: &syn::Type, provided_type: &Option<attributes::Type>) -> TokenStream {
+    if let Some(provided_type) = provided_type {
+        check_type(native_type, provided_type.value());
+        provided_type.to_token_stream()
+    } else {
+        deduce_type(native_type)
+    }
+}
+
+pub fn check_type(native_type: &syn::Type, provided_type: &metadata::Type) {
+    if let metadata::Type::List(item_type) = provided_type {
+        match get_generic_arg_type(native_type, "Vec") {
+            Some(native_item_type) => check_type(native_item_type, item_type),
+            None => abort_call_site!("Expected Vec, got '{}'", native_type.to_token_stream()),
+        }
+
+        return;
+    }
+
+    if let metadata::Type::Nullable(value_type) = provided_type {
+        match get_generic_arg_type(native_type, "Option") {
+            Some(native_value_type) => check_type(native_value_type, value_type),
+            None => abort_call_site!("Expected Option, got '{}'", native_type.to_token_stream()),
+        }
+
+        return;
+    }
+
+    if let metadata::Type::Object(_) | metadata::Type::Complex(_) = provided_type {
+        // Checked at runtime by the MylifeValue implementation
+        return;
+    }
 
-pub fn get_type(
-    native_type: &syn::Type,
-    provided_type: &Option<attributes::Type>,
-) -> attributes::Type {
     let native_type_name = get_native_type_name(native_type);
 
-    if let Some(provided_type) = provided_type {
-        match provided_type.value() {
-            metadata::Type::Range(min, max) => {
-                if native_type_name != "i64" {
-                    abort_call_site!("Expected i64, got '{}'", native_type_name);
-                }
-
-                if min >= max {
-                    abort_call_site!("Expected min ({}) < max ({})", min, max);
-                }
+    match provided_type {
+        metadata::Type::Range(min, max) => {
+            let (native_min, native_max) = match get_integer_bounds(&native_type_name) {
+                Some(bounds) => bounds,
+                None => abort_call_site!("Expected integer, got '{}'", native_type_name),
+            };
+
+            if min >= max {
+                abort_call_site!("Expected min ({}) < max ({})", min, max);
             }
-            metadata::Type::Text => {
-                if native_type_name != "String" {
-                    abort_call_site!("Expected String, got '{}'", native_type_name);
-                }
+
+            if (*min as i128) < native_min || (*max as i128) > native_max {
+                abort_call_site!(
+                    "Range [{};{}] does not fit in '{}'",
+                    min,
+                    max,
+                    native_type_name
+                );
+            }
+        }
+        metadata::Type::Text => {
+            if native_type_name != "String" {
+                abort_call_site!("Expected String, got '{}'", native_type_name);
             }
-            metadata::Type::Float => {
-                if native_type_name != "f64" {
-                    abort_call_site!("Expected Float64, got '{}'", native_type_name);
-                }
+        }
+        metadata::Type::Float => {
+            if native_type_name != "f64" && native_type_name != "f32" {
+                abort_call_site!("Expected f64 or f32, got '{}'", native_type_name);
             }
-            metadata::Type::Bool => {
-                if native_type_name != "bool" {
-                    abort_call_site!("Expected Bool, got '{}'", native_type_name);
-                }
+        }
+        metadata::Type::Bool => {
+            if native_type_name != "bool" {
+                abort_call_site!("Expected Bool, got '{}'", native_type_name);
             }
-            metadata::Type::Enum(vec) => {
-                if native_type_name != "String" {
-                    abort_call_site!("Expected String, got '{}'", native_type_name);
-                }
-
-                if vec.len() < 2 {
-                    abort_call_site!("Expected at least 2 values in enum, got '{:?}'", vec);
-                }
+        }
+        metadata::Type::Duration => {
+            if native_type_name != "Duration" {
+                abort_call_site!("Expected Duration, got '{}'", native_type_name);
             }
-            metadata::Type::Complex => abort_call_site!("Complex value not supported for now"),
         }
+        metadata::Type::Timestamp => {
+            if native_type_name != "SystemTime" {
+                abort_call_site!("Expected SystemTime, got '{}'", native_type_name);
+            }
+        }
+        metadata::Type::Char => {
+            if native_type_name != "char" {
+                abort_call_site!("Expected char, got '{}'", native_type_name);
+            }
+        }
+        metadata::Type::Color => {
+            if native_type_name != "Rgb" {
+                abort_call_site!("Expected Rgb, got '{}'", native_type_name);
+            }
+        }
+        metadata::Type::Enum(vec) => {
+            if native_type_name != "String" {
+                abort_call_site!("Expected String, got '{}'", native_type_name);
+            }
 
-        return provided_type.clone();
-    } else {
-        let typ = match native_type_name.as_str() {
-            "f64" => metadata::Type::Float,
-            "bool" => metadata::Type::Bool,
-            "String" => metadata::Type::Text, // If only String default to Text (drop Enum)
-            unsupported => {
-                abort_call_site!("Unable to deduce type with native type '{}'", unsupported)
+            if vec.len() < 2 {
+                abort_call_site!("Expected at least 2 values in enum, got '{:?}'", vec);
             }
+        }
+        metadata::Type::List(_)
+        | metadata::Type::Nullable(_)
+        | metadata::Type::Object(_)
+        | metadata::Type::Complex(_) => {
+            unreachable!()
+        }
+    }
+}
+
+// Complex types schemas are taken from the MylifeValue implementation of the native type
+pub fn register_complex_types(
+    native_type: &syn::Type,
+    provided_type: &Option<attributes::Type>,
+) -> TokenStream {
+    match provided_type {
+        Some(provided_type) => register_complex_type(native_type, provided_type.value()),
+        None => quote! {},
+    }
+}
+
+fn register_complex_type(native_type: &syn::Type, provided_type: &metadata::Type) -> TokenStream {
+    match provided_type {
+        metadata::Type::Complex(name) => quote! {
+            builder.register_complex_type(
+                #name,
+                core_plugin_runtime::macros_backend::complex_type_fields(
+                    #name,
+                    <#native_type as core_plugin_runtime::runtime::MylifeValue>::value_type()
+                )
+            );
+        },
+        metadata::Type::List(item_type) => match get_generic_arg_type(native_type, "Vec") {
+            Some(native_item_type) => register_complex_type(native_item_type, item_type),
+            None => quote! {},
+        },
+        metadata::Type::Nullable(value_type) => match get_generic_arg_type(native_type, "Option") {
+            Some(native_value_type) => register_complex_type(native_value_type, value_type),
+            None => quote! {},
+        },
+        _ => quote! {},
+    }
+}
+
+fn deduce_type(native_type: &syn::Type) -> TokenStream {
+    if let Some(native_item_type) = get_generic_arg_type(native_type, "Vec") {
+        let item_type = deduce_type(native_item_type);
+        return quote! { core_plugin_runtime::metadata::Type::List(std::boxed::Box::new(#item_type)) };
+    }
+
+    if let Some(native_value_type) = get_generic_arg_type(native_type, "Option") {
+        let value_type = deduce_type(native_value_type);
+        return quote! { core_plugin_runtime::metadata::Type::Nullable(std::boxed::Box::new(#value_type)) };
+    }
+
+    let native_type_name = get_native_type_name(native_type);
+
+    // Small integers: deduce range from native bounds
+    if let ("i8" | "i16" | "i32" | "u8" | "u16" | "u32", Some((min, max))) = (
+        native_type_name.as_str(),
+        get_integer_bounds(&native_type_name),
+    ) {
+        let r#type = metadata::Type::Range(min as i64, max as i64);
+        return attributes::Type::new(r#type).to_token_stream();
+    }
+
+    let r#type = match native_type_name.as_str() {
+        "f64" | "f32" => metadata::Type::Float,
+        "bool" => metadata::Type::Bool,
+        "Duration" => metadata::Type::Duration,
+        "SystemTime" => metadata::Type::Timestamp,
+        "char" => metadata::Type::Char,
+        "Rgb" => metadata::Type::Color,
+        "String" => metadata::Type::Text, // If only String default to Text (drop Enum)
+        "i64" | "u64" => abort_call_site!(
+            "Unable to deduce type with native type '{}', provide r#type = \"range[min;max]\"",
+            native_type_name
+        ),
+        "usize" | "isize" | "i128" | "u128" | "str" => abort_call_site!(
+            "Unsupported native type '{}', supported types: {}",
+            native_type_name,
+            SUPPORTED_TYPES
+        ),
+        // Other types are expected to #[derive(MylifeValue)]
+        _ => {
+            return quote! { <#native_type as core_plugin_runtime::runtime::MylifeValue>::value_type() }
+        }
+    };
+
+    attributes::Type::new(r#type).to_token_stream()
+}
+
+// Reject what can never be converted from a Value with an error on the argument itself,
+// instead of a trait bound error deep in the generated executor
+pub fn check_action_arg_type(native_type: &syn::Type) -> TokenStream {
+    let unsupported = || -> ! {
+        abort!(
+            native_type.span(),
+            "unsupported action argument type: {}",
+            native_type.to_token_stream();
+            help = "supported types: {}", SUPPORTED_TYPES
+        )
+    };
+
+    let path = match native_type {
+        syn::Type::Path(path) if path.qself.is_none() => path,
+        _ => unsupported(),
+    };
+
+    let seg = path.path.segments.last().unwrap_or_else(|| unsupported());
+    let name = seg.ident.to_string();
+
+    if name == "Vec" || name == "Option" {
+        return match get_generic_arg_type(native_type, &name) {
+            Some(arg_type) => check_action_arg_type(arg_type),
+            None => unsupported(),
         };
+    }
+
+    if !seg.arguments.is_empty() {
+        unsupported();
+    }
 
-        return attributes::Type::new(typ);
+    if get_integer_bounds(&name).is_some() {
+        return quote! {};
+    }
+
+    match name.as_str() {
+        "f64" | "f32" | "bool" | "Duration" | "SystemTime" | "String" | "char" | "Rgb" => {
+            quote! {}
+        }
+        "usize" | "isize" | "i128" | "u128" | "str" => unsupported(),
+        // Other types are expected to #[derive(MylifeValue)], checked on the argument
+        _ => quote_spanned! {native_type.span()=>
+            const _: fn() = || {
+                fn assert_mylife_value<T: core_plugin_runtime::runtime::MylifeValue>() {}
+                assert_mylife_value::<#native_type>();
+            };
+        },
     }
 }
 
+const SUPPORTED_TYPES: &str = "bool, String, f32, f64, i8, i16, i32, i64, u8, u16, u32, u64, Duration, SystemTime, char, Rgb, Vec<T>, Option<T>, #[derive(MylifeValue)] struct";
+
+fn get_integer_bounds(native_type_name: &str) -> Option<(i128, i128)> {
+    match native_type_name {
+        "i8" => Some((i8::MIN as i128, i8::MAX as i128)),
+        "i16" => Some((i16::MIN as i128, i16::MAX as i128)),
+        "i32" => Some((i32::MIN as i128, i32::MAX as i128)),
+        "i64" => Some((i64::MIN as i128, i64::MAX as i128)),
+        "u8" => Some((0, u8::MAX as i128)),
+        "u16" => Some((0, u16::MAX as i128)),
+        "u32" => Some((0, u32::MAX as i128)),
+        "u64" => Some((0, u64::MAX as i128)),
+        _ => None,
+    }
+}
+
+// Option<u32> => get u32 (nullable configs)
+pub fn get_option_type(native_type: &syn::Type) -> Option<&syn::Type> {
+    get_generic_arg_type(native_type, "Option")
+}
+
+// Vec<bool> => get bool
+fn get_generic_arg_type<'a>(
+    native_type: &'a syn::Type,
+    generic_name: &str,
+) -> Option<&'a syn::Type> {
+    if let syn::Type::Path(path) = native_type {
+        let seg = path.path.segments.last()?;
+        if seg.ident != generic_name {
+            return None;
+        }
+
+        if let syn::PathArguments::AngleBracketed(args) = &seg.arguments {
+            if let Some(syn::GenericArgument::Type(arg_type)) = args.args.first() {
+                return Some(arg_type);
+            }
+        }
+    }
+
+    None
+}
+
 fn get_native_type_name(native_type: &syn::Type) -> String {
     if let syn::Type::Path(path) = native_type {
         if let Some(ident) = path.path.get_ident() {
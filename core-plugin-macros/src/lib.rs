@@ -1,6 +1,7 @@
 use std::slice;
 
 use attributes::ConfigType;
+use core_plugin_runtime::metadata;
 use darling::{FromAttributes, FromDeriveInput, FromField, ToTokens};
 use proc_macro2::TokenStream;
 use proc_macro_error::{abort, abort_call_site, emit_warning, proc_macro_error};
@@ -12,7 +13,10 @@ mod helpers;
 // TODO: path.get_ident() does not work if `core_plugin_runtime::Toto`
 // TODO: abort_call_site => find real call site
 
-#[proc_macro_derive(MylifePlugin, attributes(mylife_plugin, mylife_config, mylife_state))]
+#[proc_macro_derive(
+    MylifePlugin,
+    attributes(mylife_plugin, mylife_config, mylife_state, mylife_event)
+)]
 #[proc_macro_error]
 pub fn derive_mylife_plugin(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input: syn::DeriveInput = syn::parse_macro_input!(input);
@@ -55,6 +59,14 @@ pub fn derive_mylife_plugin(input: proc_macro::TokenStream) -> proc_macro::Token
                     };
                 }
 
+                "mylife_event" => {
+                    if let Some(attr_event) =
+                        errors.handle(attributes::MylifeEvent::from_field(field))
+                    {
+                        streams.push(process_event(name, &attr_event));
+                    }
+                }
+
                 unknown => {
                     emit_warning!(attr_ident, "Ignored attribute : {}", unknown);
                 }
@@ -98,6 +110,109 @@ pub fn derive_mylife_plugin(input: proc_macro::TokenStream) -> proc_macro::Token
     gen.into()
 }
 
+#[proc_macro_derive(MylifeValue, attributes(mylife_value))]
+#[proc_macro_error]
+pub fn derive_mylife_value(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input: syn::DeriveInput = syn::parse_macro_input!(input);
+    let mut errors = darling::Error::accumulator();
+
+    let attr_value = errors.handle(attributes::MylifeValue::from_derive_input(&input));
+
+    let fields = if let syn::Data::Struct(data) = &input.data {
+        &data.fields
+    } else {
+        abort_call_site!("Unexpected parsing error (expected struct)");
+    };
+
+    let attr_fields: Vec<attributes::MylifeValueField> = fields
+        .iter()
+        .filter_map(|field| errors.handle(attributes::MylifeValueField::from_field(field)))
+        .collect();
+
+    match errors.finish() {
+        Ok(_) => (),
+        Err(err) => {
+            return err.write_errors().into();
+        }
+    }
+
+    let name = &attr_value.unwrap().ident;
+    let native_type = name.to_string();
+    let mut field_types = Vec::new();
+    let mut field_setters = Vec::new();
+    let mut field_getters = Vec::new();
+
+    for attr in attr_fields.iter() {
+        let target_ident = attr
+            .ident
+            .as_ref()
+            .expect("Unexpected unnamed value member");
+        let var_name = helpers::make_member_name(target_ident);
+        let field_name = attr.name.as_ref().unwrap_or(&var_name);
+        let r#type = helpers::get_type(&attr.ty, &attr.r#type);
+
+        field_types.push(quote! { (#field_name.to_string(), #r#type) });
+
+        field_setters.push(quote! {
+            fields.insert(
+                #field_name.to_string(),
+                value.#target_ident.typed_into(core_plugin_runtime::macros_backend::object_field_type(field_types, #field_name)),
+            );
+        });
+
+        field_getters.push(quote! {
+            #target_ident: fields
+                .remove(#field_name)
+                .unwrap()
+                .typed_try_into(core_plugin_runtime::macros_backend::object_field_type(field_types, #field_name))?,
+        });
+    }
+
+    let gen = quote! {
+        impl core_plugin_runtime::runtime::MylifeValue for #name {
+            fn value_type() -> core_plugin_runtime::metadata::Type {
+                core_plugin_runtime::metadata::Type::Object(vec![#(#field_types),*])
+            }
+        }
+
+        impl core_plugin_runtime::runtime::TypedFrom<#name> for core_plugin_runtime::runtime::Value {
+            fn typed_from(value: #name, ty: &core_plugin_runtime::metadata::Type) -> Self {
+                use core_plugin_runtime::runtime::{MylifeValue, TypedInto};
+
+                let ty = &core_plugin_runtime::macros_backend::object_type(ty, #name::value_type);
+                let field_types = core_plugin_runtime::macros_backend::object_field_types(#native_type, ty);
+                let mut fields = std::collections::BTreeMap::new();
+                #(#field_setters)*
+
+                core_plugin_runtime::runtime::Value::Object(fields)
+            }
+        }
+
+        impl core_plugin_runtime::runtime::TypedTryFrom<core_plugin_runtime::runtime::Value> for #name {
+            type Error = core_plugin_runtime::runtime::ValueConversionError;
+
+            fn typed_try_from(
+                value: core_plugin_runtime::runtime::Value,
+                ty: &core_plugin_runtime::metadata::Type,
+            ) -> std::result::Result<Self, Self::Error> {
+                use core_plugin_runtime::runtime::{MylifeValue, TypedTryInto};
+
+                let ty = &core_plugin_runtime::macros_backend::object_type(ty, #name::value_type);
+                let mut fields = core_plugin_runtime::macros_backend::object_fields(#native_type, value, ty)?;
+                let field_types = core_plugin_runtime::macros_backend::object_field_types(#native_type, ty);
+
+                std::result::Result::Ok(#name {
+                    #(#field_getters)*
+                })
+            }
+        }
+    };
+
+    helpers::dump_output(&gen);
+
+    gen.into()
+}
+
 #[proc_macro_attribute]
 #[proc_macro_error]
 pub fn mylife_actions(
@@ -124,6 +239,12 @@ pub fn mylife_actions(
 
     for item in input.items.iter_mut() {
         if let syn::ImplItem::Method(method) = item {
+            let action_count = method
+                .attrs
+                .iter()
+                .filter(|attr| attr.path.is_ident("mylife_action"))
+                .count();
+
             method.attrs.retain(|attr| {
                 let attr_ident = attr.path.get_ident().unwrap();
                 match attr_ident.to_string().as_str() {
@@ -132,7 +253,12 @@ pub fn mylife_actions(
                             slice::from_ref(attr),
                         )) {
                             Some(attr_action) => {
-                                streams.push(process_action(name, &method.sig, &attr_action));
+                                streams.push(process_action(
+                                    name,
+                                    &method.sig,
+                                    &attr_action,
+                                    action_count > 1,
+                                ));
                             }
                             None => (),
                         };
@@ -174,13 +300,58 @@ fn process_plugin(name: &syn::Ident, attr: &attributes::MylifePlugin) -> TokenSt
     let name = attr.name.as_ref().unwrap_or(&struct_name);
     let description = attributes::option_string_to_tokens(&attr.description);
     let usage = &attr.usage;
+    let tags = attr.tags.value();
+    let set_category = if attr.category.is_none() && tags.is_empty() {
+        quote! {}
+    } else {
+        let category = match &attr.category {
+            Some(category) => quote! { Some(#category) },
+            None => quote! { None },
+        };
+
+        quote! { builder.set_plugin_category(#category, &[#(#tags),*]); }
+    };
+
+    let set_id = if let Some(id) = &attr.id {
+        quote! { builder.set_plugin_id(#id); }
+    } else {
+        quote! {}
+    };
+
+    let set_version = if let Some(version) = &attr.version {
+        quote! { builder.set_plugin_version(#version); }
+    } else {
+        quote! {}
+    };
+
+    let set_deprecated = if let Some(message) = &attr.deprecated {
+        quote! { builder.set_plugin_deprecated(#message); }
+    } else {
+        quote! {}
+    };
 
     quote! {
         builder.set_plugin(#name, #description, #usage);
+        #set_id
+        #set_version
+        #set_category
+        #set_deprecated
+    }
+}
+
+fn set_member_deprecated(name: &str, deprecated: &Option<String>) -> TokenStream {
+    if let Some(message) = deprecated {
+        quote! { builder.set_member_deprecated(#name, #message); }
+    } else {
+        quote! {}
     }
 }
 
 fn process_config(plugin_name: &syn::Ident, attr: &attributes::MylifeConfig) -> TokenStream {
+    if attr.extra.is_present() {
+        return process_extra_config(plugin_name, attr);
+    }
+
     let var_name = helpers::make_member_name(
         attr.ident
             .as_ref()
@@ -189,7 +360,10 @@ fn process_config(plugin_name: &syn::Ident, attr: &attributes::MylifeConfig) ->
 
     let name = attr.name.as_ref().unwrap_or(&var_name);
     let description = attributes::option_string_to_tokens(&attr.description);
-    let r#type = ConfigType::try_from(&attr.ty).unwrap();
+    // Option<T> fields are nullable configs of type T
+    let nullable_type = helpers::get_option_type(&attr.ty);
+    let value_type = nullable_type.unwrap_or(&attr.ty);
+    let r#type = ConfigType::try_from(value_type).unwrap_or_else(|err| abort_call_site!("{}", err));
     let target_ident = &attr.ident;
 
     let setter = quote! {
@@ -210,6 +384,76 @@ fn process_config(plugin_name: &syn::Ident, attr: &attributes::MylifeConfig) ->
         }
     }
 
+    let set_pattern = if let Some(pattern) = &attr.pattern {
+        if r#type != ConfigType::String {
+            abort_call_site!(
+                "Pattern provided for config '{}' which is not a String",
+                name
+            );
+        }
+
+        quote! { builder.set_config_pattern(#name, #pattern); }
+    } else {
+        quote! {}
+    };
+
+    let set_range = match &attr.range {
+        Some(attributes::IntegerRange(min, max)) => {
+            if r#type != ConfigType::Integer {
+                abort_call_site!(
+                    "Range provided for config '{}' which is not an integer",
+                    name
+                );
+            }
+
+            helpers::check_type(value_type, &metadata::Type::Range(*min, *max));
+
+            let clamp = attr.clamp.is_present();
+            quote! { builder.set_config_range(#name, #min, #max, #clamp); }
+        }
+        None => {
+            if attr.clamp.is_present() {
+                abort_call_site!("Clamp provided for config '{}' without range", name);
+            }
+
+            quote! {}
+        }
+    };
+
+    let set_unit = if let Some(unit) = &attr.unit {
+        quote! { builder.set_config_unit(#name, #unit); }
+    } else {
+        quote! {}
+    };
+
+    let set_nullable = if nullable_type.is_some() {
+        quote! { builder.set_config_nullable(#name); }
+    } else {
+        quote! {}
+    };
+
+    let set_secret = if attr.secret.is_present() {
+        quote! { builder.set_config_secret(#name); }
+    } else {
+        quote! {}
+    };
+
+    let set_deprecated = if let Some(message) = &attr.deprecated {
+        quote! { builder.set_config_deprecated(#name, #message); }
+    } else {
+        quote! {}
+    };
+
+    let expose_as_state = if attr.expose_as_state.is_present() {
+        if attr.secret.is_present() {
+            abort_call_site!("Secret config '{}' cannot be exposed as state", name);
+        }
+
+        process_config_state(plugin_name, attr, name, &description)
+    } else {
+        quote! {}
+    };
+
     quote! {
         builder.add_config(
             #name,
@@ -217,6 +461,94 @@ fn process_config(plugin_name: &syn::Ident, attr: &attributes::MylifeConfig) ->
             #r#type,
             #setter
         );
+
+        #set_pattern
+        #set_range
+        #set_unit
+        #set_nullable
+        #set_secret
+        #set_deprecated
+        #expose_as_state
+    }
+}
+
+fn process_config_state(
+    plugin_name: &syn::Ident,
+    attr: &attributes::MylifeConfig,
+    name: &str,
+    description: &TokenStream,
+) -> TokenStream {
+    if let "i64" | "u64" = attr.ty.to_token_stream().to_string().as_str() {
+        abort_call_site!(
+            "Config '{}' cannot be exposed as state: i64 and u64 have no bounded range",
+            name
+        );
+    }
+
+    let r#type = helpers::get_type(&attr.ty, &None);
+    let set_unit = if let Some(unit) = &attr.unit {
+        quote! { builder.set_state_unit(#name, #unit); }
+    } else {
+        quote! {}
+    };
+
+    let target_ident = &attr.ident;
+
+    let register = quote! {
+        |_target: &mut #plugin_name, _listener: std::boxed::Box<dyn std::ops::Fn(core_plugin_runtime::runtime::Value)>| {}
+    };
+
+    let getter = quote! {
+        |target: &#plugin_name| -> core_plugin_runtime::runtime::Value {
+            use core_plugin_runtime::runtime::TypedInto;
+
+            lazy_static::lazy_static! {
+                static ref RUNTIME_TYPE: core_plugin_runtime::metadata::Type = #r#type;
+            }
+
+            target.#target_ident.clone().typed_into(&RUNTIME_TYPE)
+        }
+    };
+
+    quote! {
+        builder.add_state(
+            #name,
+            #description,
+            #r#type,
+            false,
+            #register,
+            #getter
+        );
+
+        builder.set_state_from_config(#name);
+        #set_unit
+    }
+}
+
+fn process_extra_config(plugin_name: &syn::Ident, attr: &attributes::MylifeConfig) -> TokenStream {
+    if attr.name.is_some()
+        || attr.r#type.is_some()
+        || attr.pattern.is_some()
+        || attr.range.is_some()
+        || attr.clamp.is_present()
+        || attr.unit.is_some()
+        || attr.expose_as_state.is_present()
+        || attr.secret.is_present()
+        || attr.deprecated.is_some()
+    {
+        abort_call_site!(
+            "Extra config does not support name, type, pattern, range, clamp, unit, expose_as_state, secret or deprecated"
+        );
+    }
+
+    let target_ident = &attr.ident;
+
+    quote! {
+        builder.set_extra_config(
+            |target: &mut #plugin_name, config: core_plugin_runtime::runtime::Config| {
+                target.#target_ident = config;
+            }
+        );
     }
 }
 
@@ -229,10 +561,128 @@ fn process_state(plugin_name: &syn::Ident, attr: &attributes::MylifeState) -> To
 
     let name = attr.name.as_ref().unwrap_or(&var_name);
     let description = attributes::option_string_to_tokens(&attr.description);
-    let var_type = get_state_type(&attr.ty);
+    let var_type = get_wrapped_type(&attr.ty, "State");
     let r#type = helpers::get_type(var_type, &attr.r#type);
+    let register_complex_types = helpers::register_complex_types(var_type, &attr.r#type);
+    let derived = attr.derived.is_present();
+    let mut actions = attr.actions.value().clone();
     let target_ident = &attr.ident;
 
+    let set_latched = if attr.latched.is_present() {
+        let is_bool = quote! { #var_type }.to_string() == "bool"
+            && matches!(
+                attr.r#type.as_ref().map(|r#type| r#type.value()),
+                None | Some(metadata::Type::Bool)
+            );
+        if !is_bool {
+            abort_call_site!("latched provided for state '{}' which is not bool", name);
+        }
+
+        let mut chars = name.chars();
+        let clear_name = match chars.next() {
+            Some(first) => format!("clear{}{}", first.to_uppercase(), chars.as_str()),
+            None => String::from("clear"),
+        };
+        let clear_description = format!("Acknowledges latched state '{}'", name);
+        actions.push(clear_name.clone());
+
+        quote! {
+            builder.set_state_latched(#name, |target: &mut #plugin_name| {
+                target.#target_ident.runtime_latched();
+            });
+
+            // triggered on true, like a button
+            builder.add_action(
+                #clear_name,
+                Some(#clear_description),
+                core_plugin_runtime::metadata::Type::Bool,
+                None,
+                |target: &mut #plugin_name, _name: &str, arg: core_plugin_runtime::runtime::Value| {
+                    if arg == core_plugin_runtime::runtime::Value::Bool(true) {
+                        target.#target_ident.acknowledge();
+                    }
+
+                    Ok(None)
+                }
+            );
+        }
+    } else {
+        quote! {}
+    };
+
+    let link_actions = if actions.is_empty() {
+        quote! {}
+    } else {
+        quote! { builder.link_state_actions(#name, &[#(#actions),*]); }
+    };
+
+    let set_unit = if let Some(unit) = &attr.unit {
+        quote! { builder.set_state_unit(#name, #unit); }
+    } else {
+        quote! {}
+    };
+
+    let set_clamp = if attr.clamp.is_present() {
+        match attr.r#type.as_ref().map(|r#type| r#type.value()) {
+            Some(metadata::Type::Range(min, max)) => quote! {
+                builder.set_state_clamp(#name, |target: &mut #plugin_name| {
+                    target.#target_ident.runtime_clamp(|value: #var_type| value.clamp(#min as #var_type, #max as #var_type));
+                });
+            },
+            _ => abort_call_site!(
+                "Clamp provided for state '{}' without r#type = \"range[min;max]\"",
+                name
+            ),
+        }
+    } else {
+        quote! {}
+    };
+
+    let set_deprecated = set_member_deprecated(name, &attr.deprecated);
+
+    let set_polled = match attr.mode.as_deref() {
+        None | Some("push") => quote! {},
+        Some("polled") => quote! {
+            builder.set_state_polled(
+                #name,
+                |target: &mut #plugin_name, changed: std::sync::Arc<std::sync::atomic::AtomicBool>| {
+                    target.#target_ident.runtime_polled(changed);
+                }
+            );
+        },
+        Some(mode) => abort_call_site!(
+            "Unknown mode '{}' for state '{}': expected \"push\" or \"polled\"",
+            mode,
+            name
+        ),
+    };
+
+    let set_history = match attr.history {
+        None => quote! {},
+        Some(0) => abort_call_site!("history provided for state '{}' must be at least 1", name),
+        Some(history) => quote! {
+            builder.set_state_history(
+                #name,
+                |target: &mut #plugin_name| {
+                    target.#target_ident.runtime_history(#history);
+                },
+                |target: &#plugin_name| -> std::vec::Vec<(std::time::SystemTime, core_plugin_runtime::runtime::Value)> {
+                    use core_plugin_runtime::runtime::TypedInto;
+
+                    lazy_static::lazy_static! {
+                        static ref RUNTIME_TYPE: core_plugin_runtime::metadata::Type = #r#type;
+                    }
+
+                    target
+                        .#target_ident
+                        .history()
+                        .map(|(time, native_value)| (*time, native_value.clone().typed_into(&RUNTIME_TYPE)))
+                        .collect()
+                }
+            );
+        },
+    };
+
     let register = quote! {
         |target: &mut #plugin_name, listener: std::boxed::Box<dyn std::ops::Fn(core_plugin_runtime::runtime::Value)>| {
             let runtime_type: core_plugin_runtime::metadata::Type = #r#type;
@@ -258,9 +708,55 @@ fn process_state(plugin_name: &syn::Ident, attr: &attributes::MylifeState) -> To
             #name,
             #description,
             #r#type,
+            #derived,
             #register,
             #getter
         );
+
+        #register_complex_types
+        #link_actions
+        #set_clamp
+        #set_unit
+        #set_history
+        #set_polled
+        #set_latched
+        #set_deprecated
+    }
+}
+
+fn process_event(plugin_name: &syn::Ident, attr: &attributes::MylifeEvent) -> TokenStream {
+    let var_name = helpers::make_member_name(
+        attr.ident
+            .as_ref()
+            .expect("Unexpected unnamed event member"),
+    );
+
+    let name = attr.name.as_ref().unwrap_or(&var_name);
+    let description = attributes::option_string_to_tokens(&attr.description);
+    let var_type = get_wrapped_type(&attr.ty, "Event");
+    let r#type = helpers::get_type(var_type, &attr.r#type);
+    let register_complex_types = helpers::register_complex_types(var_type, &attr.r#type);
+    let target_ident = &attr.ident;
+
+    let register = quote! {
+        |target: &mut #plugin_name, listener: std::boxed::Box<dyn std::ops::Fn(core_plugin_runtime::runtime::Value)>| {
+            let runtime_type: core_plugin_runtime::metadata::Type = #r#type;
+            target.#target_ident.runtime_register(listener, runtime_type);
+        }
+    };
+
+    let set_deprecated = set_member_deprecated(name, &attr.deprecated);
+
+    quote! {
+        builder.add_event(
+            #name,
+            #description,
+            #r#type,
+            #register
+        );
+
+        #register_complex_types
+        #set_deprecated
     }
 }
 
@@ -268,14 +764,83 @@ fn process_action(
     plugin_name: &syn::Ident,
     sig: &syn::Signature,
     attr: &attributes::MylifeAction,
+    shared: bool,
 ) -> TokenStream {
     let var_name = helpers::make_member_name(&sig.ident);
 
+    if shared && attr.name.is_none() {
+        abort!(
+            sig.ident.span(),
+            "name must be provided when multiple mylife_action are set on the same method"
+        );
+    }
+
     let name = attr.name.as_ref().unwrap_or(&var_name);
     let description = attributes::option_string_to_tokens(&attr.description);
-    let var_type = &get_action_type(sig);
-    let r#type = helpers::get_type(var_type, &attr.r#type);
+    let (has_name_arg, args) = get_action_args(sig);
     let target_ident = &sig.ident;
+    let native_type = target_ident.to_string();
+    let mut check_arg_type = TokenStream::new();
+    let mut register_complex_types = TokenStream::new();
+
+    // single argument: the action value is the argument,
+    // multiple arguments: the action value is an object with one field per argument (a list is accepted by position)
+    let (r#type, convert_args, call_args) = if let [(_, var_type)] = args.as_slice() {
+        check_arg_type.extend(helpers::check_action_arg_type(var_type));
+        register_complex_types.extend(helpers::register_complex_types(var_type, &attr.r#type));
+
+        (
+            helpers::get_type(var_type, &attr.r#type),
+            quote! { let value: #var_type = arg.clone().typed_try_into(&RUNTIME_TYPE)?; },
+            quote! { value },
+        )
+    } else {
+        if attr.r#type.is_some() {
+            abort!(
+                sig.ident.span(),
+                "r#type cannot be provided on an action with multiple arguments, argument types are deduced"
+            );
+        }
+
+        let mut field_types = Vec::new();
+        let mut field_getters = Vec::new();
+        let mut call_args = Vec::new();
+
+        for (index, (arg_ident, var_type)) in args.iter().enumerate() {
+            check_arg_type.extend(helpers::check_action_arg_type(var_type));
+            register_complex_types.extend(helpers::register_complex_types(var_type, &None));
+
+            let field_name = helpers::make_member_name(arg_ident);
+            let field_type = helpers::get_type(var_type, &None);
+            let value_ident = format_ident!("value_{}", index);
+
+            field_types.push(quote! { (#field_name.to_string(), #field_type) });
+            field_getters.push(quote! {
+                let #value_ident: #var_type = fields
+                    .remove(#field_name)
+                    .unwrap()
+                    .typed_try_into(core_plugin_runtime::macros_backend::object_field_type(field_types, #field_name))?;
+            });
+            call_args.push(value_ident);
+        }
+
+        (
+            quote! { core_plugin_runtime::metadata::Type::Object(vec![#(#field_types),*]) },
+            quote! {
+                let value = core_plugin_runtime::macros_backend::action_args(arg.clone(), &RUNTIME_TYPE);
+                let mut fields = core_plugin_runtime::macros_backend::object_fields(#native_type, value, &RUNTIME_TYPE)?;
+                let field_types = core_plugin_runtime::macros_backend::object_field_types(#native_type, &RUNTIME_TYPE);
+                #(#field_getters)*
+            },
+            quote! { #(#call_args),* },
+        )
+    };
+
+    let name_arg = if has_name_arg {
+        quote! { name, }
+    } else {
+        quote! {}
+    };
 
     let has_output = match &sig.output {
         syn::ReturnType::Default => false,
@@ -288,39 +853,162 @@ fn process_action(
         quote! {}
     };
 
+    let native_result_type = get_action_result_type(sig);
+    let result_type = native_result_type
+        .as_ref()
+        .map(|native_result_type| helpers::get_type(native_result_type, &attr.result_type));
+
+    if let Some(native_result_type) = &native_result_type {
+        register_complex_types.extend(helpers::register_complex_types(
+            native_result_type,
+            &attr.result_type,
+        ));
+    }
+
+    if result_type.is_none() && attr.result_type.is_some() {
+        abort!(
+            sig.ident.span(),
+            "result_type provided but action does not return Result<value, error>"
+        );
+    }
+
+    let (result_type_tokens, call) = if let Some(result_type) = &result_type {
+        (
+            quote! { Some(#result_type) },
+            quote! {
+                lazy_static::lazy_static! {
+                    static ref RESULT_TYPE: core_plugin_runtime::metadata::Type = #result_type;
+                }
+
+                let result = target.#target_ident(#name_arg #call_args)?;
+                std::result::Result::Ok(Some(result.typed_into(&RESULT_TYPE)))
+            },
+        )
+    } else {
+        (
+            quote! { None },
+            quote! {
+                target.#target_ident(#name_arg #call_args)#end_ident;
+                std::result::Result::Ok(None)
+            },
+        )
+    };
+
+    let set_retry = match (attr.retries, &attr.backoff) {
+        (None, None) => quote! {},
+        (None, Some(_)) => abort!(sig.ident.span(), "backoff provided without retries"),
+        (Some(retries), backoff) => {
+            let backoff = match backoff {
+                Some(backoff) => core_plugin_runtime::runtime::parse_duration(backoff)
+                    .unwrap_or_else(|err| abort!(sig.ident.span(), "{}", err)),
+                None => std::time::Duration::ZERO,
+            };
+            let backoff_millis = backoff.as_millis() as u64;
+
+            quote! { builder.set_action_retry(#name, #retries, std::time::Duration::from_millis(#backoff_millis)); }
+        }
+    };
+
+    let set_rate_limit = if let Some(rate_limit) = &attr.rate_limit {
+        let rate_limit: metadata::RateLimit = rate_limit
+            .parse()
+            .unwrap_or_else(|err| abort!(sig.ident.span(), "{}", err));
+        let count = rate_limit.count();
+        let period_millis = rate_limit.period().as_millis() as u64;
+
+        quote! { builder.set_action_rate_limit(#name, #count, std::time::Duration::from_millis(#period_millis)); }
+    } else {
+        quote! {}
+    };
+
+    let set_default_arg = if let Some(default_arg) = &attr.default_arg {
+        quote! { builder.set_action_default_arg(#name, #default_arg); }
+    } else {
+        quote! {}
+    };
+
+    let set_deprecated = set_member_deprecated(name, &attr.deprecated);
+
+    let set_guard = if let Some(guard) = &attr.guard {
+        let guard: syn::Ident = syn::parse_str(guard)
+            .unwrap_or_else(|_| abort!(sig.ident.span(), "Invalid guard method name '{}'", guard));
+
+        quote! { builder.set_action_guard(#name, |target: &#plugin_name| target.#guard()); }
+    } else {
+        quote! {}
+    };
+
     let executor = quote! {
-        |target: &mut #plugin_name, arg: core_plugin_runtime::runtime::Value| -> std::result::Result<(), Box<dyn std::error::Error>> {
-            use core_plugin_runtime::runtime::TypedTryInto;
+        |target: &mut #plugin_name, name: &str, arg: core_plugin_runtime::runtime::Value| -> std::result::Result<Option<core_plugin_runtime::runtime::Value>, Box<dyn std::error::Error>> {
+            use core_plugin_runtime::runtime::{TypedInto, TypedTryInto};
 
             lazy_static::lazy_static! {
                 static ref RUNTIME_TYPE: core_plugin_runtime::metadata::Type = #r#type;
             }
 
-            let value: #var_type = arg.clone().typed_try_into(&RUNTIME_TYPE)?;
-            target.#target_ident(value)#end_ident;
-
-            std::result::Result::Ok(())
+            #convert_args
+            #call
         }
     };
 
     quote! {
+        #check_arg_type
+
         builder.add_action(
             #name,
             #description,
             #r#type,
+            #result_type_tokens,
             #executor
         );
+
+        #register_complex_types
+        #set_default_arg
+        #set_retry
+        #set_guard
+        #set_rate_limit
+        #set_deprecated
+    }
+}
+
+// fn toto(&mut self, arg: bool) -> Result<i64, Box<dyn Error>> => get i64
+// fn toto(&mut self, arg: bool) -> Result<(), Box<dyn Error>> => get None
+fn get_action_result_type(sig: &syn::Signature) -> Option<syn::Type> {
+    if let syn::ReturnType::Type(_, output_type) = &sig.output {
+        if let syn::Type::Path(path) = output_type.as_ref() {
+            let seg = path.path.segments.last()?;
+            if seg.ident != "Result" {
+                return None;
+            }
+
+            if let syn::PathArguments::AngleBracketed(args) = &seg.arguments {
+                if let Some(syn::GenericArgument::Type(result_type)) = args.args.first() {
+                    if let syn::Type::Tuple(tuple) = result_type {
+                        if tuple.elems.is_empty() {
+                            return None;
+                        }
+                    }
+
+                    return Some(result_type.clone());
+                }
+            }
+        }
     }
+
+    None
 }
 
 // State<bool> => get bool
-fn get_state_type(var_type: &syn::Type) -> &syn::Type {
+// State<type> or Event<type> => type
+fn get_wrapped_type<'a>(var_type: &'a syn::Type, wrapper: &str) -> &'a syn::Type {
     if let syn::Type::Path(path) = var_type {
         let seg = path.path.segments.last().unwrap();
-        if seg.ident.to_string() != "State" {
+        if seg.ident != wrapper {
             abort!(
                 seg.ident.span(),
-                "mylife_state variable must be of type State"
+                "mylife_{} variable must be of type {}",
+                wrapper.to_lowercase(),
+                wrapper
             );
         }
 
@@ -338,25 +1026,63 @@ fn get_state_type(var_type: &syn::Type) -> &syn::Type {
     }
 
     abort_call_site!(
-        "Wrong value type '{}', expected 'State<type>'",
-        var_type.to_token_stream()
+        "Wrong value type '{}', expected '{}<type>'",
+        var_type.to_token_stream(),
+        wrapper
     );
 }
 
-// fn toto(&mut self, arg: bool) => get bool
-fn get_action_type(sig: &syn::Signature) -> syn::Type {
-    if sig.inputs.len() != 2 {
-        abort!(sig.ident.span(), "Invalid method args");
+// fn toto(&mut self, arg: bool) => get (false, [(arg, bool)])
+// fn toto(&mut self, name: &str, arg: bool) => get (true, [(arg, bool)])
+// fn toto(&mut self, r: u8, g: u8) => get (false, [(r, u8), (g, u8)])
+fn get_action_args(sig: &syn::Signature) -> (bool, Vec<(syn::Ident, syn::Type)>) {
+    if !matches!(sig.inputs.first(), Some(syn::FnArg::Receiver(_))) {
+        abort!(
+            sig.ident.span(),
+            "Invalid method args: action must take &mut self";
+            help = "expected fn {}(&mut self, arg: T)", sig.ident
+        );
     }
 
-    if let syn::FnArg::Receiver(_) = &sig.inputs[0] {
-    } else {
-        abort!(sig.ident.span(), "Invalid method args");
+    let has_name_arg = sig.inputs.len() > 2 && is_str_arg(&sig.inputs[1]);
+    let skip = if has_name_arg { 2 } else { 1 };
+
+    let args: Vec<(syn::Ident, syn::Type)> = sig
+        .inputs
+        .iter()
+        .skip(skip)
+        .map(|input| match input {
+            syn::FnArg::Typed(syn::PatType { pat, ty, .. }) => match pat.as_ref() {
+                syn::Pat::Ident(pat_ident) => {
+                    // _arg => arg
+                    let name = pat_ident.ident.to_string();
+                    let ident = format_ident!("{}", name.trim_start_matches('_'));
+                    (ident, ty.as_ref().clone())
+                }
+                _ => abort!(pat, "Invalid method args: expected a named argument"),
+            },
+            syn::FnArg::Receiver(_) => abort!(input, "Invalid method args"),
+        })
+        .collect();
+
+    if args.is_empty() {
+        abort!(
+            sig.ident.span(),
+            "Invalid method args: action takes no argument";
+            help = "expected fn {}(&mut self, arg: T), use arg: bool for a trigger", sig.ident
+        );
     }
 
-    if let syn::FnArg::Typed(syn::PatType { ty, .. }) = &sig.inputs[1] {
-        ty.as_ref().clone()
-    } else {
-        abort!(sig.ident.span(), "Invalid method args");
+    (has_name_arg, args)
+}
+
+fn is_str_arg(arg: &syn::FnArg) -> bool {
+    if let syn::FnArg::Typed(syn::PatType { ty, .. }) = arg {
+        if let syn::Type::Reference(reference) = ty.as_ref() {
+            return reference.mutability.is_none()
+                && reference.elem.to_token_stream().to_string() == "str";
+        }
     }
+
+    false
 }
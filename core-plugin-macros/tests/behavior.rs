@@ -1,12 +1,26 @@
 // Note : this also test runtime, but is easier to implement here than in core_plugin_runtime
 
-use std::sync::Mutex;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+    sync::{mpsc, Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime},
+};
 
-use core_plugin_macros::{mylife_actions, MylifePlugin};
+use core_plugin_macros::{mylife_actions, MylifePlugin, MylifeValue};
 use core_plugin_runtime::{
-    runtime::{Config, ConfigValue, MylifePluginRuntime, Value},
-    MylifePlugin, MylifePluginHooks, State,
+    metadata::Capability,
+    runtime::{
+        ActionTimeoutError, ComponentState, Config, ConfigValue, DeviceUnavailableError,
+        FailContext, FailReporter, Health, MylifeComponent, MylifePluginRuntime, Rgb,
+        TypedComponent, Value,
+    },
+    worker::{ComponentWorker, SyncComponent, WorkerStoppedError},
+    CancellationToken, Event, MylifePlugin, MylifePluginHooks, State, StreamBackpressure,
 };
+use log::LevelFilter;
 
 mod utils;
 
@@ -80,6 +94,8 @@ fn test_behavior() {
     let runtime: Box<dyn MylifePluginRuntime> = TestPlugin::runtime();
     let mut component = runtime.create("comp-id");
 
+    assert_eq!(component.id(), "comp-id");
+
     HISTORY.clear();
 
     let mut config = Config::new();
@@ -112,3 +128,2628 @@ fn test_behavior() {
         Value::Bool(true)
     );
 }
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct InitialStatePlugin {
+    #[mylife_state]
+    state_value: State<bool>,
+}
+
+impl MylifePluginHooks for InitialStatePlugin {
+    fn new(_id: &str) -> Self {
+        let mut plugin = InitialStatePlugin::default();
+
+        // state is not bound to the component yet
+        plugin.state_value.set(true);
+
+        plugin
+    }
+}
+
+#[test]
+fn test_set_unbound_state() {
+    let runtime: Box<dyn MylifePluginRuntime> = InitialStatePlugin::runtime();
+    let component = runtime.create("comp-id");
+
+    assert_eq!(
+        component.get_state("stateValue").unwrap(),
+        Value::Bool(true)
+    );
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct DerivedStatePlugin {
+    #[mylife_state]
+    first: State<bool>,
+
+    #[mylife_state]
+    second: State<bool>,
+
+    #[mylife_state(derived)]
+    any_on: State<bool>,
+}
+
+impl MylifePluginHooks for DerivedStatePlugin {
+    fn new(_id: &str) -> Self {
+        DerivedStatePlugin::default()
+    }
+
+    fn recompute(&mut self) {
+        let any_on = *self.first.get() || *self.second.get();
+        self.any_on.set(any_on);
+    }
+}
+
+#[mylife_actions]
+impl DerivedStatePlugin {
+    #[mylife_action]
+    fn set_first(&mut self, arg: bool) {
+        self.first.set(arg)
+    }
+
+    #[mylife_action]
+    fn set_second(&mut self, arg: bool) {
+        self.second.set(arg)
+    }
+}
+
+#[test]
+fn test_derived_state() {
+    let runtime: Box<dyn MylifePluginRuntime> = DerivedStatePlugin::runtime();
+    let mut component = runtime.create("comp-id");
+
+    component.configure(&Config::new()).unwrap();
+    component.init().unwrap();
+    assert_eq!(component.get_state("anyOn").unwrap(), Value::Bool(false));
+
+    component
+        .execute_action("setSecond", Value::Bool(true))
+        .unwrap();
+    assert_eq!(component.get_state("anyOn").unwrap(), Value::Bool(true));
+
+    component
+        .execute_action("setSecond", Value::Bool(false))
+        .unwrap();
+    assert_eq!(component.get_state("anyOn").unwrap(), Value::Bool(false));
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct NullableStatePlugin {
+    #[mylife_state]
+    reading: State<Option<f64>>,
+}
+
+impl MylifePluginHooks for NullableStatePlugin {
+    fn new(_id: &str) -> Self {
+        NullableStatePlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl NullableStatePlugin {
+    #[mylife_action]
+    fn set_reading(&mut self, arg: Option<f64>) {
+        self.reading.set(arg)
+    }
+}
+
+#[test]
+fn test_nullable_state() {
+    let runtime: Box<dyn MylifePluginRuntime> = NullableStatePlugin::runtime();
+    let mut component = runtime.create("comp-id");
+
+    component.configure(&Config::new()).unwrap();
+    component.init().unwrap();
+    assert_eq!(component.get_state("reading").unwrap(), Value::Null);
+
+    component
+        .execute_action("setReading", Value::Float(42.0))
+        .unwrap();
+    assert_eq!(component.get_state("reading").unwrap(), Value::Float(42.0));
+
+    component.execute_action("setReading", Value::Null).unwrap();
+    assert_eq!(component.get_state("reading").unwrap(), Value::Null);
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct SharedActionPlugin {
+    #[mylife_state(r#type = "range[0;100]")]
+    level: State<i64>,
+}
+
+impl MylifePluginHooks for SharedActionPlugin {
+    fn new(_id: &str) -> Self {
+        SharedActionPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl SharedActionPlugin {
+    #[mylife_action(name = "levelLow")]
+    #[mylife_action(name = "levelHigh")]
+    fn set_level(&mut self, name: &str, arg: bool) {
+        if !arg {
+            return;
+        }
+
+        match name {
+            "levelLow" => self.level.set(10),
+            "levelHigh" => self.level.set(90),
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[test]
+fn test_shared_action() {
+    let runtime: Box<dyn MylifePluginRuntime> = SharedActionPlugin::runtime();
+    let mut component = runtime.create("comp-id");
+
+    component.configure(&Config::new()).unwrap();
+    component.init().unwrap();
+
+    component
+        .execute_action("levelHigh", Value::Bool(true))
+        .unwrap();
+    assert_eq!(component.get_state("level").unwrap(), Value::Range(90));
+
+    component
+        .execute_action("levelLow", Value::Bool(true))
+        .unwrap();
+    assert_eq!(component.get_state("level").unwrap(), Value::Range(10));
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct SlowActionPlugin {}
+
+impl MylifePluginHooks for SlowActionPlugin {
+    fn new(_id: &str) -> Self {
+        SlowActionPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl SlowActionPlugin {
+    #[mylife_action]
+    fn wait(&mut self, arg: f64) {
+        thread::sleep(Duration::from_secs_f64(arg));
+    }
+}
+
+#[test]
+fn test_action_timeout() {
+    let runtime: Arc<dyn MylifePluginRuntime> = Arc::from(SlowActionPlugin::runtime());
+    let worker = ComponentWorker::spawn(move || create_ready(runtime.as_ref()));
+    let (sender, receiver) = mpsc::channel();
+    worker.set_on_fail(Box::new(move |context, err| {
+        sender.send((context.clone(), err.to_string())).unwrap();
+    }));
+
+    worker
+        .execute_action_with_timeout("wait", Value::Float(0.0), Duration::from_secs(5))
+        .unwrap();
+
+    // the caller does not wait for the action to complete
+    let start = Instant::now();
+    let err = worker
+        .execute_action_with_timeout("wait", Value::Float(0.5), Duration::from_millis(10))
+        .unwrap_err();
+    assert!(err.is::<ActionTimeoutError>());
+
+    // the overrun is reported while the action still runs
+    let (context, message) = receiver.try_recv().unwrap();
+    assert_eq!(context, FailContext::Action(String::from("wait")));
+    assert!(message.contains("timeout"));
+    assert!(matches!(worker.health().unwrap(), Health::Degraded(_)));
+    assert!(start.elapsed() < Duration::from_millis(400));
+
+    // queued behind the overrunning action, the time spent waiting does not count
+    worker
+        .execute_action_with_timeout("wait", Value::Float(0.0), Duration::from_millis(100))
+        .unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(500));
+
+    // an action completing in time recovers
+    assert_eq!(worker.health().unwrap(), Health::Ok);
+    assert!(receiver.try_recv().is_err());
+}
+
+#[test]
+fn test_action_timeout_drop() {
+    let runtime: Arc<dyn MylifePluginRuntime> = Arc::from(SlowActionPlugin::runtime());
+    let worker = ComponentWorker::spawn(move || create_ready(runtime.as_ref()));
+
+    let start = Instant::now();
+    worker
+        .execute_action_with_timeout("wait", Value::Float(5.0), Duration::from_millis(10))
+        .unwrap_err();
+
+    // the worker thread is stuck in the action, it is not joined
+    drop(worker);
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct ConfigPatternPlugin {
+    #[mylife_config(pattern = "^[a-z0-9-]+$")]
+    host: String,
+}
+
+impl MylifePluginHooks for ConfigPatternPlugin {
+    fn new(_id: &str) -> Self {
+        ConfigPatternPlugin::default()
+    }
+}
+
+#[test]
+fn test_config_pattern() {
+    let runtime: Box<dyn MylifePluginRuntime> = ConfigPatternPlugin::runtime();
+    assert_eq!(
+        runtime.metadata().config()["host"].pattern(),
+        Some("^[a-z0-9-]+$")
+    );
+
+    let mut component = runtime.create("comp-id");
+
+    let mut config = Config::new();
+    config.insert("host".to_string(), ConfigValue::String("Bad Host".into()));
+    assert!(component.configure(&config).is_err());
+
+    config.insert("host".to_string(), ConfigValue::String("good-host".into()));
+    component.configure(&config).unwrap();
+}
+
+#[test]
+fn test_configure_strict() {
+    let runtime: Box<dyn MylifePluginRuntime> = ConfigPatternPlugin::runtime();
+    let mut component = runtime.create("comp-id");
+
+    let mut config = Config::new();
+    config.insert("host".to_string(), ConfigValue::String("good-host".into()));
+    component.configure_strict(&config).unwrap();
+
+    config.insert("hots".to_string(), ConfigValue::String("typo".into()));
+    component.configure(&config).unwrap();
+    assert!(component.configure_strict(&config).is_err());
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct NullableConfigPlugin {
+    #[mylife_config(range = "0..=100")]
+    level: Option<u8>,
+
+    #[mylife_config]
+    host: String,
+
+    #[mylife_state]
+    effective_level: State<u8>,
+}
+
+impl MylifePluginHooks for NullableConfigPlugin {
+    fn new(_id: &str) -> Self {
+        NullableConfigPlugin::default()
+    }
+
+    fn init(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        // null means automatic
+        self.effective_level.set(self.level.unwrap_or(50));
+        Ok(())
+    }
+}
+
+#[test]
+fn test_nullable_config() {
+    let runtime: Box<dyn MylifePluginRuntime> = NullableConfigPlugin::runtime();
+    assert!(runtime.metadata().config()["level"].nullable());
+    assert!(!runtime.metadata().config()["host"].nullable());
+
+    let mut config = Config::new();
+    config.insert("level".to_string(), ConfigValue::Null);
+    config.insert("host".to_string(), ConfigValue::String("host".into()));
+
+    let mut component = runtime.create("comp-id");
+    component.configure(&config).unwrap();
+    component.init().unwrap();
+    assert_eq!(component.get_state("effectiveLevel").unwrap(), Value::Range(50));
+
+    config.insert("level".to_string(), ConfigValue::Integer(20));
+    let mut component = runtime.create("comp-id");
+    component.configure(&config).unwrap();
+    component.init().unwrap();
+    assert_eq!(component.get_state("effectiveLevel").unwrap(), Value::Range(20));
+
+    config.insert("host".to_string(), ConfigValue::Null);
+    let mut component = runtime.create("comp-id");
+    assert!(component.configure(&config).is_err());
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct SecretConfigPlugin {
+    #[mylife_config(secret, pattern = "^[a-z]+$")]
+    api_key: String,
+
+    #[mylife_config]
+    host: String,
+
+    #[mylife_state]
+    key_length: State<u8>,
+}
+
+impl MylifePluginHooks for SecretConfigPlugin {
+    fn new(_id: &str) -> Self {
+        SecretConfigPlugin::default()
+    }
+
+    fn init(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.key_length.set(self.api_key.len() as u8);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_secret_config() {
+    let runtime: Box<dyn MylifePluginRuntime> = SecretConfigPlugin::runtime();
+    assert!(runtime.metadata().config()["apiKey"].secret());
+    assert!(!runtime.metadata().config()["host"].secret());
+
+    let mut config = Config::new();
+    config.insert("apiKey".to_string(), ConfigValue::String("Hunter2".into()));
+    config.insert("host".to_string(), ConfigValue::String("host".into()));
+
+    let mut component = runtime.create("comp-id");
+    let err = component.configure(&config).unwrap_err();
+    assert!(!err.to_string().contains("Hunter2"));
+
+    config.insert("apiKey".to_string(), ConfigValue::String("hunter".into()));
+    component.configure(&config).unwrap();
+    component.init().unwrap();
+
+    // the plugin gets the real value
+    assert_eq!(component.get_state("keyLength").unwrap(), Value::Range(6));
+
+    let snapshot = component.describe();
+    assert_eq!(
+        snapshot.config()["apiKey"],
+        ConfigValue::String("***".into())
+    );
+    assert_eq!(snapshot.config()["host"], ConfigValue::String("host".into()));
+
+    // wrong type: the setter error must not leak the value either
+    config.insert("apiKey".to_string(), ConfigValue::Integer(424242));
+    let mut component = runtime.create("comp-id");
+    let err = component.configure(&config).unwrap_err();
+    assert!(!err.to_string().contains("424242"));
+    assert!(err.to_string().contains("apiKey"));
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct ExtraConfigPlugin {
+    #[mylife_config]
+    url: String,
+
+    #[mylife_config(extra)]
+    headers: HashMap<String, ConfigValue>,
+
+    #[mylife_state]
+    header_count: State<u8>,
+}
+
+impl MylifePluginHooks for ExtraConfigPlugin {
+    fn new(_id: &str) -> Self {
+        ExtraConfigPlugin::default()
+    }
+
+    fn init(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.header_count.set(self.headers.len() as u8);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_extra_config() {
+    let runtime: Box<dyn MylifePluginRuntime> = ExtraConfigPlugin::runtime();
+    assert!(runtime.metadata().accepts_extra_config());
+    assert_eq!(runtime.metadata().config().len(), 1);
+
+    let mut component = runtime.create("comp-id");
+
+    let mut config = Config::new();
+    config.insert("url".to_string(), ConfigValue::String("http://host".into()));
+    config.insert("accept".to_string(), ConfigValue::String("text/plain".into()));
+    config.insert("retries".to_string(), ConfigValue::Integer(3));
+    component.configure_strict(&config).unwrap();
+    component.init().unwrap();
+
+    assert_eq!(component.get_state("headerCount").unwrap(), Value::Range(2));
+    assert_eq!(component.describe().config(), &config);
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct ExposedConfigPlugin {
+    #[mylife_config(expose_as_state, description = "Device model")]
+    model: String,
+
+    #[mylife_config(expose_as_state, unit = "%")]
+    max_brightness: u8,
+}
+
+impl MylifePluginHooks for ExposedConfigPlugin {
+    fn new(_id: &str) -> Self {
+        ExposedConfigPlugin::default()
+    }
+}
+
+#[test]
+fn test_config_exposed_as_state() {
+    let runtime: Box<dyn MylifePluginRuntime> = ExposedConfigPlugin::runtime();
+    let metadata = runtime.metadata();
+    assert!(metadata.config().contains_key("model"));
+    assert_eq!(
+        metadata.members()["model"].member_type(),
+        core_plugin_runtime::metadata::MemberType::State
+    );
+    assert_eq!(metadata.members()["model"].description(), Some("Device model"));
+    assert_eq!(
+        metadata.members()["maxBrightness"].value_type(),
+        &core_plugin_runtime::metadata::Type::Range(0, 255)
+    );
+    assert_eq!(metadata.config()["maxBrightness"].unit(), Some("%"));
+    assert_eq!(metadata.members()["maxBrightness"].unit(), Some("%"));
+    assert_eq!(metadata.members()["model"].unit(), None);
+
+    let mut component = runtime.create("comp-id");
+    let emitted = Rc::new(RefCell::new(Vec::new()));
+    let handler_emitted = emitted.clone();
+    component.set_on_state(Box::new(move |name, value| {
+        handler_emitted.borrow_mut().push((name.to_string(), value));
+    }));
+
+    let mut config = Config::new();
+    config.insert("model".to_string(), ConfigValue::String("dimmer-x".into()));
+    config.insert("maxBrightness".to_string(), ConfigValue::Integer(200));
+    component.configure(&config).unwrap();
+
+    let mut emitted = emitted.borrow().clone();
+    emitted.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        emitted,
+        vec![
+            ("maxBrightness".to_string(), Value::Range(200)),
+            ("model".to_string(), Value::Text("dimmer-x".into())),
+        ]
+    );
+    assert_eq!(
+        component.get_state("model").unwrap(),
+        Value::Text("dimmer-x".into())
+    );
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "sensor", version = "2.1.0")]
+struct NetworkSensorPlugin {
+    #[mylife_state]
+    online: State<bool>,
+}
+
+impl MylifePluginHooks for NetworkSensorPlugin {
+    fn new(_id: &str) -> Self {
+        NetworkSensorPlugin::default()
+    }
+
+    fn required_capabilities() -> &'static [Capability] {
+        &[Capability::Network, Capability::Time]
+    }
+}
+
+#[test]
+fn test_required_capabilities() {
+    let runtime: Box<dyn MylifePluginRuntime> = NetworkSensorPlugin::runtime();
+    assert_eq!(
+        runtime.metadata().required_capabilities(),
+        &[Capability::Network, Capability::Time]
+    );
+
+    let runtime: Box<dyn MylifePluginRuntime> = ExposedConfigPlugin::runtime();
+    assert!(runtime.metadata().required_capabilities().is_empty());
+}
+
+#[test]
+fn test_plugin_version() {
+    let runtime: Box<dyn MylifePluginRuntime> = NetworkSensorPlugin::runtime();
+    assert_eq!(runtime.metadata().version(), Some("2.1.0"));
+
+    let runtime: Box<dyn MylifePluginRuntime> = ExposedConfigPlugin::runtime();
+    assert_eq!(runtime.metadata().version(), None);
+}
+
+#[test]
+fn test_apply_config_diff() {
+    let runtime: Box<dyn MylifePluginRuntime> = ExposedConfigPlugin::runtime();
+    let mut component = runtime.create("comp-id");
+
+    let mut changes = Config::new();
+    changes.insert("model".to_string(), ConfigValue::String("dimmer-y".into()));
+    assert!(component.apply_config_diff(&changes).is_err());
+
+    let mut config = Config::new();
+    config.insert("model".to_string(), ConfigValue::String("dimmer-x".into()));
+    config.insert("maxBrightness".to_string(), ConfigValue::Integer(200));
+    component.configure(&config).unwrap();
+    component.init().unwrap();
+
+    let emitted = Rc::new(RefCell::new(Vec::new()));
+    let handler_emitted = emitted.clone();
+    component.set_on_state(Box::new(move |name, value| {
+        handler_emitted.borrow_mut().push((name.to_string(), value));
+    }));
+
+    changes.insert("maxBrightness".to_string(), ConfigValue::Integer(200));
+    assert_eq!(component.apply_config_diff(&changes).unwrap(), vec!["model"]);
+    assert_eq!(
+        *emitted.borrow(),
+        vec![("model".to_string(), Value::Text("dimmer-y".into()))]
+    );
+    assert_eq!(
+        component.describe().config()["model"],
+        ConfigValue::String("dimmer-y".into())
+    );
+
+    // nothing is applied if a key is invalid
+    changes.insert("maxBrightness".to_string(), ConfigValue::Integer(300));
+    changes.insert("model".to_string(), ConfigValue::String("dimmer-z".into()));
+    assert!(component.apply_config_diff(&changes).is_err());
+    assert_eq!(
+        component.get_state("model").unwrap(),
+        Value::Text("dimmer-y".into())
+    );
+
+    let mut changes = Config::new();
+    changes.insert("unknown".to_string(), ConfigValue::Integer(1));
+    assert!(component.apply_config_diff(&changes).is_err());
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct ValidateConfigPlugin {
+    #[mylife_config(pattern = "^[a-z0-9-]+$")]
+    host: String,
+
+    #[mylife_config]
+    port: i64,
+}
+
+impl MylifePluginHooks for ValidateConfigPlugin {
+    fn new(_id: &str) -> Self {
+        ValidateConfigPlugin::default()
+    }
+}
+
+#[test]
+fn test_validate_config() {
+    let runtime: Box<dyn MylifePluginRuntime> = ValidateConfigPlugin::runtime();
+    let component = runtime.create("comp-id");
+
+    let mut config = Config::new();
+    config.insert("host".to_string(), ConfigValue::String("Bad Host".into()));
+    config.insert("port".to_string(), ConfigValue::Bool(true));
+
+    let errors = component.validate_config(&config).unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].to_string().contains("host"));
+
+    config.remove("port");
+    assert_eq!(component.validate_config(&config).unwrap_err().len(), 2);
+
+    config.insert("host".to_string(), ConfigValue::String("good-host".into()));
+    config.insert("port".to_string(), ConfigValue::Integer(8080));
+    assert!(component.validate_config(&config).is_ok());
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct ConfigOrderPlugin {
+    #[mylife_config]
+    zone: String,
+
+    #[mylife_config]
+    address: String,
+}
+
+impl MylifePluginHooks for ConfigOrderPlugin {
+    fn new(_id: &str) -> Self {
+        ConfigOrderPlugin::default()
+    }
+}
+
+#[test]
+fn test_config_order() {
+    let runtime: Box<dyn MylifePluginRuntime> = ConfigOrderPlugin::runtime();
+    let component = runtime.create("comp-id");
+
+    // setters run in declaration order
+    let errors = component.validate_config(&Config::new()).unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].to_string().contains("zone"));
+    assert!(errors[1].to_string().contains("address"));
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct ActionResultPlugin {}
+
+impl MylifePluginHooks for ActionResultPlugin {
+    fn new(_id: &str) -> Self {
+        ActionResultPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl ActionResultPlugin {
+    #[mylife_action(result_type = "range[0;100]")]
+    fn double(&mut self, arg: f64) -> Result<i64, Box<dyn std::error::Error>> {
+        Ok((arg * 2.0) as i64)
+    }
+
+    #[mylife_action]
+    fn nothing(&mut self, _arg: bool) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_action_result() {
+    let runtime: Box<dyn MylifePluginRuntime> = ActionResultPlugin::runtime();
+    let members = runtime.metadata().members();
+    assert_eq!(
+        members["double"].result_type(),
+        Some(&core_plugin_runtime::metadata::Type::Range(0, 100))
+    );
+    assert_eq!(members["nothing"].result_type(), None);
+
+    let mut component = runtime.create("comp-id");
+
+    component.configure(&Config::new()).unwrap();
+    component.init().unwrap();
+
+    assert_eq!(
+        component
+            .execute_action_with_result("double", Value::Float(21.0))
+            .unwrap(),
+        Some(Value::Range(42))
+    );
+    assert_eq!(
+        component
+            .execute_action_with_result("nothing", Value::Bool(true))
+            .unwrap(),
+        None
+    );
+}
+
+#[derive(MylifeValue, Clone, Default, Debug, PartialEq)]
+struct Weather {
+    temperature: f64,
+
+    #[mylife_value(r#type = "range[0;100]")]
+    relative_humidity: i64,
+
+    alerts: Vec<String>,
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct ObjectStatePlugin {
+    #[mylife_state]
+    weather: State<Weather>,
+}
+
+impl MylifePluginHooks for ObjectStatePlugin {
+    fn new(_id: &str) -> Self {
+        ObjectStatePlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl ObjectStatePlugin {
+    #[mylife_action]
+    fn set_weather(&mut self, arg: Weather) {
+        self.weather.set(arg)
+    }
+}
+
+#[derive(MylifeValue, Clone, Default, Debug, PartialEq)]
+struct RgbTriplet {
+    #[mylife_value(r#type = "range[0;255]")]
+    red: i64,
+    #[mylife_value(r#type = "range[0;255]")]
+    green: i64,
+    #[mylife_value(r#type = "range[0;255]")]
+    blue: i64,
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct ComplexStatePlugin {
+    #[mylife_state(r#type = "complex[rgb]")]
+    color: State<RgbTriplet>,
+
+    #[mylife_state(r#type = "list[complex[rgb]]")]
+    palette: State<Vec<RgbTriplet>>,
+}
+
+impl MylifePluginHooks for ComplexStatePlugin {
+    fn new(_id: &str) -> Self {
+        ComplexStatePlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl ComplexStatePlugin {
+    #[mylife_action(r#type = "complex[rgb]")]
+    fn set_color(&mut self, arg: RgbTriplet) {
+        self.color.set(arg);
+    }
+}
+
+#[test]
+fn test_complex_state() {
+    let runtime: Box<dyn MylifePluginRuntime> = ComplexStatePlugin::runtime();
+    let metadata = runtime.metadata();
+    assert_eq!(
+        metadata.members()["color"].value_type().to_string(),
+        "complex[rgb]"
+    );
+    assert_eq!(metadata.complex_types().len(), 1);
+    let schema = metadata.complex_type("rgb").unwrap();
+    assert_eq!(
+        schema.to_string(),
+        "object{red:range[0;255],green:range[0;255],blue:range[0;255]}"
+    );
+
+    let mut component = create_ready(runtime.as_ref());
+
+    let mut fields = BTreeMap::new();
+    fields.insert(String::from("red"), Value::Range(255));
+    fields.insert(String::from("green"), Value::Range(128));
+    fields.insert(String::from("blue"), Value::Range(0));
+
+    component
+        .execute_action("setColor", Value::Object(fields.clone()))
+        .unwrap();
+    let color = component.get_state("color").unwrap();
+    assert_eq!(color, Value::Object(fields));
+    assert!(color.encode(&schema).is_ok());
+
+    let mut fields = BTreeMap::new();
+    fields.insert(String::from("red"), Value::Range(300));
+    assert!(component
+        .execute_action("setColor", Value::Object(fields))
+        .is_err());
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct DefaultArgPlugin {
+    #[mylife_state]
+    level: State<u8>,
+}
+
+impl MylifePluginHooks for DefaultArgPlugin {
+    fn new(_id: &str) -> Self {
+        DefaultArgPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl DefaultArgPlugin {
+    #[mylife_action(default_arg = "100")]
+    fn set_level(&mut self, arg: u8) {
+        self.level.set(arg);
+    }
+}
+
+#[test]
+fn test_action_default_arg() {
+    let runtime: Box<dyn MylifePluginRuntime> = DefaultArgPlugin::runtime();
+    assert_eq!(
+        runtime.metadata().members()["setLevel"].default_arg(),
+        Some(&Value::Range(100))
+    );
+
+    let mut component = create_ready(runtime.as_ref());
+
+    component.execute_action("setLevel", Value::Range(10)).unwrap();
+    assert_eq!(component.get_state("level").unwrap(), Value::Range(10));
+
+    component.execute_action("setLevel", Value::Null).unwrap();
+    assert_eq!(component.get_state("level").unwrap(), Value::Range(100));
+}
+
+#[test]
+fn test_object_state() {
+    let runtime: Box<dyn MylifePluginRuntime> = ObjectStatePlugin::runtime();
+    assert_eq!(
+        runtime.metadata().members()["weather"]
+            .value_type()
+            .to_string(),
+        "object{temperature:float,relativeHumidity:range[0;100],alerts:list[text]}"
+    );
+
+    let mut component = runtime.create("comp-id");
+
+    component.configure(&Config::new()).unwrap();
+    component.init().unwrap();
+
+    let mut fields = BTreeMap::new();
+    fields.insert(String::from("temperature"), Value::Float(21.5));
+    fields.insert(String::from("relativeHumidity"), Value::Range(40));
+    fields.insert(
+        String::from("alerts"),
+        Value::List(vec![Value::Text(String::from("wind"))]),
+    );
+
+    component
+        .execute_action("setWeather", Value::Object(fields.clone()))
+        .unwrap();
+    assert_eq!(
+        component.get_state("weather").unwrap(),
+        Value::Object(fields.clone())
+    );
+
+    fields.insert(String::from("relativeHumidity"), Value::Range(140));
+    assert!(component
+        .execute_action("setWeather", Value::Object(fields))
+        .is_err());
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct LogLevelPlugin {
+    #[mylife_state]
+    verbose: State<bool>,
+}
+
+impl MylifePluginHooks for LogLevelPlugin {
+    fn new(_id: &str) -> Self {
+        LogLevelPlugin::default()
+    }
+
+    fn set_log_level(&mut self, level: LevelFilter) {
+        self.verbose.set(level >= LevelFilter::Debug);
+    }
+}
+
+#[test]
+fn test_log_level() {
+    let runtime: Box<dyn MylifePluginRuntime> = LogLevelPlugin::runtime();
+    let mut component = runtime.create("comp-id");
+
+    assert_eq!(component.log_level(), LevelFilter::Trace);
+
+    component.set_log_level(LevelFilter::Debug);
+    assert_eq!(component.log_level(), LevelFilter::Debug);
+    assert_eq!(component.get_state("verbose").unwrap(), Value::Bool(true));
+
+    component.set_log_level(LevelFilter::Warn);
+    assert_eq!(component.log_level(), LevelFilter::Warn);
+    assert_eq!(component.get_state("verbose").unwrap(), Value::Bool(false));
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct ReadyPlugin {
+    #[mylife_config]
+    fail_init: bool,
+}
+
+impl MylifePluginHooks for ReadyPlugin {
+    fn new(_id: &str) -> Self {
+        ReadyPlugin::default()
+    }
+
+    fn init(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.fail_init {
+            return Err("init failed".into());
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_on_ready() {
+    let runtime: Box<dyn MylifePluginRuntime> = ReadyPlugin::runtime();
+
+    for (fail_init, expected_ready) in [(false, 1), (true, 0)] {
+        let ready_count = Rc::new(Cell::new(0));
+        let mut component = runtime.create("comp-id");
+
+        let handler_ready_count = ready_count.clone();
+        component.set_on_ready(Box::new(move || {
+            handler_ready_count.set(handler_ready_count.get() + 1);
+        }));
+
+        let mut config = Config::new();
+        config.insert("failInit".to_string(), ConfigValue::Bool(fail_init));
+        component.configure(&config).unwrap();
+
+        assert_eq!(component.init().is_ok(), !fail_init);
+        assert_eq!(ready_count.get(), expected_ready);
+    }
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct DurationPlugin {
+    #[mylife_config]
+    delay: Duration,
+
+    #[mylife_state]
+    current_delay: State<Duration>,
+}
+
+impl MylifePluginHooks for DurationPlugin {
+    fn new(_id: &str) -> Self {
+        DurationPlugin::default()
+    }
+
+    fn init(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.current_delay.set(self.delay);
+
+        Ok(())
+    }
+}
+
+#[mylife_actions]
+impl DurationPlugin {
+    #[mylife_action]
+    fn set_delay(&mut self, arg: Duration) {
+        self.current_delay.set(arg)
+    }
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "sensor")]
+struct TimestampPlugin {
+    #[mylife_state]
+    last_pressed: State<Option<SystemTime>>,
+}
+
+impl MylifePluginHooks for TimestampPlugin {
+    fn new(_id: &str) -> Self {
+        TimestampPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl TimestampPlugin {
+    #[mylife_action]
+    fn press(&mut self, at: SystemTime) {
+        self.last_pressed.set(Some(at))
+    }
+}
+
+#[test]
+fn test_timestamp() {
+    let runtime: Box<dyn MylifePluginRuntime> = TimestampPlugin::runtime();
+    assert_eq!(
+        runtime.metadata().members()["lastPressed"].value_type(),
+        &core_plugin_runtime::metadata::Type::Nullable(Box::new(
+            core_plugin_runtime::metadata::Type::Timestamp
+        ))
+    );
+
+    let mut component = create_ready(runtime.as_ref());
+    assert_eq!(component.get_state("lastPressed").unwrap(), Value::Null);
+
+    component
+        .execute_action("press", Value::Timestamp(1_700_000_000_000))
+        .unwrap();
+    assert_eq!(
+        component.get_state("lastPressed").unwrap(),
+        Value::Timestamp(1_700_000_000_000)
+    );
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "sensor")]
+struct KeypadPlugin {
+    #[mylife_state]
+    last_key: State<char>,
+}
+
+impl MylifePluginHooks for KeypadPlugin {
+    fn new(_id: &str) -> Self {
+        KeypadPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl KeypadPlugin {
+    #[mylife_action]
+    fn press(&mut self, key: char) {
+        self.last_key.set(key)
+    }
+}
+
+#[test]
+fn test_char() {
+    let runtime: Box<dyn MylifePluginRuntime> = KeypadPlugin::runtime();
+    assert_eq!(
+        runtime.metadata().members()["lastKey"].value_type(),
+        &core_plugin_runtime::metadata::Type::Char
+    );
+
+    let mut component = create_ready(runtime.as_ref());
+    component
+        .execute_action("press", Value::Text("#".into()))
+        .unwrap();
+    assert_eq!(
+        component.get_state("lastKey").unwrap(),
+        Value::Text("#".into())
+    );
+
+    assert!(component
+        .execute_action("press", Value::Text("12".into()))
+        .is_err());
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "actuator")]
+struct RgbLightPlugin {
+    #[mylife_state]
+    color: State<Rgb>,
+}
+
+impl MylifePluginHooks for RgbLightPlugin {
+    fn new(_id: &str) -> Self {
+        RgbLightPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl RgbLightPlugin {
+    #[mylife_action]
+    fn set_color(&mut self, color: Rgb) {
+        self.color.set(color)
+    }
+}
+
+#[test]
+fn test_color() {
+    let runtime: Box<dyn MylifePluginRuntime> = RgbLightPlugin::runtime();
+    assert_eq!(
+        runtime.metadata().members()["color"].value_type(),
+        &core_plugin_runtime::metadata::Type::Color
+    );
+
+    let mut component = create_ready(runtime.as_ref());
+    component
+        .execute_action("setColor", Rgb::new(0, 128, 255).into())
+        .unwrap();
+    assert_eq!(
+        component.get_state("color").unwrap(),
+        Value::Color {
+            r: 0,
+            g: 128,
+            b: 255
+        }
+    );
+
+    assert!(component
+        .execute_action("setColor", Value::Text("#0080ff".into()))
+        .is_err());
+}
+
+#[test]
+fn test_duration() {
+    let runtime: Box<dyn MylifePluginRuntime> = DurationPlugin::runtime();
+    assert_eq!(
+        runtime.metadata().config()["delay"].value_type(),
+        core_plugin_runtime::metadata::ConfigType::Duration
+    );
+    assert_eq!(
+        runtime.metadata().members()["currentDelay"].value_type(),
+        &core_plugin_runtime::metadata::Type::Duration
+    );
+
+    let mut component = runtime.create("comp-id");
+
+    let mut config = Config::new();
+    config.insert("delay".to_string(), ConfigValue::String("2x".into()));
+    assert!(component.configure(&config).is_err());
+
+    config.insert("delay".to_string(), ConfigValue::String("500ms".into()));
+    component.configure(&config).unwrap();
+    component.init().unwrap();
+    assert_eq!(
+        component.get_state("currentDelay").unwrap(),
+        Value::Duration(Duration::from_millis(500))
+    );
+
+    component
+        .execute_action("setDelay", Value::Duration(Duration::from_secs(60)))
+        .unwrap();
+    assert_eq!(
+        component.get_state("currentDelay").unwrap(),
+        Value::Duration(Duration::from_secs(60))
+    );
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct RetryPlugin {
+    #[mylife_state]
+    attempts: State<f64>,
+}
+
+impl MylifePluginHooks for RetryPlugin {
+    fn new(_id: &str) -> Self {
+        RetryPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl RetryPlugin {
+    // fails while attempts < arg
+    #[mylife_action(retries = 3, backoff = "1ms")]
+    fn flaky(&mut self, arg: f64) -> Result<(), Box<dyn std::error::Error>> {
+        self.attempts.set(self.attempts.get() + 1.0);
+
+        if *self.attempts.get() < arg {
+            return Err(Box::new(DeviceUnavailableError::new("not ready")));
+        }
+
+        Ok(())
+    }
+
+    #[mylife_action(retries = 3)]
+    fn invalid(&mut self, _arg: bool) -> Result<(), Box<dyn std::error::Error>> {
+        self.attempts.set(self.attempts.get() + 1.0);
+
+        Err("invalid value".into())
+    }
+}
+
+fn create_ready(runtime: &dyn MylifePluginRuntime) -> Box<dyn MylifeComponent> {
+    let mut component = runtime.create("comp-id");
+    component.configure(&Config::new()).unwrap();
+    component.init().unwrap();
+    component
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "sensor")]
+struct OverheatPlugin {
+    #[mylife_state(latched)]
+    fault: State<bool>,
+
+    // non ASCII first character: the clear action name is built per char, not per byte
+    #[mylife_state(latched, name = "ébullition")]
+    boiling: State<bool>,
+}
+
+impl MylifePluginHooks for OverheatPlugin {
+    fn new(_id: &str) -> Self {
+        OverheatPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl OverheatPlugin {
+    #[mylife_action]
+    fn set_overheat(&mut self, overheat: bool) {
+        self.fault.set(overheat)
+    }
+}
+
+#[test]
+fn test_latched_state() {
+    let runtime: Box<dyn MylifePluginRuntime> = OverheatPlugin::runtime();
+    assert_eq!(runtime.metadata().members()["fault"].actions(), &["clearFault"]);
+    assert_eq!(
+        runtime.metadata().members()["ébullition"].actions(),
+        &["clearÉbullition"]
+    );
+    assert_eq!(
+        runtime.metadata().members()["clearFault"].value_type(),
+        &core_plugin_runtime::metadata::Type::Bool
+    );
+
+    let mut component = create_ready(runtime.as_ref());
+    component
+        .execute_action("setOverheat", Value::Bool(true))
+        .unwrap();
+    component
+        .execute_action("setOverheat", Value::Bool(false))
+        .unwrap();
+    assert_eq!(component.get_state("fault").unwrap(), Value::Bool(true));
+
+    // condition cleared: acknowledge releases the state
+    component
+        .execute_action("clearFault", Value::Bool(false))
+        .unwrap();
+    assert_eq!(component.get_state("fault").unwrap(), Value::Bool(true));
+    component
+        .execute_action("clearFault", Value::Bool(true))
+        .unwrap();
+    assert_eq!(component.get_state("fault").unwrap(), Value::Bool(false));
+
+    // condition still present: the state stays set
+    component
+        .execute_action("setOverheat", Value::Bool(true))
+        .unwrap();
+    component
+        .execute_action("clearFault", Value::Bool(true))
+        .unwrap();
+    assert_eq!(component.get_state("fault").unwrap(), Value::Bool(true));
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "actuator", deprecated = "use dimmer instead")]
+struct LegacyDimmerPlugin {
+    #[mylife_config(deprecated = "use channel instead")]
+    address: i64,
+
+    #[mylife_state]
+    level: State<u8>,
+}
+
+impl MylifePluginHooks for LegacyDimmerPlugin {
+    fn new(_id: &str) -> Self {
+        LegacyDimmerPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl LegacyDimmerPlugin {
+    #[mylife_action(deprecated = "use setLevel instead")]
+    fn set_value(&mut self, value: u8) {
+        self.level.set(value)
+    }
+
+    #[mylife_action]
+    fn set_level(&mut self, value: u8) {
+        self.level.set(value)
+    }
+}
+
+#[test]
+fn test_deprecated() {
+    let runtime: Box<dyn MylifePluginRuntime> = LegacyDimmerPlugin::runtime();
+    let metadata = runtime.metadata();
+    assert_eq!(metadata.deprecated(), Some("use dimmer instead"));
+    assert_eq!(
+        metadata.config()["address"].deprecated(),
+        Some("use channel instead")
+    );
+    assert_eq!(
+        metadata.members()["setValue"].deprecated(),
+        Some("use setLevel instead")
+    );
+    assert_eq!(metadata.members()["setLevel"].deprecated(), None);
+    assert_eq!(metadata.members()["level"].deprecated(), None);
+
+    // still executed
+    let mut component = runtime.create("comp-id");
+    let mut config = Config::new();
+    config.insert("address".to_string(), ConfigValue::Integer(1));
+    component.configure(&config).unwrap();
+    component.init().unwrap();
+    component.execute_action("setValue", Value::Range(42)).unwrap();
+    component.execute_action("setValue", Value::Range(43)).unwrap();
+    assert_eq!(component.get_state("level").unwrap(), Value::Range(43));
+}
+
+#[test]
+fn test_typed_component() {
+    let runtime: Box<dyn MylifePluginRuntime> = KeypadPlugin::runtime();
+    assert_eq!(
+        runtime.plugin_type_id(),
+        Some(std::any::TypeId::of::<KeypadPlugin>())
+    );
+
+    let mut component =
+        TypedComponent::<KeypadPlugin>::new(create_ready(runtime.as_ref())).unwrap();
+    component.plugin_mut().press('*');
+    assert_eq!(component.plugin().last_key.get(), &'*');
+    assert_eq!(
+        component.get_state("lastKey").unwrap(),
+        Value::Text("*".into())
+    );
+
+    let component = component.into_inner();
+    let component = TypedComponent::<RgbLightPlugin>::new(component).unwrap_err();
+    assert_eq!(component.id(), "comp-id");
+}
+
+#[test]
+fn test_action_retry() {
+    let runtime: Box<dyn MylifePluginRuntime> = RetryPlugin::runtime();
+
+    let mut component = create_ready(runtime.as_ref());
+    component.execute_action("flaky", Value::Float(3.0)).unwrap();
+    assert_eq!(component.get_state("attempts").unwrap(), Value::Float(3.0));
+
+    let mut component = create_ready(runtime.as_ref());
+    assert!(component.execute_action("flaky", Value::Float(10.0)).is_err());
+    assert_eq!(component.get_state("attempts").unwrap(), Value::Float(4.0));
+
+    let mut component = create_ready(runtime.as_ref());
+    assert!(component.execute_action("invalid", Value::Bool(true)).is_err());
+    assert_eq!(component.get_state("attempts").unwrap(), Value::Float(1.0));
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct EmitPlugin {
+    #[mylife_state]
+    value: State<bool>,
+}
+
+impl MylifePluginHooks for EmitPlugin {
+    fn new(_id: &str) -> Self {
+        EmitPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl EmitPlugin {
+    #[mylife_action]
+    fn set_value(&mut self, arg: bool) {
+        self.value.set(arg)
+    }
+
+    #[mylife_action]
+    fn force_value(&mut self, arg: bool) {
+        self.value.set_force(arg)
+    }
+}
+
+#[test]
+fn test_state_deduplicate() {
+    let runtime: Box<dyn MylifePluginRuntime> = EmitPlugin::runtime();
+    let mut component = create_ready(runtime.as_ref());
+
+    let emitted = Rc::new(Cell::new(0));
+    let handler_emitted = emitted.clone();
+    component.set_on_state(Box::new(move |_name, _value| {
+        handler_emitted.set(handler_emitted.get() + 1);
+    }));
+
+    component
+        .execute_action("setValue", Value::Bool(false))
+        .unwrap();
+    assert_eq!(emitted.get(), 0);
+
+    component.execute_action("setValue", Value::Bool(true)).unwrap();
+    component.execute_action("setValue", Value::Bool(true)).unwrap();
+    assert_eq!(emitted.get(), 1);
+
+    component
+        .execute_action("forceValue", Value::Bool(true))
+        .unwrap();
+    assert_eq!(emitted.get(), 2);
+}
+
+#[test]
+fn test_state_debounce() {
+    let runtime: Box<dyn MylifePluginRuntime> = EmitPlugin::runtime();
+    let mut component = create_ready(runtime.as_ref());
+
+    let emitted = Rc::new(RefCell::new(Vec::new()));
+    let handler_emitted = emitted.clone();
+    component.set_on_state(Box::new(move |_name, value| {
+        handler_emitted.borrow_mut().push(value);
+    }));
+
+    // the clock is injected: the interval is far longer than the test, which never sleeps
+    let interval = Duration::from_secs(3600);
+    assert!(component.set_state_debounce("unknown", interval).is_err());
+    component.set_state_debounce("value", interval).unwrap();
+
+    let start = Instant::now();
+    component.execute_action("setValue", Value::Bool(true)).unwrap();
+    component
+        .execute_action("setValue", Value::Bool(false))
+        .unwrap();
+    component.execute_action("setValue", Value::Bool(true)).unwrap();
+
+    component.process_debounced_states();
+    assert!(emitted.borrow().is_empty());
+
+    component.process_debounced_states_at(start + interval / 2);
+    assert!(emitted.borrow().is_empty());
+
+    component.process_debounced_states_at(Instant::now() + interval);
+    assert_eq!(*emitted.borrow(), vec![Value::Bool(true)]);
+
+    // disabling publishes pending value
+    component
+        .execute_action("setValue", Value::Bool(false))
+        .unwrap();
+    component
+        .set_state_debounce("value", Duration::ZERO)
+        .unwrap();
+    assert_eq!(
+        *emitted.borrow(),
+        vec![Value::Bool(true), Value::Bool(false)]
+    );
+
+    // terminate publishes pending value
+    component.set_state_debounce("value", interval).unwrap();
+    component.execute_action("setValue", Value::Bool(true)).unwrap();
+    assert_eq!(emitted.borrow().len(), 2);
+    component.terminate().unwrap();
+    assert_eq!(
+        *emitted.borrow(),
+        vec![Value::Bool(true), Value::Bool(false), Value::Bool(true)]
+    );
+}
+
+#[test]
+fn test_create_components() {
+    let runtime: Box<dyn MylifePluginRuntime> = EmitPlugin::runtime();
+    let mut components = runtime.create_components(&["comp-1", "comp-2", "comp-3"]);
+
+    let ids: Vec<&str> = components.iter().map(|component| component.id()).collect();
+    assert_eq!(ids, vec!["comp-1", "comp-2", "comp-3"]);
+
+    // state listeners stay bound to their own instance
+    let emitted = Rc::new(RefCell::new(Vec::new()));
+    for component in components.iter_mut() {
+        let handler_emitted = emitted.clone();
+        let id = component.id().to_string();
+        component.set_on_state(Box::new(move |_name, _value| {
+            handler_emitted.borrow_mut().push(id.clone());
+        }));
+        component.configure(&Config::new()).unwrap();
+        component.init().unwrap();
+    }
+
+    components[1]
+        .execute_action("setValue", Value::Bool(true))
+        .unwrap();
+    assert_eq!(*emitted.borrow(), vec!["comp-2"]);
+
+    // the state setup prepared for the batch applies to each component
+    let runtime: Box<dyn MylifePluginRuntime> = OverheatPlugin::runtime();
+    for mut component in runtime.create_components(&["comp-1", "comp-2"]) {
+        component.configure(&Config::new()).unwrap();
+        component.init().unwrap();
+        component
+            .execute_action("setOverheat", Value::Bool(true))
+            .unwrap();
+        component
+            .execute_action("setOverheat", Value::Bool(false))
+            .unwrap();
+        assert_eq!(component.get_state("fault").unwrap(), Value::Bool(true));
+    }
+}
+
+#[test]
+fn test_component_worker() {
+    let runtime: Arc<dyn MylifePluginRuntime> = Arc::from(EmitPlugin::runtime());
+    let worker = ComponentWorker::spawn(move || create_ready(runtime.as_ref()));
+
+    let (sender, receiver) = mpsc::channel();
+    worker
+        .post(move |component| {
+            component.set_on_state(Box::new(move |name, value| {
+                sender.send((name.to_string(), value)).unwrap();
+            }));
+        })
+        .unwrap();
+
+    let result = worker
+        .call(|component| {
+            component
+                .execute_action("setValue", Value::Bool(true))
+                .map_err(|err| err.to_string())
+        })
+        .unwrap();
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        receiver.recv_timeout(Duration::from_secs(1)).unwrap(),
+        ("value".to_string(), Value::Bool(true))
+    );
+
+    let result = worker
+        .call(|component| {
+            component
+                .execute_action("unknown", Value::Bool(true))
+                .map_err(|err| err.to_string())
+        })
+        .unwrap();
+    assert!(result.is_err());
+
+    // a panicking command stops the worker: later calls fail instead of panicking
+    let result = worker.call(|_component| -> () { panic!("command failure") });
+    assert_eq!(result, Err(WorkerStoppedError));
+    assert_eq!(
+        worker.call(|component| String::from(component.id())),
+        Err(WorkerStoppedError)
+    );
+    assert_eq!(worker.post(|_component| {}), Err(WorkerStoppedError));
+}
+
+#[test]
+fn test_sync_component() {
+    let runtime: Arc<dyn MylifePluginRuntime> = Arc::from(EmitPlugin::runtime());
+    let component = SyncComponent::spawn(move || runtime.create("comp-id")).unwrap();
+    assert_eq!(component.id(), "comp-id");
+
+    let (sender, receiver) = mpsc::channel();
+    component
+        .set_on_state(move |name, value| {
+            sender.send((name.to_string(), value)).unwrap();
+        })
+        .unwrap();
+
+    component.configure(Config::new()).unwrap();
+    component.init().unwrap();
+
+    let shared = Arc::new(Mutex::new(component.clone()));
+    let handler = thread::spawn(move || {
+        shared
+            .lock()
+            .unwrap()
+            .execute_action("setValue", Value::Bool(true))
+    });
+    assert_eq!(handler.join().unwrap().unwrap(), None);
+
+    assert_eq!(
+        receiver.recv_timeout(Duration::from_secs(1)).unwrap(),
+        ("value".to_string(), Value::Bool(true))
+    );
+    assert_eq!(component.get_state("value").unwrap(), Value::Bool(true));
+    assert!(component.execute_action("unknown", Value::Bool(true)).is_err());
+
+    component.terminate().unwrap();
+    assert_eq!(component.state().unwrap(), ComponentState::Terminated);
+}
+
+#[test]
+fn test_describe() {
+    let runtime: Box<dyn MylifePluginRuntime> = DurationPlugin::runtime();
+    let mut component = runtime.create("comp-id");
+
+    let snapshot = component.describe();
+    assert_eq!(snapshot.id(), "comp-id");
+    assert_eq!(snapshot.plugin(), "duration-plugin");
+    assert!(snapshot.config().is_empty());
+    assert_eq!(snapshot.state(), ComponentState::New);
+
+    let mut config = Config::new();
+    config.insert("delay".to_string(), ConfigValue::String("2s".into()));
+
+    component.configure(&config).unwrap();
+    component.init().unwrap();
+
+    let snapshot = component.describe();
+    assert_eq!(snapshot.config(), &config);
+    assert_eq!(
+        snapshot.states()["currentDelay"],
+        Value::Duration(Duration::from_secs(2))
+    );
+    assert_eq!(snapshot.state(), ComponentState::Ready);
+}
+
+#[test]
+fn test_debug_info() {
+    let runtime: Box<dyn MylifePluginRuntime> = DurationPlugin::runtime();
+    let components = runtime.create_components(&["comp-1", "comp-2"]);
+
+    let info = components[0].debug_info();
+    assert_eq!(info.id(), "comp-1");
+    assert_eq!(info.plugin(), "duration-plugin");
+    assert_eq!(info.state(), ComponentState::New);
+
+    let output = format!("{:?}", components);
+    assert!(output.contains("\"comp-1\""));
+    assert!(output.contains("\"comp-2\""));
+    assert!(output.contains("New"));
+}
+
+#[test]
+fn test_list_members() {
+    let runtime: Box<dyn MylifePluginRuntime> = ShutterPlugin::runtime();
+    let component = create_ready(runtime.as_ref());
+
+    assert_eq!(component.list_actions(), vec!["close", "open"]);
+    assert_eq!(component.list_states(), vec!["opened"]);
+}
+
+#[test]
+fn test_multiple_state_handlers() {
+    let runtime: Box<dyn MylifePluginRuntime> = EmitPlugin::runtime();
+    let mut component = create_ready(runtime.as_ref());
+
+    let emitted = Rc::new(RefCell::new(Vec::new()));
+
+    let handler_emitted = emitted.clone();
+    component.set_on_state(Box::new(move |_name, _value| {
+        handler_emitted.borrow_mut().push("replaced");
+    }));
+
+    let handler_emitted = emitted.clone();
+    component.set_on_state(Box::new(move |_name, _value| {
+        handler_emitted.borrow_mut().push("bus");
+    }));
+
+    let handler_emitted = emitted.clone();
+    component.add_on_state(Box::new(move |_name, _value| {
+        handler_emitted.borrow_mut().push("logger");
+    }));
+
+    component.execute_action("setValue", Value::Bool(true)).unwrap();
+    assert_eq!(*emitted.borrow(), vec!["bus", "logger"]);
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "sensor")]
+struct PolledPlugin {
+    #[mylife_state(mode = "polled")]
+    counter: State<u8>,
+
+    #[mylife_state]
+    pushed: State<bool>,
+}
+
+impl MylifePluginHooks for PolledPlugin {
+    fn new(_id: &str) -> Self {
+        PolledPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl PolledPlugin {
+    #[mylife_action]
+    fn increment(&mut self, times: u8) {
+        for _ in 0..times {
+            self.counter.set(self.counter.get() + 1);
+        }
+
+        self.pushed.set(true);
+    }
+}
+
+#[test]
+fn test_polled_state() {
+    let runtime: Box<dyn MylifePluginRuntime> = PolledPlugin::runtime();
+    let mut component = create_ready(runtime.as_ref());
+
+    let emitted = Rc::new(RefCell::new(Vec::new()));
+    let handler_emitted = emitted.clone();
+    component.set_on_state(Box::new(move |name, value| {
+        handler_emitted.borrow_mut().push((name.to_string(), value));
+    }));
+
+    component.execute_action("increment", Value::Range(3)).unwrap();
+    assert_eq!(
+        *emitted.borrow(),
+        vec![("pushed".to_string(), Value::Bool(true))]
+    );
+    assert_eq!(component.get_state("counter").unwrap(), Value::Range(3));
+
+    component.poll_states();
+    component.poll_states();
+    assert_eq!(
+        *emitted.borrow(),
+        vec![
+            ("pushed".to_string(), Value::Bool(true)),
+            ("counter".to_string(), Value::Range(3)),
+        ]
+    );
+}
+
+#[test]
+fn test_state_stream() {
+    let runtime: Box<dyn MylifePluginRuntime> = EmitPlugin::runtime();
+    let mut component = create_ready(runtime.as_ref());
+
+    let mut stream = component.state_stream(StreamBackpressure::Unbounded);
+    component.execute_action("setValue", Value::Bool(true)).unwrap();
+    component.execute_action("setValue", Value::Bool(false)).unwrap();
+
+    assert_eq!(stream.try_next(), Some(("value".to_string(), Value::Bool(true))));
+    assert_eq!(stream.try_next(), Some(("value".to_string(), Value::Bool(false))));
+    assert_eq!(stream.try_next(), None);
+
+    // replacing the handlers ends the stream
+    component.set_on_state(Box::new(|_name, _value| {}));
+    let next = stream.poll_next(&mut std::task::Context::from_waker(std::task::Waker::noop()));
+    assert_eq!(next, std::task::Poll::Ready(None));
+}
+
+#[test]
+fn test_lifecycle() {
+    let runtime: Box<dyn MylifePluginRuntime> = ReadyPlugin::runtime();
+    let mut component = runtime.create("comp-id");
+    assert_eq!(component.state(), ComponentState::New);
+
+    assert!(component.init().is_err());
+    assert_eq!(component.state(), ComponentState::New);
+
+    let mut config = Config::new();
+    config.insert("failInit".to_string(), ConfigValue::Bool(true));
+    component.configure(&config).unwrap();
+    assert_eq!(component.state(), ComponentState::Configured);
+
+    assert!(component.init().is_err());
+    assert_eq!(component.state(), ComponentState::Failed);
+
+    config.insert("failInit".to_string(), ConfigValue::Bool(false));
+    component.configure(&config).unwrap();
+    component.init().unwrap();
+    assert_eq!(component.state(), ComponentState::Ready);
+
+    assert!(component.configure(&config).is_err());
+    assert!(component.init().is_err());
+    assert_eq!(component.state(), ComponentState::Ready);
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct ReinitPlugin {
+    #[mylife_state]
+    init_count: State<u8>,
+}
+
+impl MylifePluginHooks for ReinitPlugin {
+    fn new(_id: &str) -> Self {
+        ReinitPlugin::default()
+    }
+
+    fn init(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.init_count.set(*self.init_count.get() + 1);
+
+        if *self.init_count.get() < 3 {
+            return Err(DeviceUnavailableError::new("not reachable yet").into());
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_reinit() {
+    let runtime: Box<dyn MylifePluginRuntime> = ReinitPlugin::runtime();
+    let mut component = runtime.create("comp-id");
+
+    let ready = Rc::new(Cell::new(0));
+    let handler_ready = ready.clone();
+    component.set_on_ready(Box::new(move || {
+        handler_ready.set(handler_ready.get() + 1);
+    }));
+
+    component.configure(&Config::new()).unwrap();
+    assert!(component.reinit().is_err());
+    assert_eq!(component.state(), ComponentState::Configured);
+
+    assert!(component.init().is_err());
+    assert!(component.reinit().is_err());
+    assert_eq!(component.state(), ComponentState::Failed);
+
+    component.reinit().unwrap();
+    assert_eq!(component.state(), ComponentState::Ready);
+    assert_eq!(component.get_state("initCount").unwrap(), Value::Range(3));
+    assert_eq!(ready.get(), 1);
+
+    assert!(component.reinit().is_err());
+}
+
+fn collect_fails(component: &mut dyn MylifeComponent) -> Rc<RefCell<Vec<FailContext>>> {
+    let fails = Rc::new(RefCell::new(Vec::new()));
+    let handler_fails = fails.clone();
+    component.set_on_fail(Box::new(move |context, _err| {
+        handler_fails.borrow_mut().push(context.clone());
+    }));
+    fails
+}
+
+#[test]
+fn test_on_fail() {
+    let runtime: Box<dyn MylifePluginRuntime> = ConfigPatternPlugin::runtime();
+    let mut component = runtime.create("comp-id");
+    let fails = collect_fails(component.as_mut());
+    let mut config = Config::new();
+    config.insert("host".to_string(), ConfigValue::String("Bad Host".into()));
+    assert!(component.configure(&config).is_err());
+    assert_eq!(*fails.borrow(), vec![FailContext::Config("host".into())]);
+
+    let runtime: Box<dyn MylifePluginRuntime> = ReadyPlugin::runtime();
+    let mut component = runtime.create("comp-id");
+    let fails = collect_fails(component.as_mut());
+    let mut config = Config::new();
+    config.insert("failInit".to_string(), ConfigValue::Bool(true));
+    component.configure(&config).unwrap();
+    assert!(component.init().is_err());
+    assert_eq!(*fails.borrow(), vec![FailContext::Init]);
+
+    let runtime: Box<dyn MylifePluginRuntime> = RetryPlugin::runtime();
+    let mut component = create_ready(runtime.as_ref());
+    let fails = collect_fails(component.as_mut());
+    component.execute_action("flaky", Value::Float(3.0)).unwrap();
+    assert!(component.execute_action("invalid", Value::Bool(true)).is_err());
+    assert_eq!(*fails.borrow(), vec![FailContext::Action("invalid".into())]);
+
+    // caller errors are not plugin failures
+    assert!(component.execute_action("unknown", Value::Bool(true)).is_err());
+    assert_eq!(fails.borrow().len(), 1);
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct DeviceWatchPlugin {
+    reporter: Option<FailReporter>,
+}
+
+impl MylifePluginHooks for DeviceWatchPlugin {
+    fn new(_id: &str) -> Self {
+        DeviceWatchPlugin::default()
+    }
+
+    fn set_fail_reporter(&mut self, reporter: FailReporter) {
+        self.reporter = Some(reporter);
+    }
+}
+
+#[mylife_actions]
+impl DeviceWatchPlugin {
+    // stands for a background check noticing the device is gone
+    #[mylife_action]
+    fn check(&mut self, _arg: bool) {
+        let err = DeviceUnavailableError::new("device lost");
+        self.reporter.as_ref().unwrap().report("device", &err);
+    }
+}
+
+#[test]
+fn test_on_fail_custom() {
+    let runtime: Box<dyn MylifePluginRuntime> = DeviceWatchPlugin::runtime();
+    let mut component = create_ready(runtime.as_ref());
+    let fails = collect_fails(component.as_mut());
+
+    // the action itself succeeds, the failure is reported by the plugin
+    component.execute_action("check", Value::Bool(true)).unwrap();
+    assert_eq!(*fails.borrow(), vec![FailContext::Custom("device".into())]);
+}
+
+#[test]
+fn test_action_before_init() {
+    let runtime: Box<dyn MylifePluginRuntime> = EmitPlugin::runtime();
+    let mut component = runtime.create("comp-id");
+
+    let err = component
+        .execute_action("setValue", Value::Bool(true))
+        .unwrap_err();
+    assert!(err.to_string().contains("New"));
+    assert_eq!(component.get_state("value").unwrap(), Value::Bool(false));
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct PollerPlugin {
+    #[mylife_state]
+    stopped: State<bool>,
+
+    cancellation: CancellationToken,
+    poller: Option<JoinHandle<()>>,
+}
+
+impl MylifePluginHooks for PollerPlugin {
+    fn new(_id: &str) -> Self {
+        PollerPlugin::default()
+    }
+
+    fn set_cancellation(&mut self, token: CancellationToken) {
+        self.cancellation = token;
+    }
+
+    fn init(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let token = self.cancellation.clone();
+        self.poller = Some(thread::spawn(move || {
+            while !token.wait_timeout(Duration::from_secs(10)) {}
+        }));
+
+        Ok(())
+    }
+
+    fn terminate(&mut self) {
+        if let Some(poller) = self.poller.take() {
+            poller.join().unwrap();
+        }
+
+        self.stopped.set(true);
+    }
+}
+
+#[test]
+fn test_terminate() {
+    let runtime: Box<dyn MylifePluginRuntime> = PollerPlugin::runtime();
+    let mut component = create_ready(runtime.as_ref());
+
+    let start = Instant::now();
+    component.terminate().unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(component.state(), ComponentState::Terminated);
+    assert_eq!(component.get_state("stopped").unwrap(), Value::Bool(true));
+
+    assert!(component.configure(&Config::new()).is_err());
+    assert!(component.terminate().is_err());
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct ClampPlugin {
+    #[mylife_config(range = "0..=100", clamp)]
+    level: u8,
+
+    #[mylife_config(range = "1..=65535")]
+    port: u32,
+
+    #[mylife_state(r#type = "range[0;100]", clamp, unit = "%")]
+    output: State<u8>,
+}
+
+impl MylifePluginHooks for ClampPlugin {
+    fn new(_id: &str) -> Self {
+        ClampPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl ClampPlugin {
+    #[mylife_action]
+    fn set_output(&mut self, arg: u8) {
+        self.output.set(arg);
+    }
+
+    #[mylife_action]
+    fn copy_config(&mut self, _arg: bool) {
+        self.output.set(self.level);
+    }
+}
+
+#[test]
+fn test_range_clamp() {
+    let runtime: Box<dyn MylifePluginRuntime> = ClampPlugin::runtime();
+    let level = &runtime.metadata().config()["level"];
+    assert_eq!(level.range(), Some((0, 100)));
+    assert!(level.clamp());
+    assert!(!runtime.metadata().config()["port"].clamp());
+    assert_eq!(runtime.metadata().members()["output"].unit(), Some("%"));
+
+    let mut component = runtime.create("comp-id");
+    let mut config = Config::new();
+    config.insert("level".to_string(), ConfigValue::Integer(150));
+    config.insert("port".to_string(), ConfigValue::Integer(0));
+    assert!(component.configure(&config).is_err());
+
+    config.insert("port".to_string(), ConfigValue::Integer(8080));
+    component.configure(&config).unwrap();
+    component.init().unwrap();
+
+    component
+        .execute_action("copyConfig", Value::Bool(true))
+        .unwrap();
+    assert_eq!(component.get_state("output").unwrap(), Value::Range(100));
+
+    component.execute_action("setOutput", Value::Range(42)).unwrap();
+    assert_eq!(component.get_state("output").unwrap(), Value::Range(42));
+
+    component
+        .execute_action("setOutput", Value::Range(200))
+        .unwrap();
+    assert_eq!(component.get_state("output").unwrap(), Value::Range(100));
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "actuator")]
+struct ShutterPlugin {
+    #[mylife_state]
+    opened: State<bool>,
+}
+
+impl MylifePluginHooks for ShutterPlugin {
+    fn new(_id: &str) -> Self {
+        ShutterPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl ShutterPlugin {
+    #[mylife_action(guard = "is_closed")]
+    fn open(&mut self, _arg: bool) {
+        self.opened.set(true);
+    }
+
+    #[mylife_action(guard = "is_open")]
+    fn close(&mut self, _arg: bool) {
+        self.opened.set(false);
+    }
+
+    fn is_open(&self) -> bool {
+        *self.opened.get()
+    }
+
+    fn is_closed(&self) -> bool {
+        !self.is_open()
+    }
+}
+
+#[test]
+fn test_action_guard() {
+    let runtime: Box<dyn MylifePluginRuntime> = ShutterPlugin::runtime();
+    let mut component = create_ready(runtime.as_ref());
+    let fails = collect_fails(component.as_mut());
+
+    let err = component
+        .execute_action("close", Value::Bool(true))
+        .unwrap_err();
+    assert!(err.to_string().contains("guard"));
+
+    component.execute_action("open", Value::Bool(true)).unwrap();
+    assert_eq!(component.get_state("opened").unwrap(), Value::Bool(true));
+    assert!(component.execute_action("open", Value::Bool(true)).is_err());
+
+    component.execute_action("close", Value::Bool(true)).unwrap();
+    assert_eq!(component.get_state("opened").unwrap(), Value::Bool(false));
+
+    // rejected actions are caller errors
+    assert!(fails.borrow().is_empty());
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "actuator")]
+struct RateLimitedRelayPlugin {
+    #[mylife_state]
+    toggles: State<u8>,
+}
+
+impl MylifePluginHooks for RateLimitedRelayPlugin {
+    fn new(_id: &str) -> Self {
+        RateLimitedRelayPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl RateLimitedRelayPlugin {
+    #[mylife_action(rate_limit = "2/h")]
+    fn toggle(&mut self, _arg: bool) {
+        self.toggles.set(*self.toggles.get() + 1);
+    }
+}
+
+#[test]
+fn test_action_rate_limit() {
+    let runtime: Box<dyn MylifePluginRuntime> = RateLimitedRelayPlugin::runtime();
+    let rate_limit = runtime.metadata().members()["toggle"].rate_limit().unwrap();
+    assert_eq!(rate_limit.count(), 2);
+    assert_eq!(rate_limit.period(), Duration::from_secs(3600));
+
+    let mut component = create_ready(runtime.as_ref());
+    let fails = collect_fails(component.as_mut());
+
+    let dropped = Rc::new(RefCell::new(Vec::new()));
+    let dropped_handler = dropped.clone();
+    component.set_on_action_dropped(Box::new(move |name: &str| {
+        dropped_handler.borrow_mut().push(String::from(name));
+    }));
+
+    component.execute_action("toggle", Value::Bool(true)).unwrap();
+    component.execute_action("toggle", Value::Bool(true)).unwrap();
+    let err = component
+        .execute_action("toggle", Value::Bool(true))
+        .unwrap_err();
+    assert!(err.to_string().contains("rate limit"));
+
+    assert_eq!(component.get_state("toggles").unwrap(), Value::Range(2));
+    assert_eq!(*dropped.borrow(), vec![String::from("toggle")]);
+    assert!(fails.borrow().is_empty());
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "logic")]
+struct ResetPlugin {
+    #[mylife_state]
+    counter: State<u8>,
+}
+
+impl MylifePluginHooks for ResetPlugin {
+    fn new(_id: &str) -> Self {
+        ResetPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl ResetPlugin {
+    #[mylife_action]
+    fn increment(&mut self, _arg: bool) {
+        self.counter.set(*self.counter.get() + 1);
+    }
+
+    // the new State is not bound to the component
+    #[mylife_action]
+    fn reset(&mut self, _arg: bool) {
+        self.counter = State::default();
+    }
+}
+
+#[test]
+fn test_rebind_states() {
+    let runtime: Box<dyn MylifePluginRuntime> = ResetPlugin::runtime();
+    let mut component = create_ready(runtime.as_ref());
+
+    // bound lazily: changes before the first handler are not reported
+    component
+        .execute_action("increment", Value::Bool(true))
+        .unwrap();
+
+    let changes = Rc::new(RefCell::new(Vec::new()));
+    let handler_changes = changes.clone();
+    component.set_on_state(Box::new(move |_name, value| {
+        handler_changes.borrow_mut().push(value);
+    }));
+
+    component
+        .execute_action("increment", Value::Bool(true))
+        .unwrap();
+    assert_eq!(*changes.borrow(), vec![Value::Range(2)]);
+
+    component.execute_action("reset", Value::Bool(true)).unwrap();
+    component
+        .execute_action("increment", Value::Bool(true))
+        .unwrap();
+    assert_eq!(changes.borrow().len(), 1);
+
+    component.rebind_states();
+    component
+        .execute_action("increment", Value::Bool(true))
+        .unwrap();
+    assert_eq!(*changes.borrow(), vec![Value::Range(2), Value::Range(2)]);
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "sensor")]
+struct ButtonPlugin {
+    #[mylife_state]
+    presses: State<u8>,
+
+    #[mylife_event(description = "button pressed, with the press duration")]
+    pressed: Event<Duration>,
+}
+
+impl MylifePluginHooks for ButtonPlugin {
+    fn new(_id: &str) -> Self {
+        ButtonPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl ButtonPlugin {
+    #[mylife_action]
+    fn press(&mut self, arg: u8) {
+        self.presses.set(*self.presses.get() + 1);
+        self.pressed.emit(Duration::from_millis(arg as u64 * 100));
+    }
+}
+
+#[test]
+fn test_events() {
+    let runtime: Box<dyn MylifePluginRuntime> = ButtonPlugin::runtime();
+    let member = &runtime.metadata().members()["pressed"];
+    assert_eq!(
+        member.member_type(),
+        core_plugin_runtime::metadata::MemberType::Event
+    );
+    assert_eq!(
+        member.value_type(),
+        &core_plugin_runtime::metadata::Type::Duration
+    );
+
+    let mut component = create_ready(runtime.as_ref());
+
+    // no handler: dropped
+    component.execute_action("press", Value::Range(1)).unwrap();
+
+    let states = Rc::new(RefCell::new(Vec::new()));
+    let handler_states = states.clone();
+    component.set_on_state(Box::new(move |name, _value| {
+        handler_states.borrow_mut().push(name.to_string());
+    }));
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let handler_events = events.clone();
+    component.set_on_event(Box::new(move |name, value| {
+        handler_events.borrow_mut().push((name.to_string(), value));
+    }));
+
+    component.execute_action("press", Value::Range(5)).unwrap();
+
+    assert_eq!(*states.borrow(), vec!["presses".to_string()]);
+    assert_eq!(
+        *events.borrow(),
+        vec![(
+            "pressed".to_string(),
+            Value::Duration(Duration::from_millis(500))
+        )]
+    );
+    assert!(component.get_state("pressed").is_err());
+    assert!(!component.describe().states().contains_key("pressed"));
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "sensor")]
+struct ThermometerPlugin {
+    #[mylife_state]
+    temperature: State<f64>,
+
+    simulated: bool,
+}
+
+impl MylifePluginHooks for ThermometerPlugin {
+    fn new(_id: &str) -> Self {
+        ThermometerPlugin::default()
+    }
+
+    fn set_simulated(&mut self, simulated: bool) {
+        self.simulated = simulated;
+    }
+
+    fn init(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.simulated {
+            return Err("No device".into());
+        }
+
+        self.temperature.set(21.5);
+        Ok(())
+    }
+}
+
+#[mylife_actions]
+impl ThermometerPlugin {}
+
+#[test]
+fn test_simulated() {
+    let runtime: Box<dyn MylifePluginRuntime> = ThermometerPlugin::runtime();
+
+    let mut component = runtime.create("comp-id");
+    assert!(!component.is_simulated());
+    component.configure(&Config::new()).unwrap();
+    assert!(component.init().is_err());
+
+    let mut component = runtime.create("comp-id");
+    component.set_simulated(true).unwrap();
+    assert!(component.is_simulated());
+    component.configure(&Config::new()).unwrap();
+    component.init().unwrap();
+    assert_eq!(component.get_state("temperature").unwrap(), Value::Float(21.5));
+
+    assert!(component.set_simulated(false).is_err());
+    assert!(component.is_simulated());
+}
+
+#[test]
+fn test_states_batch() {
+    let runtime: Box<dyn MylifePluginRuntime> = DerivedStatePlugin::runtime();
+    let mut component = create_ready(runtime.as_ref());
+
+    let states = Rc::new(RefCell::new(Vec::new()));
+    let handler_states = states.clone();
+    component.set_on_state(Box::new(move |name, value| {
+        handler_states.borrow_mut().push((name.to_string(), value));
+    }));
+
+    let mut run_batch = |component: &mut dyn MylifeComponent| {
+        component
+            .execute_action("setFirst", Value::Bool(true))
+            .unwrap();
+        component.with_batch(&mut |component| {
+            component
+                .execute_action("setSecond", Value::Bool(true))
+                .unwrap();
+        });
+        component
+            .execute_action("setFirst", Value::Bool(false))
+            .unwrap();
+    };
+
+    let expected = vec![
+        ("first".to_string(), Value::Bool(false)),
+        ("anyOn".to_string(), Value::Bool(true)),
+        ("second".to_string(), Value::Bool(true)),
+    ];
+
+    // no batch handler: coalesced changes go to the state handlers
+    component.with_batch(&mut run_batch);
+    assert_eq!(*states.borrow(), expected);
+    states.borrow_mut().clear();
+
+    let batches = Rc::new(RefCell::new(Vec::new()));
+    let handler_batches = batches.clone();
+    component.set_on_states_batch(Box::new(move |changes| {
+        handler_batches.borrow_mut().push(changes);
+    }));
+
+    // observers added with add_on_state (eg: state_stream, subscriptions) must see batched changes
+    let observed = Rc::new(RefCell::new(Vec::new()));
+    let handler_observed = observed.clone();
+    component.add_on_state(Box::new(move |name, value| {
+        handler_observed.borrow_mut().push((name.to_string(), value));
+    }));
+
+    component.with_batch(&mut |component| {
+        component
+            .execute_action("setSecond", Value::Bool(false))
+            .unwrap();
+    });
+
+    let expected = vec![
+        ("second".to_string(), Value::Bool(false)),
+        ("anyOn".to_string(), Value::Bool(false)),
+    ];
+    assert!(states.borrow().is_empty());
+    assert_eq!(*batches.borrow(), vec![expected.clone()]);
+    assert_eq!(*observed.borrow(), expected);
+
+    // outside of a batch, changes are reported as usual
+    component.execute_action("setFirst", Value::Bool(true)).unwrap();
+    assert_eq!(states.borrow().len(), 2);
+    assert_eq!(batches.borrow().len(), 1);
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "actuator")]
+struct ColorPlugin {
+    #[mylife_state]
+    color: State<String>,
+}
+
+impl MylifePluginHooks for ColorPlugin {
+    fn new(_id: &str) -> Self {
+        ColorPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl ColorPlugin {
+    #[mylife_action]
+    fn set_color(&mut self, red: u8, green: u8, blue: u8) {
+        self.color.set(format!("{red},{green},{blue}"));
+    }
+
+    #[mylife_action]
+    fn set_channel(
+        &mut self,
+        channel: String,
+        _level: u8,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        Err(format!("Cannot set {channel}").into())
+    }
+}
+
+#[test]
+fn test_multiple_args_action() {
+    use core_plugin_runtime::metadata::Type;
+
+    let runtime: Box<dyn MylifePluginRuntime> = ColorPlugin::runtime();
+    assert_eq!(
+        runtime.metadata().members()["setColor"].value_type(),
+        &Type::Object(vec![
+            ("red".to_string(), Type::Range(0, 255)),
+            ("green".to_string(), Type::Range(0, 255)),
+            ("blue".to_string(), Type::Range(0, 255)),
+        ])
+    );
+    assert_eq!(
+        runtime.metadata().members()["setChannel"].value_type(),
+        &Type::Object(vec![
+            ("channel".to_string(), Type::Text),
+            ("level".to_string(), Type::Range(0, 255)),
+        ])
+    );
+
+    let mut component = create_ready(runtime.as_ref());
+
+    // by name
+    component
+        .execute_action(
+            "setColor",
+            Value::Object(BTreeMap::from([
+                ("red".to_string(), Value::Range(255)),
+                ("green".to_string(), Value::Range(128)),
+                ("blue".to_string(), Value::Range(0)),
+            ])),
+        )
+        .unwrap();
+    assert_eq!(
+        component.get_state("color").unwrap(),
+        Value::Text("255,128,0".to_string())
+    );
+
+    // by position
+    component
+        .execute_action(
+            "setColor",
+            Value::List(vec![Value::Range(1), Value::Range(2), Value::Range(3)]),
+        )
+        .unwrap();
+    assert_eq!(
+        component.get_state("color").unwrap(),
+        Value::Text("1,2,3".to_string())
+    );
+
+    assert!(component
+        .execute_action("setColor", Value::List(vec![Value::Range(1)]))
+        .is_err());
+    assert!(component
+        .execute_action("setColor", Value::Range(1))
+        .is_err());
+
+    let err = component
+        .execute_action(
+            "setChannel",
+            Value::List(vec![Value::Text("red".to_string()), Value::Range(1)]),
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Cannot set red");
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "actuator")]
+struct LinkPlugin {
+    #[mylife_state]
+    connected: State<bool>,
+}
+
+impl MylifePluginHooks for LinkPlugin {
+    fn new(_id: &str) -> Self {
+        LinkPlugin::default()
+    }
+
+    fn health(&self) -> Health {
+        if *self.connected.get() {
+            Health::Ok
+        } else {
+            Health::Degraded(String::from("device unreachable"))
+        }
+    }
+}
+
+#[mylife_actions]
+impl LinkPlugin {
+    #[mylife_action]
+    fn set_connected(&mut self, arg: bool) {
+        self.connected.set(arg);
+    }
+}
+
+#[test]
+fn test_health() {
+    let runtime: Box<dyn MylifePluginRuntime> = LinkPlugin::runtime();
+    let mut component = runtime.create("comp-id");
+    assert_eq!(
+        component.health(),
+        Health::Degraded(String::from("not initialized"))
+    );
+
+    component.configure(&Config::new()).unwrap();
+    component.init().unwrap();
+    assert_eq!(
+        component.health(),
+        Health::Degraded(String::from("device unreachable"))
+    );
+
+    component
+        .execute_action("setConnected", Value::Bool(true))
+        .unwrap();
+    assert_eq!(component.health(), Health::Ok);
+
+    component.terminate().unwrap();
+    assert_eq!(component.health(), Health::Failed(String::from("terminated")));
+
+    // no hook: Ok once Ready
+    let runtime: Box<dyn MylifePluginRuntime> = ReinitPlugin::runtime();
+    let mut component = runtime.create("comp-id");
+    component.configure(&Config::new()).unwrap();
+    assert!(component.init().is_err());
+    assert_eq!(
+        component.health(),
+        Health::Failed(String::from("Device unavailable: not reachable yet"))
+    );
+
+    assert!(component.reinit().is_err());
+    component.reinit().unwrap();
+    assert_eq!(component.health(), Health::Ok);
+}
+
+#[derive(MylifePlugin, Default)]
+#[mylife_plugin(usage = "actuator")]
+struct RelayPlugin {
+    #[mylife_state(history = 3)]
+    on: State<bool>,
+
+    #[mylife_state]
+    toggles: State<f64>,
+}
+
+impl MylifePluginHooks for RelayPlugin {
+    fn new(_id: &str) -> Self {
+        RelayPlugin::default()
+    }
+}
+
+#[mylife_actions]
+impl RelayPlugin {
+    #[mylife_action]
+    fn toggle(&mut self, _arg: bool) {
+        self.on.set(!*self.on.get());
+        self.toggles.set(*self.toggles.get() + 1.0);
+    }
+}
+
+#[test]
+fn test_state_history() {
+    let runtime: Box<dyn MylifePluginRuntime> = RelayPlugin::runtime();
+    let mut component = create_ready(runtime.as_ref());
+    assert!(component.state_history("on").unwrap().is_empty());
+
+    for _ in 0..4 {
+        component.execute_action("toggle", Value::Bool(true)).unwrap();
+    }
+
+    let history = component.state_history("on").unwrap();
+    let values: Vec<Value> = history.iter().map(|(_, value)| value.clone()).collect();
+    assert_eq!(
+        values,
+        vec![Value::Bool(false), Value::Bool(true), Value::Bool(false)]
+    );
+    assert!(history.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+
+    assert!(component.state_history("toggles").unwrap().is_empty());
+    assert!(component.state_history("unknown").is_err());
+}
+
+// the type of a compiled out plugin is not needed
+core_plugin_runtime::mylife_register_plugins!(
+    KeypadPlugin,
+    #[cfg(all())]
+    RgbLightPlugin,
+    #[cfg(any())]
+    GpioOnlyPlugin,
+);
+
+#[derive(Default)]
+struct NamesRegistry(Vec<String>);
+
+impl core_plugin_runtime::PluginRegistry for NamesRegistry {
+    fn register_plugin(
+        &mut self,
+        plugin: Box<dyn MylifePluginRuntime>,
+    ) -> Result<(), core_plugin_runtime::RegistrationError> {
+        self.0.push(plugin.metadata().name().to_string());
+        Ok(())
+    }
+}
+
+#[test]
+fn test_register_plugins() {
+    let mut registry = NamesRegistry::default();
+    (mylife_home_core_module_declaration.register)(&mut registry);
+    assert_eq!(registry.0, ["keypad-plugin", "rgb-light-plugin"]);
+
+    let metadata = (mylife_home_core_module_metadata.plugins)();
+    assert_eq!(metadata.len(), 2);
+    assert!(metadata[0].contains("\"keypad-plugin\""));
+}
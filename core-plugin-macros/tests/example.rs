@@ -1,6 +1,6 @@
 use core_plugin_macros::{mylife_actions, MylifePlugin};
 use core_plugin_runtime::{
-    metadata::{ConfigType, PluginUsage, Type},
+    metadata::{ConfigType, PluginCategory, PluginUsage, Type},
     runtime::MylifePluginRuntime,
     MylifePlugin, MylifePluginHooks, State,
 };
@@ -12,8 +12,11 @@ mod utils;
 #[derive(MylifePlugin)]
 #[mylife_plugin(
     name = "example-plugin", // Optional, infered from struct name
+    id = "example", // Optional, stable plugin id (defaults to "<module>.<name>")
     description = "plugin description", // Optional
-    usage = "logic"
+    usage = "logic",
+    category = "lighting", // Optional, for UI organization
+    tags("actuator", "dimmable") // Optional, for UI filtering
 )]
 struct ExamplePlugin {
     #[allow(dead_code)]
@@ -29,7 +32,8 @@ struct ExamplePlugin {
     #[mylife_state(
       name = "stateValue", // Optional, infered from field name
       description = "state description", // Optional
-      r#type = "bool"
+      r#type = "bool",
+      actions("action1", "action2") // Optional, actions that drive this state
     )]
     state_value: State<bool>,
 }
@@ -101,4 +105,11 @@ fn test_example() {
     expected.add_action("action2", Some("action description"), Type::Bool);
 
     assert_eq!(TestMetadata::from_metadata(meta), expected);
+    assert_eq!(
+        meta.members()["stateValue"].actions(),
+        &["action1", "action2"]
+    );
+    assert_eq!(meta.id(), Some("example"));
+    assert_eq!(meta.category(), Some(PluginCategory::Lighting));
+    assert_eq!(meta.tags(), &["actuator", "dimmable"]);
 }
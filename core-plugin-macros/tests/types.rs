@@ -42,6 +42,20 @@ struct TestPlugin {
         r#type = "enum{one,two,three}"
     )]
     state_enum: State<String>,
+
+    #[mylife_state(
+        name = "stateList",
+        description = "state description",
+        r#type = "list[range[0;42]]"
+    )]
+    state_list: State<Vec<i64>>,
+
+    #[mylife_state(
+        name = "stateNullable",
+        description = "state description",
+        r#type = "nullable[enum{ one, two , three }]"
+    )]
+    state_nullable: State<Option<String>>,
     // Complex: not implemented
 }
 
@@ -79,6 +93,20 @@ fn test_typing() {
             "three".to_string(),
         ]),
     );
+    expected.add_state(
+        "stateList",
+        Some("state description"),
+        Type::List(Box::new(Type::Range(0, 42))),
+    );
+    expected.add_state(
+        "stateNullable",
+        Some("state description"),
+        Type::Nullable(Box::new(Type::Enum(vec![
+            "one".to_string(),
+            "two".to_string(),
+            "three".to_string(),
+        ]))),
+    );
 
     assert_eq!(TestMetadata::from_metadata(meta), expected);
 }
@@ -28,15 +28,30 @@ struct TestPlugin {
     #[mylife_config(name = "configFloat", description = "config description")]
     config_float: f64,
 
-    // Range: cannot infer
+    #[mylife_config(name = "configSmallInteger", description = "config description")]
+    config_small_integer: u8,
+
+    // Range: inferred from small integer bounds, cannot infer from i64/u64
+    #[mylife_state(name = "stateRange", description = "state description")]
+    state_range: State<u8>,
+
     #[mylife_state(name = "stateText", description = "state description")]
     state_text: State<String>,
 
     #[mylife_state(name = "stateFloat", description = "state description")]
     state_float: State<f64>,
 
+    #[mylife_state(name = "stateSmallFloat", description = "state description")]
+    state_small_float: State<f32>,
+
     #[mylife_state(name = "stateBool", description = "state description")]
     state_bool: State<bool>,
+
+    #[mylife_state(name = "stateList", description = "state description")]
+    state_list: State<Vec<bool>>,
+
+    #[mylife_state(name = "stateNullable", description = "state description")]
+    state_nullable: State<Option<f64>>,
     // Enum: cannot infer
 
     // Complex: not implemented
@@ -75,9 +90,26 @@ fn test_typing() {
         ConfigType::Integer,
     );
     expected.add_config("configFloat", Some("config description"), ConfigType::Float);
+    expected.add_config(
+        "configSmallInteger",
+        Some("config description"),
+        ConfigType::Integer,
+    );
+    expected.add_state("stateRange", Some("state description"), Type::Range(0, 255));
     expected.add_state("stateText", Some("state description"), Type::Text);
     expected.add_state("stateFloat", Some("state description"), Type::Float);
+    expected.add_state("stateSmallFloat", Some("state description"), Type::Float);
     expected.add_state("stateBool", Some("state description"), Type::Bool);
+    expected.add_state(
+        "stateList",
+        Some("state description"),
+        Type::List(Box::new(Type::Bool)),
+    );
+    expected.add_state(
+        "stateNullable",
+        Some("state description"),
+        Type::Nullable(Box::new(Type::Float)),
+    );
 
     assert_eq!(TestMetadata::from_metadata(meta), expected);
 }
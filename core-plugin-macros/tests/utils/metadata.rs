@@ -41,6 +41,9 @@ impl TestMetadata {
                 MemberType::State => {
                     meta.add_state(name, member.description(), member.value_type().clone());
                 }
+                MemberType::Event => {
+                    meta.add_event(name, member.description(), member.value_type().clone());
+                }
             }
         }
 
@@ -92,6 +95,24 @@ impl TestMetadata {
         self
     }
 
+    pub fn add_event(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        value_type: Type,
+    ) -> &mut Self {
+        self.members.push(TestMember {
+            name: String::from(name),
+            description: description.map(str::to_string),
+            member_type: MemberType::Event,
+            value_type,
+        });
+
+        self.members.sort_by_key(|member| member.name.clone());
+
+        self
+    }
+
     pub fn add_action(
         &mut self,
         name: &str,
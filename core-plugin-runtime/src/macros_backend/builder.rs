@@ -1,60 +1,113 @@
-use std::collections::HashMap;
+use regex::Regex;
+use std::{collections::HashMap, sync::atomic::AtomicBool, time::Duration};
 
 use crate::{
-    metadata::{ConfigItem, ConfigType, Member, MemberType, PluginMetadata, PluginUsage, Type},
+    metadata::{ConfigType, PluginCategory, PluginMetadataBuilder, PluginUsage, RateLimit, Type},
     runtime::MylifePluginRuntime,
     MylifePlugin,
 };
 
 use super::{
-    ActionRuntimeExecutor, ConfigRuntimeSetter, PluginRuntimeAccess, PluginRuntimeImpl,
-    StateRuntime, StateRuntimeGetter, StateRuntimeRegister,
+    ActionRetryPolicy, ActionRuntime, ActionRuntimeExecutor, ActionRuntimeGuard, ConfigRuntime,
+    ConfigRuntimeSetter, EventRuntime, EventRuntimeRegister, ExtraConfigRuntimeSetter,
+    PluginRuntimeAccess, PluginRuntimeImpl, StateRuntime, StateRuntimeClamp, StateRuntimeGetter,
+    StateRuntimeHistoryEnable, StateRuntimeHistoryGetter, StateRuntimeLatchEnable,
+    StateRuntimePolledEnable, StateRuntimeRegister,
 };
 
 pub struct PluginRuntimeBuilder<PluginType: MylifePlugin + 'static> {
-    name: Option<String>,
-    usage: Option<PluginUsage>,
-    description: Option<String>,
-    members: HashMap<String, Member>,
-    config: HashMap<String, ConfigItem>,
-    config_runtime: HashMap<String, ConfigRuntimeSetter<PluginType>>,
+    metadata: PluginMetadataBuilder,
+    config_runtime: Vec<(String, ConfigRuntime<PluginType>)>,
+    extra_config_runtime: Option<ExtraConfigRuntimeSetter<PluginType>>,
     state_runtime: HashMap<String, StateRuntime<PluginType>>,
-    action_runtime: HashMap<String, ActionRuntimeExecutor<PluginType>>,
+    event_runtime: HashMap<String, EventRuntime<PluginType>>,
+    action_runtime: HashMap<String, ActionRuntime<PluginType>>,
 }
 
 impl<PluginType: MylifePlugin + 'static> PluginRuntimeBuilder<PluginType> {
     pub fn new() -> Self {
         PluginRuntimeBuilder {
-            name: None,
-            usage: None,
-            description: None,
-            members: HashMap::new(),
-            config: HashMap::new(),
-            config_runtime: HashMap::new(),
+            metadata: PluginMetadataBuilder::new(),
+            config_runtime: Vec::new(),
+            extra_config_runtime: None,
             state_runtime: HashMap::new(),
+            event_runtime: HashMap::new(),
             action_runtime: HashMap::new(),
         }
     }
 
-    pub fn build(self) -> Box<dyn MylifePluginRuntime> {
-        let generator_panic = "Plugin macros error: name has not been set, this indicates an incorrect behavior in the macro code generator";
+    pub fn build(mut self) -> Box<dyn MylifePluginRuntime> {
+        let metadata = self
+            .metadata
+            .required_capabilities(PluginType::required_capabilities())
+            .build()
+            .unwrap_or_else(|err| panic!("Plugin macros error: {}", err));
 
-        PluginRuntimeImpl::<PluginType>::new(
-            PluginMetadata::new(
-                self.name.expect(generator_panic),
-                self.usage.expect(generator_panic),
-                self.description,
-                self.members,
-                self.config,
-            ),
-            PluginRuntimeAccess::new(self.config_runtime, self.state_runtime, self.action_runtime),
-        )
+        for (name, action) in self.action_runtime.iter_mut() {
+            action.default_arg = metadata
+                .members()
+                .get(name)
+                .and_then(|member| member.default_arg().cloned());
+            action.rate_limit = metadata
+                .members()
+                .get(name)
+                .and_then(|member| member.rate_limit());
+            action.deprecated = metadata
+                .members()
+                .get(name)
+                .and_then(|member| member.deprecated())
+                .map(String::from);
+        }
+
+        let access = PluginRuntimeAccess::new(
+            self.config_runtime,
+            self.extra_config_runtime,
+            self.state_runtime,
+            self.event_runtime,
+            self.action_runtime,
+        );
+
+        access
+            .check_metadata(&metadata)
+            .unwrap_or_else(|err| panic!("Plugin macros error: {}", err));
+
+        PluginRuntimeImpl::<PluginType>::new(metadata, access)
     }
 
     pub fn set_plugin(&mut self, name: &str, description: Option<&str>, usage: PluginUsage) {
-        self.name = Some(String::from(name));
-        self.description = description.map(String::from);
-        self.usage = Some(usage);
+        self.metadata
+            .name(name)
+            .description(description)
+            .usage(usage);
+    }
+
+    pub fn set_plugin_id(&mut self, id: &str) {
+        self.metadata.id(id);
+    }
+
+    pub fn set_plugin_version(&mut self, version: &str) {
+        self.metadata.version(version);
+    }
+
+    pub fn set_plugin_deprecated(&mut self, message: &str) {
+        self.metadata.deprecated(message);
+    }
+
+    // states, events and actions. Deprecated actions are logged on their first execution
+    pub fn set_member_deprecated(&mut self, name: &str, message: &str) {
+        self.metadata.set_member_deprecated(name, message);
+    }
+
+    pub fn set_config_deprecated(&mut self, name: &str, message: &str) {
+        self.metadata.set_config_deprecated(name, message);
+    }
+
+    pub fn set_plugin_category(&mut self, category: Option<PluginCategory>, tags: &[&str]) {
+        self.metadata.category(category).tags(tags);
+    }
+
+    pub fn register_complex_type(&mut self, name: &str, fields: Vec<(String, Type)>) {
+        self.metadata.register_complex_type(name, fields);
     }
 
     pub fn add_config(
@@ -64,9 +117,71 @@ impl<PluginType: MylifePlugin + 'static> PluginRuntimeBuilder<PluginType> {
         value_type: ConfigType,
         setter: ConfigRuntimeSetter<PluginType>,
     ) {
-        let config_item = ConfigItem::new(description.map(String::from), value_type);
-        self.config.insert(String::from(name), config_item);
-        self.config_runtime.insert(String::from(name), setter);
+        self.metadata.add_config(name, description, value_type);
+        self.config_runtime.push((
+            String::from(name),
+            ConfigRuntime {
+                setter,
+                pattern: None,
+                range: None,
+                nullable: false,
+                secret: false,
+            },
+        ));
+    }
+
+    pub fn set_config_pattern(&mut self, name: &str, pattern: &str) {
+        self.metadata.set_config_pattern(name, pattern);
+
+        // Note: invalid pattern is reported at build by metadata
+        if let Some((_, config)) = self
+            .config_runtime
+            .iter_mut()
+            .find(|(config_name, _)| config_name == name)
+        {
+            config.pattern = Regex::new(pattern).ok();
+        }
+    }
+
+    pub fn set_config_range(&mut self, name: &str, min: i64, max: i64, clamp: bool) {
+        self.metadata.set_config_range(name, min, max, clamp);
+
+        if let Some((_, config)) = self
+            .config_runtime
+            .iter_mut()
+            .find(|(config_name, _)| config_name == name)
+        {
+            config.range = Some((min, max, clamp));
+        }
+    }
+
+    pub fn set_config_nullable(&mut self, name: &str) {
+        self.metadata.set_config_nullable(name);
+
+        if let Some((_, config)) = self
+            .config_runtime
+            .iter_mut()
+            .find(|(config_name, _)| config_name == name)
+        {
+            config.nullable = true;
+        }
+    }
+
+    pub fn set_config_secret(&mut self, name: &str) {
+        self.metadata.set_config_secret(name);
+
+        if let Some((_, config)) = self
+            .config_runtime
+            .iter_mut()
+            .find(|(config_name, _)| config_name == name)
+        {
+            config.secret = true;
+        }
+    }
+
+    pub fn set_extra_config(&mut self, setter: ExtraConfigRuntimeSetter<PluginType>) {
+        self.metadata.accept_extra_config();
+        self.extra_config_runtime = Some(setter);
     }
 
     pub fn add_state(
@@ -74,13 +189,84 @@ impl<PluginType: MylifePlugin + 'static> PluginRuntimeBuilder<PluginType> {
         name: &str,
         description: Option<&str>,
         value_type: Type,
+        derived: bool,
         register: StateRuntimeRegister<PluginType>,
         getter: StateRuntimeGetter<PluginType>,
     ) {
-        let member = Member::new(description.map(String::from), MemberType::State, value_type);
-        self.members.insert(String::from(name), member);
-        self.state_runtime
-            .insert(String::from(name), StateRuntime { register, getter });
+        self.metadata.add_state(name, description, value_type);
+        self.state_runtime.insert(
+            String::from(name),
+            StateRuntime {
+                register,
+                getter,
+                derived,
+                from_config: false,
+                clamp: None,
+                history: None,
+                polled: None,
+                latch: None,
+            },
+        );
+    }
+
+    pub fn add_event(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        value_type: Type,
+        register: EventRuntimeRegister<PluginType>,
+    ) {
+        self.metadata.add_event(name, description, value_type);
+        self.event_runtime
+            .insert(String::from(name), EventRuntime { register });
+    }
+
+    pub fn set_state_unit(&mut self, name: &str, unit: &str) {
+        self.metadata.set_state_unit(name, unit);
+    }
+
+    pub fn set_config_unit(&mut self, name: &str, unit: &str) {
+        self.metadata.set_config_unit(name, unit);
+    }
+
+    pub fn set_state_clamp(&mut self, name: &str, clamp: StateRuntimeClamp<PluginType>) {
+        if let Some(state) = self.state_runtime.get_mut(name) {
+            state.clamp = Some(clamp);
+        }
+    }
+
+    pub fn set_state_history(
+        &mut self,
+        name: &str,
+        enable: StateRuntimeHistoryEnable<PluginType>,
+        getter: StateRuntimeHistoryGetter<PluginType>,
+    ) {
+        if let Some(state) = self.state_runtime.get_mut(name) {
+            state.history = Some((enable, getter));
+        }
+    }
+
+    pub fn set_state_polled(&mut self, name: &str, enable: StateRuntimePolledEnable<PluginType>) {
+        if let Some(state) = self.state_runtime.get_mut(name) {
+            state.polled = Some(enable);
+        }
+    }
+
+    // the acknowledge action is declared separately, with add_action
+    pub fn set_state_latched(&mut self, name: &str, enable: StateRuntimeLatchEnable<PluginType>) {
+        if let Some(state) = self.state_runtime.get_mut(name) {
+            state.latch = Some(enable);
+        }
+    }
+
+    pub fn set_state_from_config(&mut self, name: &str) {
+        if let Some(state) = self.state_runtime.get_mut(name) {
+            state.from_config = true;
+        }
+    }
+
+    pub fn link_state_actions(&mut self, name: &str, actions: &[&str]) {
+        self.metadata.link_state_actions(name, actions);
     }
 
     pub fn add_action(
@@ -88,15 +274,48 @@ impl<PluginType: MylifePlugin + 'static> PluginRuntimeBuilder<PluginType> {
         name: &str,
         description: Option<&str>,
         value_type: Type,
+        result_type: Option<Type>,
         executor: ActionRuntimeExecutor<PluginType>,
     ) {
-        let member = Member::new(
-            description.map(String::from),
-            MemberType::Action,
-            value_type,
+        if let Some(result_type) = result_type {
+            self.metadata
+                .add_action_with_result(name, description, value_type, result_type);
+        } else {
+            self.metadata.add_action(name, description, value_type);
+        }
+        self.action_runtime.insert(
+            String::from(name),
+            ActionRuntime {
+                executor,
+                retry: None,
+                default_arg: None,
+                guard: None,
+                rate_limit: None,
+                deprecated: None,
+                deprecation_logged: AtomicBool::new(false),
+            },
         );
-        self.members.insert(String::from(name), member);
-        self.action_runtime.insert(String::from(name), executor);
+    }
+
+    pub fn set_action_default_arg(&mut self, name: &str, value: &str) {
+        self.metadata.set_action_default_arg(name, value);
+    }
+
+    pub fn set_action_rate_limit(&mut self, name: &str, count: u32, period: Duration) {
+        self.metadata
+            .set_action_rate_limit(name, RateLimit::new(count, period));
+    }
+
+    pub fn set_action_retry(&mut self, name: &str, retries: u32, backoff: Duration) {
+        if let Some(action) = self.action_runtime.get_mut(name) {
+            action.retry = Some(ActionRetryPolicy { retries, backoff });
+        }
+    }
+
+    pub fn set_action_guard(&mut self, name: &str, guard: ActionRuntimeGuard<PluginType>) {
+        if let Some(action) = self.action_runtime.get_mut(name) {
+            action.guard = Some(guard);
+        }
     }
 }
 
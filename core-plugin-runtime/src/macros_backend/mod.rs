@@ -1,5 +1,7 @@
 mod builder;
 mod runtime;
+mod value;
 
 pub use builder::*;
 pub use runtime::*;
+pub use value::*;
@@ -1,10 +1,28 @@
-use log::trace;
-use std::{cell::RefCell, collections::HashMap, fmt, sync::Arc};
+use log::{debug, trace, warn, Level, LevelFilter};
+use regex::Regex;
+use std::{
+    any::{Any, TypeId},
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fmt,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
 
 use crate::{
-    metadata::PluginMetadata,
-    runtime::{Config, ConfigValue, MylifeComponent, MylifePluginRuntime, Value},
-    MylifePlugin,
+    metadata::{MemberType, PluginMetadata, RateLimit},
+    runtime::{
+        ActionDroppedHandler, ComponentDebug, ComponentSnapshot, ComponentState, Config,
+        ConfigValue, DeviceUnavailableError, FailContext, FailHandler, FailReporter, Health,
+        MylifeComponent, MylifePluginRuntime, StateChanges, StateHandler, StatesBatchHandler,
+        Value,
+    },
+    CancellationToken, MylifePlugin,
 };
 
 pub struct PluginRuntimeImpl<PluginType: MylifePlugin + 'static> {
@@ -27,90 +45,836 @@ impl<PluginType: MylifePlugin> MylifePluginRuntime for PluginRuntimeImpl<PluginT
     }
 
     fn create(&self, id: &str) -> Box<dyn MylifeComponent> {
-        ComponentImpl::<PluginType>::new(&self.access, id)
+        ComponentImpl::new(
+            &ComponentPrototype::new(&self.access, self.metadata.name()),
+            id,
+        )
+    }
+
+    fn create_components(&self, ids: &[&str]) -> Vec<Box<dyn MylifeComponent>> {
+        let prototype = ComponentPrototype::new(&self.access, self.metadata.name());
+
+        ids.iter()
+            .map(|id| ComponentImpl::new(&prototype, id) as Box<dyn MylifeComponent>)
+            .collect()
     }
+
+    fn plugin_type_id(&self) -> Option<TypeId> {
+        Some(TypeId::of::<PluginType>())
+    }
+}
+
+pub struct ConfigRuntime<PluginType> {
+    pub(crate) setter: ConfigRuntimeSetter<PluginType>,
+    pub(crate) pattern: Option<Regex>,
+    pub(crate) range: Option<(i64, i64, bool)>, // min, max, clamp
+    pub(crate) nullable: bool,
+    pub(crate) secret: bool, // value redacted in logs, errors and describe
 }
 
 pub struct StateRuntime<PluginType> {
     pub(crate) register: StateRuntimeRegister<PluginType>,
     pub(crate) getter: StateRuntimeGetter<PluginType>,
+    pub(crate) derived: bool,
+    pub(crate) from_config: bool, // config value, emitted on each configure
+    pub(crate) clamp: Option<StateRuntimeClamp<PluginType>>, // installed on creation, not with the listener
+    pub(crate) history: Option<(
+        StateRuntimeHistoryEnable<PluginType>,
+        StateRuntimeHistoryGetter<PluginType>,
+    )>, // same
+    pub(crate) polled: Option<StateRuntimePolledEnable<PluginType>>, // same
+    pub(crate) latch: Option<StateRuntimeLatchEnable<PluginType>>, // same
+}
+
+pub struct EventRuntime<PluginType> {
+    pub(crate) register: EventRuntimeRegister<PluginType>,
+}
+
+pub struct ActionRuntime<PluginType> {
+    pub(crate) executor: ActionRuntimeExecutor<PluginType>,
+    pub(crate) retry: Option<ActionRetryPolicy>,
+    pub(crate) default_arg: Option<Value>, // from metadata
+    pub(crate) guard: Option<ActionRuntimeGuard<PluginType>>,
+    pub(crate) rate_limit: Option<RateLimit>, // from metadata
+    pub(crate) deprecated: Option<String>,    // same
+    pub(crate) deprecation_logged: AtomicBool, // warned once per plugin, not per component
+}
+
+// retry on DeviceUnavailableError only, other errors fail immediately
+#[derive(Debug, Clone, Copy)]
+pub struct ActionRetryPolicy {
+    pub(crate) retries: u32,
+    pub(crate) backoff: Duration,
 }
 
 pub type ConfigRuntimeSetter<PluginType> =
     fn(target: &mut PluginType, config: ConfigValue) -> Result<(), Box<dyn std::error::Error>>;
+pub type ExtraConfigRuntimeSetter<PluginType> = fn(target: &mut PluginType, config: Config);
 pub type StateRuntimeRegister<PluginType> =
     fn(target: &mut PluginType, listener: Box<dyn Fn(Value)>) -> ();
+pub type EventRuntimeRegister<PluginType> =
+    fn(target: &mut PluginType, listener: Box<dyn Fn(Value)>) -> ();
+pub type StateRuntimeClamp<PluginType> = fn(target: &mut PluginType);
+pub type StateRuntimeHistoryEnable<PluginType> = fn(target: &mut PluginType);
+pub type StateRuntimeLatchEnable<PluginType> = fn(target: &mut PluginType);
+pub type StateRuntimePolledEnable<PluginType> =
+    fn(target: &mut PluginType, changed: Arc<AtomicBool>);
+pub type StateRuntimeHistoryGetter<PluginType> =
+    fn(target: &PluginType) -> Vec<(SystemTime, Value)>;
 pub type StateRuntimeGetter<PluginType> = fn(target: &PluginType) -> Value;
+// action is rejected while the guard returns false
+pub type ActionRuntimeGuard<PluginType> = fn(target: &PluginType) -> bool;
 pub type ActionRuntimeExecutor<PluginType> =
-    fn(target: &mut PluginType, action: Value) -> Result<(), Box<dyn std::error::Error>>;
+    fn(
+        target: &mut PluginType,
+        name: &str,
+        action: Value,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error>>;
 
 pub struct PluginRuntimeAccess<PluginType: MylifePlugin> {
-    configs: HashMap<String, ConfigRuntimeSetter<PluginType>>,
+    configs: Vec<(String, ConfigRuntime<PluginType>)>, // declaration order, setters run in that order
+    extra_config: Option<ExtraConfigRuntimeSetter<PluginType>>, // receives undeclared keys
     states: HashMap<String, StateRuntime<PluginType>>,
-    actions: HashMap<String, ActionRuntimeExecutor<PluginType>>,
+    events: HashMap<String, EventRuntime<PluginType>>,
+    actions: HashMap<String, ActionRuntime<PluginType>>,
+    has_derived_states: bool,
+    // computed once, shared by the state listeners of all components
+    state_registers: Vec<(Arc<str>, StateRuntimeRegister<PluginType>)>,
 }
 
 impl<PluginType: MylifePlugin> PluginRuntimeAccess<PluginType> {
     pub fn new(
-        configs: HashMap<String, ConfigRuntimeSetter<PluginType>>,
+        configs: Vec<(String, ConfigRuntime<PluginType>)>,
+        extra_config: Option<ExtraConfigRuntimeSetter<PluginType>>,
         states: HashMap<String, StateRuntime<PluginType>>,
-        actions: HashMap<String, ActionRuntimeExecutor<PluginType>>,
+        events: HashMap<String, EventRuntime<PluginType>>,
+        actions: HashMap<String, ActionRuntime<PluginType>>,
     ) -> Arc<Self> {
+        let has_derived_states = states.values().any(|state| state.derived);
+        let state_registers = states
+            .iter()
+            .map(|(name, state)| (Arc::from(name.as_str()), state.register))
+            .collect();
+
         Arc::new(PluginRuntimeAccess {
             configs,
+            extra_config,
             states,
+            events,
             actions,
+            has_derived_states,
+            state_registers,
         })
     }
+
+    pub fn config_names(&self) -> impl Iterator<Item = &str> {
+        self.configs.iter().map(|(name, _)| name.as_str())
+    }
+
+    fn has_config(&self, name: &str) -> bool {
+        self.configs
+            .iter()
+            .any(|(config_name, _)| config_name == name)
+    }
+
+    fn is_secret_config(&self, name: &str) -> bool {
+        self.configs
+            .iter()
+            .any(|(config_name, config)| config_name == name && config.secret)
+    }
+
+    // copy of config which can be printed or handed out
+    fn redact_config(&self, config: &Config) -> Config {
+        config
+            .iter()
+            .map(|(name, value)| {
+                if self.is_secret_config(name) {
+                    (name.clone(), ConfigValue::String(String::from(REDACTED)))
+                } else {
+                    (name.clone(), value.clone())
+                }
+            })
+            .collect()
+    }
+
+    pub fn state_names(&self) -> impl Iterator<Item = &str> {
+        self.states.keys().map(String::as_str)
+    }
+
+    pub fn event_names(&self) -> impl Iterator<Item = &str> {
+        self.events.keys().map(String::as_str)
+    }
+
+    pub fn action_names(&self) -> impl Iterator<Item = &str> {
+        self.actions.keys().map(String::as_str)
+    }
+
+    // Ensure runtime bindings and metadata describe the same members
+    pub fn check_metadata(&self, metadata: &PluginMetadata) -> Result<(), MetadataMismatchError> {
+        for name in self.config_names() {
+            if !metadata.config().contains_key(name) {
+                return Err(MetadataMismatchError::new("config", name));
+            }
+        }
+
+        for name in metadata.config().keys() {
+            if !self.has_config(name) {
+                return Err(MetadataMismatchError::new("config", name));
+            }
+        }
+
+        if metadata.accepts_extra_config() != self.extra_config.is_some() {
+            return Err(MetadataMismatchError::new("config", "<extra>"));
+        }
+
+        for name in self.state_names() {
+            match metadata.members().get(name) {
+                Some(member) if member.member_type() == MemberType::State => {}
+                _ => return Err(MetadataMismatchError::new("state", name)),
+            }
+        }
+
+        for name in self.event_names() {
+            match metadata.members().get(name) {
+                Some(member) if member.member_type() == MemberType::Event => {}
+                _ => return Err(MetadataMismatchError::new("event", name)),
+            }
+        }
+
+        for name in self.action_names() {
+            match metadata.members().get(name) {
+                Some(member) if member.member_type() == MemberType::Action => {}
+                _ => return Err(MetadataMismatchError::new("action", name)),
+            }
+        }
+
+        for (name, member) in metadata.members() {
+            let bound = match member.member_type() {
+                MemberType::State => self.states.contains_key(name),
+                MemberType::Event => self.events.contains_key(name),
+                MemberType::Action => self.actions.contains_key(name),
+            };
+
+            if !bound {
+                return Err(MetadataMismatchError::new("member", name));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+type StateSetup<PluginType> = fn(target: &mut PluginType);
+
+// Prepared once per create_components batch, shared by its components
+struct ComponentPrototype<PluginType: MylifePlugin> {
+    access: Arc<PluginRuntimeAccess<PluginType>>,
+    plugin_name: Arc<str>,
+    state_setup: Arc<[StateSetup<PluginType>]>, // clamp, history and latch of each state
+    polled: Vec<Arc<str>>,                      // names of the polled states
+}
+
+impl<PluginType: MylifePlugin> ComponentPrototype<PluginType> {
+    fn new(access: &Arc<PluginRuntimeAccess<PluginType>>, plugin_name: &str) -> Self {
+        let mut state_setup: Vec<StateSetup<PluginType>> = Vec::new();
+
+        for state in access.states.values() {
+            state_setup.extend(state.clamp);
+            state_setup.extend(state.history.map(|(enable_history, _)| enable_history));
+            state_setup.extend(state.latch);
+        }
+
+        ComponentPrototype {
+            access: access.clone(),
+            plugin_name: Arc::from(plugin_name),
+            state_setup: Arc::from(state_setup),
+            polled: access
+                .states
+                .iter()
+                .filter(|(_, state)| state.polled.is_some())
+                .map(|(name, _)| Arc::from(name.as_str()))
+                .collect(),
+        }
+    }
 }
 
 struct ComponentImpl<PluginType: MylifePlugin> {
     access: Arc<PluginRuntimeAccess<PluginType>>,
     component: PluginType,
     id: String,
-    state_handler: Arc<RefCell<Option<Box<dyn Fn(/*name:*/ &str, /*value:*/ Value)>>>>,
+    plugin_name: Arc<str>,
+    state_setup: Arc<[StateSetup<PluginType>]>,
+    config: Config, // last applied
+    state: ComponentState,
+    state_handlers: Rc<RefCell<Vec<StateHandler>>>,
+    log_level: Rc<Cell<LevelFilter>>,
+    ready_handler: Option<Box<dyn Fn()>>,
+    fail_handler: Rc<RefCell<Option<FailHandler>>>, // shared with the plugin FailReporter
+    debounces: Rc<RefCell<HashMap<String, StateDebounce>>>,
+    cancellation: CancellationToken,
+    states_bound: bool, // listeners are bound on the first state handler
+    event_handler: Rc<RefCell<Option<StateHandler>>>,
+    events_bound: bool, // same for events
+    simulated: bool,
+    fail_reason: Option<String>,              // init error, while Failed
+    batch: Rc<RefCell<Option<StateChanges>>>, // Some while with_batch runs
+    batch_handler: Option<StatesBatchHandler>,
+    primary_state_handler: bool, // state_handlers[0] comes from set_on_state, the others from add_on_state
+    polled: Vec<(Arc<str>, Arc<AtomicBool>)>, // changed flags of the polled states
+    rate_buckets: HashMap<String, RateBucket>, // per rate limited action, on first execution
+    action_dropped_handler: Option<ActionDroppedHandler>,
+}
+
+// token bucket: starts full, refills continuously at count per period
+struct RateBucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl RateBucket {
+    fn new(rate_limit: &RateLimit) -> Self {
+        RateBucket {
+            tokens: rate_limit.count() as f64,
+            refilled: Instant::now(),
+        }
+    }
+
+    fn try_take(&mut self, rate_limit: &RateLimit) -> bool {
+        let now = Instant::now();
+        let count = rate_limit.count() as f64;
+        let refill = now.duration_since(self.refilled).as_secs_f64() * count
+            / rate_limit.period().as_secs_f64();
+
+        self.tokens = (self.tokens + refill).min(count);
+        self.refilled = now;
+
+        if self.tokens < 1.0 {
+            return false;
+        }
+
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+struct StateDebounce {
+    interval: Duration,
+    pending: Option<(Value, Instant)>, // value, deadline
+}
+
+fn log_enabled(log_level: &Cell<LevelFilter>, level: Level) -> bool {
+    level <= log_level.get()
+}
+
+// without fail handler, the failure is logged so it does not go unnoticed
+fn report_fail(
+    fail_handler: &RefCell<Option<FailHandler>>,
+    log_level: &Cell<LevelFilter>,
+    id: &str,
+    context: FailContext,
+    err: &dyn std::error::Error,
+) {
+    match fail_handler.borrow().as_ref() {
+        Some(handler) => handler(&context, err),
+        None => {
+            if log_enabled(log_level, Level::Warn) {
+                warn!(target: "mylife:home:core:plugin-runtime:macros-backend:runtime", "[{id}] failure ({context:?}), no fail handler set: {err}");
+            }
+        }
+    }
 }
 
 impl<PluginType: MylifePlugin> ComponentImpl<PluginType> {
-    pub fn new(access: &Arc<PluginRuntimeAccess<PluginType>>, id: &str) -> Box<Self> {
+    fn new(prototype: &ComponentPrototype<PluginType>, id: &str) -> Box<Self> {
         let mut component = Box::new(ComponentImpl {
-            access: access.clone(),
+            access: prototype.access.clone(),
             component: PluginType::new(id),
             id: String::from(id),
-            state_handler: Arc::new(RefCell::new(None)),
+            plugin_name: prototype.plugin_name.clone(),
+            state_setup: prototype.state_setup.clone(),
+            config: Config::new(),
+            state: ComponentState::New,
+            state_handlers: Rc::new(RefCell::new(Vec::new())),
+            log_level: Rc::new(Cell::new(LevelFilter::Trace)),
+            ready_handler: None,
+            fail_handler: Rc::new(RefCell::new(None)),
+            debounces: Rc::new(RefCell::new(HashMap::new())),
+            cancellation: CancellationToken::new(),
+            states_bound: false,
+            event_handler: Rc::new(RefCell::new(None)),
+            events_bound: false,
+            simulated: false,
+            fail_reason: None,
+            batch: Rc::new(RefCell::new(None)),
+            batch_handler: None,
+            primary_state_handler: false,
+            polled: prototype
+                .polled
+                .iter()
+                .map(|name| (name.clone(), Arc::new(AtomicBool::new(false))))
+                .collect(),
+            rate_buckets: HashMap::new(),
+            action_dropped_handler: None,
         });
 
-        component.register_state_handlers();
+        component.setup_states();
 
         component
     }
 
-    fn register_state_handlers(&mut self) {
-        for (name, state) in self.access.states.iter() {
+    // also needed on State fields replaced by the plugin
+    fn setup_states(&mut self) {
+        for setup in self.state_setup.iter() {
+            setup(&mut self.component);
+        }
+
+        for (name, changed) in self.polled.iter() {
+            if let Some(enable_polled) = self.access.states[name.as_ref()].polled {
+                enable_polled(&mut self.component, changed.clone());
+            }
+        }
+    }
+
+    fn bind_events(&mut self) {
+        self.events_bound = true;
+
+        for (name, event) in self.access.events.iter() {
             let id = self.id.clone();
             let name = name.clone();
-            let state_handler = self.state_handler.clone();
-            (state.register)(
+            let event_handler = self.event_handler.clone();
+            let log_level = self.log_level.clone();
+            (event.register)(
                 &mut self.component,
                 Box::new(move |value: Value| {
-                    trace!(target: "mylife:home:core:plugin-runtime:macros-backend:runtime", "[{id}] state '{name}' changed to {value:?}");
+                    if log_enabled(&log_level, Level::Trace) {
+                        trace!(target: "mylife:home:core:plugin-runtime:macros-backend:runtime", "[{id}] event '{name}' emitted with {value}");
+                    }
 
-                    if let Some(handler) = state_handler.borrow().as_ref() {
+                    if let Some(handler) = event_handler.borrow().as_ref() {
                         handler(&name, value);
                     }
                 }),
             );
         }
     }
+
+    fn bind_states(&mut self) {
+        self.states_bound = true;
+
+        let id: Arc<str> = Arc::from(self.id.as_str());
+
+        for (name, register) in self.access.state_registers.iter() {
+            let id = id.clone();
+            let name = name.clone();
+            let state_handlers = self.state_handlers.clone();
+            let log_level = self.log_level.clone();
+            let debounces = self.debounces.clone();
+            let batch = self.batch.clone();
+            register(
+                &mut self.component,
+                Box::new(move |value: Value| {
+                    if log_enabled(&log_level, Level::Trace) {
+                        trace!(target: "mylife:home:core:plugin-runtime:macros-backend:runtime", "[{id}] state '{name}' changed to {value}");
+                    }
+
+                    // held back until process_debounced_states is called after the interval
+                    if let Some(debounce) = debounces.borrow_mut().get_mut(&*name) {
+                        debounce.pending = Some((value, Instant::now() + debounce.interval));
+                        return;
+                    }
+
+                    if let Some(changes) = batch.borrow_mut().as_mut() {
+                        match changes.iter_mut().find(|(changed, _)| **changed == *name) {
+                            Some((_, pending)) => *pending = value,
+                            None => changes.push((String::from(&*name), value)),
+                        }
+
+                        return;
+                    }
+
+                    Self::emit_state(&state_handlers, &log_level, &id, &name, value);
+                }),
+            );
+        }
+    }
+
+    fn emit_state(
+        state_handlers: &RefCell<Vec<StateHandler>>,
+        log_level: &Cell<LevelFilter>,
+        id: &str,
+        name: &str,
+        value: Value,
+    ) {
+        let state_handlers = state_handlers.borrow();
+
+        if state_handlers.is_empty() {
+            if log_enabled(log_level, Level::Debug) {
+                debug!(target: "mylife:home:core:plugin-runtime:macros-backend:runtime", "[{id}] state '{name}' changed while no state handler is set, change not reported");
+            }
+
+            return;
+        }
+
+        for handler in state_handlers.iter() {
+            handler(name, value.clone());
+        }
+    }
+
+    fn emit_debounced_states(&mut self, now: Option<Instant>) {
+        let mut ready = Vec::new();
+
+        for (name, debounce) in self.debounces.borrow_mut().iter_mut() {
+            let due = match (&debounce.pending, now) {
+                (Some(_), None) => true,
+                (Some((_, deadline)), Some(now)) => *deadline <= now,
+                (None, _) => false,
+            };
+
+            if due {
+                let (value, _) = debounce.pending.take().unwrap();
+                ready.push((name.clone(), value));
+            }
+        }
+
+        // emit outside of the borrow so the handler may interact with the component
+        for (name, value) in ready {
+            Self::emit_state(
+                &self.state_handlers,
+                &self.log_level,
+                &self.id,
+                &name,
+                value,
+            );
+        }
+    }
+
+    fn configure_item(
+        component: &mut PluginType,
+        name: &str,
+        config_runtime: &ConfigRuntime<PluginType>,
+        config: &Config,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut value = config
+            .get(name)
+            .ok_or_else(|| {
+                Box::new(ConfigNotSetError {
+                    name: String::from(name),
+                })
+            })?
+            .clone();
+
+        // a present null satisfies the key, the setter receives it as None
+        if value == ConfigValue::Null && !config_runtime.nullable {
+            return Err(Box::new(ConfigNullError {
+                name: String::from(name),
+            }));
+        }
+
+        if let (Some(pattern), ConfigValue::String(value)) = (&config_runtime.pattern, &value) {
+            if !pattern.is_match(value) {
+                return Err(Box::new(ConfigPatternMismatchError {
+                    name: String::from(name),
+                    value: redact(config_runtime, value),
+                    pattern: String::from(pattern.as_str()),
+                }));
+            }
+        }
+
+        if let (Some((min, max, clamp)), ConfigValue::Integer(int_value)) =
+            (config_runtime.range, &value)
+        {
+            if *int_value < min || *int_value > max {
+                if !clamp {
+                    return Err(Box::new(ConfigOutOfRangeError {
+                        name: String::from(name),
+                        value: redact(config_runtime, int_value),
+                        min,
+                        max,
+                    }));
+                }
+
+                let clamped = (*int_value).clamp(min, max);
+                warn!(target: "mylife:home:core:plugin-runtime:macros-backend:runtime", "config '{name}' value {} out of range [{min};{max}], clamped to {}", redact(config_runtime, int_value), redact(config_runtime, &clamped));
+                value = ConfigValue::Integer(clamped);
+            }
+        }
+
+        // setter errors (conversion, range) print the value: keep secrets out of them
+        (config_runtime.setter)(component, value).map_err(|err| {
+            if config_runtime.secret {
+                Box::new(ConfigSecretSetterError {
+                    name: String::from(name),
+                }) as Box<dyn std::error::Error>
+            } else {
+                err
+            }
+        })
+    }
+
+    fn report_fail(&self, context: FailContext, err: &dyn std::error::Error) {
+        report_fail(&self.fail_handler, &self.log_level, &self.id, context, err);
+    }
+
+    fn check_state(
+        &self,
+        operation: &'static str,
+        allowed: &[ComponentState],
+    ) -> Result<(), InvalidComponentStateError> {
+        if allowed.contains(&self.state) {
+            Ok(())
+        } else {
+            Err(InvalidComponentStateError {
+                operation,
+                state: self.state,
+            })
+        }
+    }
+
+    fn recompute(&mut self) {
+        if self.access.has_derived_states {
+            self.component.recompute();
+        }
+    }
 }
 
-impl<PluginType: MylifePlugin> MylifeComponent for ComponentImpl<PluginType> {
+impl<PluginType: MylifePlugin> Drop for ComponentImpl<PluginType> {
+    // not terminated: still signal background tasks, they are not waited for
+    fn drop(&mut self) {
+        self.cancellation.cancel();
+    }
+}
+
+impl<PluginType: MylifePlugin + 'static> MylifeComponent for ComponentImpl<PluginType> {
     fn id(&self) -> &str {
         &self.id
     }
 
-    fn set_on_state(&mut self, handler: Box<dyn Fn(/*name:*/ &str, /*value:*/ Value)>) {
-        *self.state_handler.borrow_mut() = Some(handler);
+    fn plugin_any(&self) -> &dyn Any {
+        &self.component
+    }
+
+    fn plugin_any_mut(&mut self) -> &mut dyn Any {
+        &mut self.component
+    }
+
+    fn set_on_state(&mut self, handler: StateHandler) {
+        *self.state_handlers.borrow_mut() = vec![handler];
+        self.primary_state_handler = true;
+
+        if !self.states_bound {
+            self.bind_states();
+        }
+    }
+
+    fn add_on_state(&mut self, handler: StateHandler) {
+        self.state_handlers.borrow_mut().push(handler);
+
+        if !self.states_bound {
+            self.bind_states();
+        }
+    }
+
+    fn set_on_states_batch(&mut self, handler: StatesBatchHandler) {
+        self.batch_handler = Some(handler);
+
+        if !self.states_bound {
+            self.bind_states();
+        }
+    }
+
+    fn with_batch(&mut self, batch: &mut dyn FnMut(&mut dyn MylifeComponent)) {
+        if self.batch.borrow().is_some() {
+            batch(self);
+            return;
+        }
+
+        *self.batch.borrow_mut() = Some(Vec::new());
+        batch(self);
+        let changes = self.batch.borrow_mut().take().unwrap_or_default();
+
+        if changes.is_empty() {
+            return;
+        }
+
+        let handler = match self.batch_handler.as_ref() {
+            Some(handler) => handler,
+            None => {
+                for (name, value) in changes {
+                    Self::emit_state(
+                        &self.state_handlers,
+                        &self.log_level,
+                        &self.id,
+                        &name,
+                        value,
+                    );
+                }
+
+                return;
+            }
+        };
+
+        handler(changes.clone());
+
+        // the batch handler replaces the set_on_state one only:
+        // observers added with add_on_state (subscriptions, state streams) still get each change
+        let skipped = usize::from(self.primary_state_handler);
+        let state_handlers = self.state_handlers.borrow();
+
+        for (name, value) in changes {
+            for handler in state_handlers.iter().skip(skipped) {
+                handler(&name, value.clone());
+            }
+        }
+    }
+
+    fn set_on_event(&mut self, handler: StateHandler) {
+        *self.event_handler.borrow_mut() = Some(handler);
+
+        if !self.events_bound {
+            self.bind_events();
+        }
+    }
+
+    fn rebind_states(&mut self) {
+        self.setup_states();
+        self.bind_states();
+    }
+
+    fn set_on_ready(&mut self, handler: Box<dyn Fn()>) {
+        self.ready_handler = Some(handler);
+    }
+
+    fn set_on_fail(&mut self, handler: FailHandler) {
+        *self.fail_handler.borrow_mut() = Some(handler);
+    }
+
+    fn set_on_action_dropped(&mut self, handler: ActionDroppedHandler) {
+        self.action_dropped_handler = Some(handler);
+    }
+
+    fn set_state_debounce(
+        &mut self,
+        name: &str,
+        interval: Duration,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.access.states.contains_key(name) {
+            return Err(Box::new(NoSuchStateError {
+                name: String::from(name),
+            }));
+        }
+
+        // flush what is pending with the previous setting so the last value is never dropped
+        self.emit_debounced_states(None);
+
+        let mut debounces = self.debounces.borrow_mut();
+        if interval.is_zero() {
+            debounces.remove(name);
+        } else {
+            debounces.insert(
+                String::from(name),
+                StateDebounce {
+                    interval,
+                    pending: None,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    fn process_debounced_states_at(&mut self, now: Instant) {
+        self.emit_debounced_states(Some(now));
+    }
+
+    fn poll_states(&mut self) {
+        for (name, changed) in self.polled.iter() {
+            if !changed.swap(false, Ordering::Acquire) {
+                continue;
+            }
+
+            let value = (self.access.states[name.as_ref()].getter)(&self.component);
+
+            if log_enabled(&self.log_level, Level::Trace) {
+                trace!(target: "mylife:home:core:plugin-runtime:macros-backend:runtime", "[{}] polled state '{name}' changed to {value}", self.id);
+            }
+
+            Self::emit_state(&self.state_handlers, &self.log_level, &self.id, name, value);
+        }
+    }
+
+    fn debug_info(&self) -> ComponentDebug {
+        ComponentDebug::new(&self.id, &self.plugin_name, self.state)
+    }
+
+    fn list_actions(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.access.action_names().collect();
+        names.sort_unstable();
+        names
+    }
+
+    fn list_states(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.access.state_names().collect();
+        names.sort_unstable();
+        names
+    }
+
+    fn describe(&self) -> ComponentSnapshot {
+        let states = self
+            .access
+            .states
+            .iter()
+            .map(|(name, state)| (name.clone(), (state.getter)(&self.component)))
+            .collect();
+
+        ComponentSnapshot::new(
+            &self.id,
+            &self.plugin_name,
+            self.access.redact_config(&self.config),
+            states,
+            self.state,
+        )
+    }
+
+    fn set_log_level(&mut self, level: LevelFilter) {
+        self.log_level.set(level);
+        self.component.set_log_level(level);
+    }
+
+    fn log_level(&self) -> LevelFilter {
+        self.log_level.get()
+    }
+
+    fn set_simulated(&mut self, simulated: bool) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_state(
+            "set_simulated",
+            &[ComponentState::New, ComponentState::Configured],
+        )?;
+
+        self.simulated = simulated;
+        Ok(())
+    }
+
+    fn is_simulated(&self) -> bool {
+        self.simulated
+    }
+
+    fn state(&self) -> ComponentState {
+        self.state
+    }
+
+    fn health(&self) -> Health {
+        match self.state {
+            ComponentState::New | ComponentState::Configured => {
+                Health::Degraded(String::from("not initialized"))
+            }
+            ComponentState::Ready => self.component.health(),
+            ComponentState::Failed => Health::Failed(
+                self.fail_reason
+                    .clone()
+                    .unwrap_or_else(|| String::from("init failed")),
+            ),
+            ComponentState::Terminated => Health::Failed(String::from("terminated")),
+        }
     }
 
     fn get_state(&self, name: &str) -> Result<Value, Box<dyn std::error::Error>> {
@@ -123,44 +887,417 @@ impl<PluginType: MylifePlugin> MylifeComponent for ComponentImpl<PluginType> {
         Ok((state.getter)(&self.component))
     }
 
+    fn state_history(
+        &self,
+        name: &str,
+    ) -> Result<Vec<(SystemTime, Value)>, Box<dyn std::error::Error>> {
+        let state = self.access.states.get(name).ok_or_else(|| {
+            Box::new(NoSuchStateError {
+                name: String::from(name),
+            })
+        })?;
+
+        Ok(match state.history {
+            Some((_, getter)) => getter(&self.component),
+            None => Vec::new(),
+        })
+    }
+
     // TODO: better error type
     fn configure(&mut self, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
-        trace!(target: "mylife:home:core:plugin-runtime:macros-backend:runtime", "[{}] configure with {config:?}", self.id);
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::info_span!("configure", component = %self.id, plugin = %self.plugin_name)
+                .entered();
 
-        for (name, setter) in self.access.configs.iter() {
-            let value = config
-                .get(name)
-                .ok_or_else(|| {
-                    Box::new(ConfigNotSetError {
-                        name: String::from(name),
-                    })
-                })?
-                .clone();
+        // a failed component can be configured again and retry init
+        self.check_state(
+            "configure",
+            &[
+                ComponentState::New,
+                ComponentState::Configured,
+                ComponentState::Failed,
+            ],
+        )?;
+
+        if log_enabled(&self.log_level, Level::Trace) {
+            let config = self.access.redact_config(config);
+            trace!(target: "mylife:home:core:plugin-runtime:macros-backend:runtime", "[{}] configure with {config:?}", self.id);
+        }
+
+        for (name, config_runtime) in self.access.configs.iter() {
+            if let Err(err) =
+                Self::configure_item(&mut self.component, name, config_runtime, config)
+            {
+                self.report_fail(FailContext::Config(name.clone()), err.as_ref());
+                return Err(err);
+            }
+        }
+
+        if let Some(setter) = self.access.extra_config {
+            let extra = config
+                .iter()
+                .filter(|(name, _)| !self.access.has_config(name))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+            setter(&mut self.component, extra);
+        }
+
+        self.config = config
+            .iter()
+            .filter(|(name, _)| self.access.extra_config.is_some() || self.access.has_config(name))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        self.state = ComponentState::Configured;
 
-            setter(&mut self.component, value)?;
+        for (name, state) in self.access.states.iter() {
+            if state.from_config {
+                let value = (state.getter)(&self.component);
+                Self::emit_state(&self.state_handlers, &self.log_level, &self.id, name, value);
+            }
         }
 
         Ok(())
     }
 
+    fn validate_config(&self, config: &Config) -> Result<(), Vec<Box<dyn std::error::Error>>> {
+        // setters are run against a throwaway instance so the live component is untouched
+        let mut component = PluginType::new(&self.id);
+        let errors: Vec<Box<dyn std::error::Error>> = self
+            .access
+            .configs
+            .iter()
+            .filter_map(|(name, config_runtime)| {
+                Self::configure_item(&mut component, name, config_runtime, config).err()
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn configure_strict(&mut self, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+        // every key is known to a plugin accepting extra config
+        if self.access.extra_config.is_some() {
+            return self.configure(config);
+        }
+
+        let mut unknown_names: Vec<String> = config
+            .keys()
+            .filter(|name| !self.access.has_config(name))
+            .cloned()
+            .collect();
+
+        if !unknown_names.is_empty() {
+            unknown_names.sort();
+            return Err(Box::new(ConfigUnknownKeysError {
+                names: unknown_names,
+            }));
+        }
+
+        self.configure(config)
+    }
+
+    fn apply_config_diff(
+        &mut self,
+        changes: &Config,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        self.check_state(
+            "apply_config_diff",
+            &[
+                ComponentState::Configured,
+                ComponentState::Ready,
+                ComponentState::Failed,
+            ],
+        )?;
+
+        if self.access.extra_config.is_none() {
+            let mut unknown_names: Vec<String> = changes
+                .keys()
+                .filter(|name| !self.access.has_config(name))
+                .cloned()
+                .collect();
+
+            if !unknown_names.is_empty() {
+                unknown_names.sort();
+                return Err(Box::new(ConfigUnknownKeysError {
+                    names: unknown_names,
+                }));
+            }
+        }
+
+        let mut changed: Vec<String> = changes
+            .iter()
+            .filter(|(name, value)| self.config.get(*name) != Some(value))
+            .map(|(name, _)| name.clone())
+            .collect();
+        changed.sort();
+
+        if changed.is_empty() {
+            return Ok(changed);
+        }
+
+        let mut config = self.config.clone();
+        config.extend(
+            changes
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone())),
+        );
+
+        // checked against a throwaway instance first, so an invalid key leaves the component untouched
+        let mut component = PluginType::new(&self.id);
+        for (name, config_runtime) in self.access.configs.iter() {
+            if !changed.contains(name) {
+                continue;
+            }
+
+            if let Err(err) = Self::configure_item(&mut component, name, config_runtime, &config) {
+                self.report_fail(FailContext::Config(name.clone()), err.as_ref());
+                return Err(err);
+            }
+        }
+
+        if log_enabled(&self.log_level, Level::Trace) {
+            trace!(target: "mylife:home:core:plugin-runtime:macros-backend:runtime", "[{}] apply config diff on {changed:?}", self.id);
+        }
+
+        for (name, config_runtime) in self.access.configs.iter() {
+            if changed.contains(name) {
+                Self::configure_item(&mut self.component, name, config_runtime, &config)?;
+            }
+        }
+
+        if let Some(setter) = self.access.extra_config {
+            if changed.iter().any(|name| !self.access.has_config(name)) {
+                let extra = config
+                    .iter()
+                    .filter(|(name, _)| !self.access.has_config(name))
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect();
+                setter(&mut self.component, extra);
+            }
+        }
+
+        self.config = config;
+
+        // config exposed as state: the state has the config name
+        for (name, state) in self.access.states.iter() {
+            if state.from_config && changed.contains(name) {
+                let value = (state.getter)(&self.component);
+                Self::emit_state(&self.state_handlers, &self.log_level, &self.id, name, value);
+            }
+        }
+
+        if self.state == ComponentState::Ready {
+            self.recompute();
+        }
+
+        Ok(changed)
+    }
+
     fn init(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        self.component.init()
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::info_span!("init", component = %self.id, plugin = %self.plugin_name).entered();
+
+        self.check_state("init", &[ComponentState::Configured])?;
+
+        self.component.set_cancellation(self.cancellation.clone());
+        self.component.set_simulated(self.simulated);
+
+        let fail_handler = self.fail_handler.clone();
+        let log_level = self.log_level.clone();
+        let id = self.id.clone();
+        self.component
+            .set_fail_reporter(FailReporter::new(move |context, err| {
+                report_fail(&fail_handler, &log_level, &id, context.clone(), err)
+            }));
+
+        if let Err(err) = self.component.init() {
+            self.state = ComponentState::Failed;
+            self.fail_reason = Some(err.to_string());
+            self.report_fail(FailContext::Init, err.as_ref());
+            return Err(err);
+        }
+
+        self.recompute();
+        self.state = ComponentState::Ready;
+        self.fail_reason = None;
+
+        if let Some(handler) = self.ready_handler.as_ref() {
+            handler();
+        }
+
+        Ok(())
+    }
+
+    fn reinit(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_state("reinit", &[ComponentState::Failed])?;
+        self.state = ComponentState::Configured;
+
+        self.init()
+    }
+
+    fn terminate(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_state(
+            "terminate",
+            &[
+                ComponentState::New,
+                ComponentState::Configured,
+                ComponentState::Ready,
+                ComponentState::Failed,
+            ],
+        )?;
+
+        // the last value of a debounced state is never dropped
+        self.emit_debounced_states(None);
+
+        self.cancellation.cancel();
+        self.component.terminate();
+        self.state = ComponentState::Terminated;
+
+        Ok(())
     }
 
-    // TODO: better error type
     fn execute_action(
         &mut self,
         name: &str,
         action: Value,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let handler = self.access.actions.get(name).ok_or_else(|| {
+        self.execute_action_with_result(name, action)?;
+
+        Ok(())
+    }
+
+    // TODO: better error type
+    fn execute_action_with_result(
+        &mut self,
+        name: &str,
+        action: Value,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error>> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("execute_action", component = %self.id, plugin = %self.plugin_name, action = name).entered();
+
+        self.check_state("execute action", &[ComponentState::Ready])?;
+
+        let action_runtime = self.access.actions.get(name).ok_or_else(|| {
             Box::new(NoSuchActionError {
                 name: String::from(name),
             })
         })?;
 
-        trace!(target: "mylife:home:core:plugin-runtime:macros-backend:runtime", "[{}] execute action '{name}' with {action:?}", self.id);
-        handler(&mut self.component, action)
+        if let Some(guard) = action_runtime.guard {
+            if !guard(&self.component) {
+                return Err(Box::new(ActionGuardError {
+                    name: String::from(name),
+                }));
+            }
+        }
+
+        if let Some(rate_limit) = &action_runtime.rate_limit {
+            let bucket = self
+                .rate_buckets
+                .entry(String::from(name))
+                .or_insert_with(|| RateBucket::new(rate_limit));
+
+            if !bucket.try_take(rate_limit) {
+                debug!(target: "mylife:home:core:plugin-runtime:macros-backend:runtime", "[{}] action '{name}' dropped, above its rate limit ({rate_limit})", self.id);
+
+                if let Some(handler) = self.action_dropped_handler.as_ref() {
+                    handler(name);
+                }
+
+                return Err(Box::new(ActionRateLimitedError {
+                    name: String::from(name),
+                    rate_limit: *rate_limit,
+                }));
+            }
+        }
+
+        if let Some(message) = &action_runtime.deprecated {
+            if !action_runtime
+                .deprecation_logged
+                .swap(true, Ordering::Relaxed)
+            {
+                warn!(target: "mylife:home:core:plugin-runtime:macros-backend:runtime", "[{}] action '{name}' of plugin '{}' is deprecated: {message}", self.id, self.plugin_name);
+            }
+        }
+
+        let action = match (action, &action_runtime.default_arg) {
+            (Value::Null, Some(default_arg)) => default_arg.clone(),
+            (action, _) => action,
+        };
+
+        if log_enabled(&self.log_level, Level::Trace) {
+            trace!(target: "mylife:home:core:plugin-runtime:macros-backend:runtime", "[{}] execute action '{name}' with {action:?}", self.id);
+        }
+
+        let mut attempt = 0;
+        let result = loop {
+            match (action_runtime.executor)(&mut self.component, name, action.clone()) {
+                Err(err) if err.is::<DeviceUnavailableError>() => match action_runtime.retry {
+                    Some(policy) if attempt < policy.retries => {
+                        attempt += 1;
+                        debug!(target: "mylife:home:core:plugin-runtime:macros-backend:runtime", "[{}] action '{name}' failed ({err}), retry {attempt}/{}", self.id, policy.retries);
+                        thread::sleep(policy.backoff);
+                    }
+                    _ => break Err(err),
+                },
+                result => break result,
+            }
+        };
+
+        if let Err(err) = &result {
+            self.report_fail(FailContext::Action(String::from(name)), err.as_ref());
+        }
+
+        let result = result?;
+        self.recompute();
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MetadataMismatchError {
+    kind: &'static str,
+    name: String,
+}
+
+impl MetadataMismatchError {
+    fn new(kind: &'static str, name: &str) -> Self {
+        MetadataMismatchError {
+            kind,
+            name: String::from(name),
+        }
+    }
+}
+
+impl std::error::Error for MetadataMismatchError {}
+
+impl fmt::Display for MetadataMismatchError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Runtime binding and metadata mismatch on {} '{}'",
+            self.kind, self.name
+        )
+    }
+}
+
+const REDACTED: &str = "***";
+
+fn redact<PluginType>(
+    config_runtime: &ConfigRuntime<PluginType>,
+    value: &impl fmt::Display,
+) -> String {
+    if config_runtime.secret {
+        String::from(REDACTED)
+    } else {
+        value.to_string()
     }
 }
 
@@ -177,6 +1314,126 @@ impl fmt::Display for ConfigNotSetError {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct ConfigNullError {
+    name: String,
+}
+
+impl std::error::Error for ConfigNullError {}
+
+impl fmt::Display for ConfigNullError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Config key '{}' is not nullable", self.name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigUnknownKeysError {
+    names: Vec<String>,
+}
+
+impl std::error::Error for ConfigUnknownKeysError {}
+
+impl fmt::Display for ConfigUnknownKeysError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Unknown config keys: '{}'", self.names.join("', '"))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigPatternMismatchError {
+    name: String,
+    value: String,
+    pattern: String,
+}
+
+impl std::error::Error for ConfigPatternMismatchError {}
+
+impl fmt::Display for ConfigPatternMismatchError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Config value '{}' for key '{}' does not match pattern '{}'",
+            self.value, self.name, self.pattern
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigOutOfRangeError {
+    name: String,
+    value: String,
+    min: i64,
+    max: i64,
+}
+
+impl std::error::Error for ConfigOutOfRangeError {}
+
+impl fmt::Display for ConfigOutOfRangeError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Config value {} for key '{}' out of range [{};{}]",
+            self.value, self.name, self.min, self.max
+        )
+    }
+}
+
+// setter failure on a secret config, the original error is dropped since it contains the value
+#[derive(Debug, Clone)]
+pub struct ConfigSecretSetterError {
+    name: String,
+}
+
+impl std::error::Error for ConfigSecretSetterError {}
+
+impl fmt::Display for ConfigSecretSetterError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Config value {} for key '{}' could not be applied",
+            REDACTED, self.name
+        )
+    }
+}
+
+// caller error: the action is executed more often than its declared rate limit
+#[derive(Debug, Clone)]
+pub struct ActionRateLimitedError {
+    name: String,
+    rate_limit: RateLimit,
+}
+
+impl std::error::Error for ActionRateLimitedError {}
+
+impl fmt::Display for ActionRateLimitedError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Action '{}' dropped: rate limit of {} exceeded",
+            self.name, self.rate_limit
+        )
+    }
+}
+
+// caller error: the plugin is not in a state where the action is valid
+#[derive(Debug, Clone)]
+pub struct ActionGuardError {
+    name: String,
+}
+
+impl std::error::Error for ActionGuardError {}
+
+impl fmt::Display for ActionGuardError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Action '{}' rejected by its guard in the current plugin state",
+            self.name
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct NoSuchActionError {
     name: String,
@@ -190,6 +1447,24 @@ impl fmt::Display for NoSuchActionError {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct InvalidComponentStateError {
+    operation: &'static str,
+    state: ComponentState,
+}
+
+impl std::error::Error for InvalidComponentStateError {}
+
+impl fmt::Display for InvalidComponentStateError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Cannot {} while component is {:?}",
+            self.operation, self.state
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct NoSuchStateError {
     name: String,
@@ -202,3 +1477,76 @@ impl fmt::Display for NoSuchStateError {
         write!(fmt, "No such state: '{}'", self.name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        metadata::{PluginMetadataBuilder, PluginUsage, Type},
+        MylifePluginHooks,
+    };
+
+    use super::*;
+
+    struct TestPlugin {}
+
+    impl MylifePluginHooks for TestPlugin {
+        fn new(_id: &str) -> Self {
+            TestPlugin {}
+        }
+    }
+
+    impl MylifePlugin for TestPlugin {
+        fn runtime() -> Box<dyn MylifePluginRuntime> {
+            panic!("not used by this test")
+        }
+    }
+
+    fn make_access() -> Arc<PluginRuntimeAccess<TestPlugin>> {
+        let mut actions: HashMap<String, ActionRuntime<TestPlugin>> = HashMap::new();
+        actions.insert(
+            String::from("action"),
+            ActionRuntime {
+                executor: |_, _, _| Ok(None),
+                retry: None,
+                default_arg: None,
+                guard: None,
+                rate_limit: None,
+                deprecated: None,
+                deprecation_logged: AtomicBool::new(false),
+            },
+        );
+
+        PluginRuntimeAccess::new(Vec::new(), None, HashMap::new(), HashMap::new(), actions)
+    }
+
+    #[test]
+    fn test_names() {
+        let access = make_access();
+
+        assert_eq!(access.config_names().count(), 0);
+        assert_eq!(access.state_names().count(), 0);
+        assert_eq!(access.action_names().collect::<Vec<_>>(), vec!["action"]);
+    }
+
+    #[test]
+    fn test_check_metadata() {
+        let access = make_access();
+
+        let metadata = PluginMetadataBuilder::new()
+            .name("test")
+            .usage(PluginUsage::Logic)
+            .add_action("action", None, Type::Bool)
+            .build()
+            .unwrap();
+        assert!(access.check_metadata(&metadata).is_ok());
+
+        let metadata = PluginMetadataBuilder::new()
+            .name("test")
+            .usage(PluginUsage::Logic)
+            .add_action("action", None, Type::Bool)
+            .add_state("state", None, Type::Bool)
+            .build()
+            .unwrap();
+        assert!(access.check_metadata(&metadata).is_err());
+    }
+}
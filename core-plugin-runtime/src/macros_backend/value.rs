@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    metadata,
+    runtime::{TypeMismatchData, Value, ValueConversionError, ValueMismatchData},
+};
+
+// Helpers used by #[derive(MylifeValue)] generated code
+
+// Complex types are objects described by the schema of the native type
+pub fn object_type(ty: &metadata::Type, value_type: fn() -> metadata::Type) -> metadata::Type {
+    if let metadata::Type::Complex(_) = ty {
+        return value_type();
+    }
+
+    ty.clone()
+}
+
+// fields of the schema of a Complex type
+pub fn complex_type_fields(
+    name: &str,
+    value_type: metadata::Type,
+) -> Vec<(String, metadata::Type)> {
+    if let metadata::Type::Object(field_types) = value_type {
+        return field_types;
+    }
+
+    panic!("Complex type '{}' is not an object: {:?}", name, value_type);
+}
+
+pub fn object_field_types<'a>(
+    native_type: &'static str,
+    ty: &'a metadata::Type,
+) -> &'a [(String, metadata::Type)] {
+    if let metadata::Type::Object(field_types) = ty {
+        return field_types;
+    }
+
+    panic!(
+        "Cannot convert from {} to Value of type {:?}",
+        native_type, ty
+    );
+}
+
+pub fn object_field_type<'a>(
+    field_types: &'a [(String, metadata::Type)],
+    name: &str,
+) -> &'a metadata::Type {
+    field_types
+        .iter()
+        .find(|(field_name, _)| field_name == name)
+        .map(|(_, field_type)| field_type)
+        .unwrap_or_else(|| panic!("Unknown object field '{}'", name))
+}
+
+// multiple arguments action: a list provides the arguments by position
+pub fn action_args(value: Value, ty: &metadata::Type) -> Value {
+    match (value, ty) {
+        (Value::List(items), metadata::Type::Object(field_types))
+            if items.len() == field_types.len() =>
+        {
+            Value::Object(
+                field_types
+                    .iter()
+                    .map(|(name, _)| name.clone())
+                    .zip(items)
+                    .collect(),
+            )
+        }
+        (value, _) => value,
+    }
+}
+
+pub fn object_fields(
+    native_type: &'static str,
+    value: Value,
+    ty: &metadata::Type,
+) -> Result<BTreeMap<String, Value>, ValueConversionError> {
+    if !matches!(ty, metadata::Type::Object(_)) {
+        return Err(ValueConversionError::TypeMismatch(TypeMismatchData {
+            native_type,
+            ty: ty.clone(),
+        }));
+    }
+
+    if !value.is_of_type(ty) {
+        return Err(ValueConversionError::ValueMismatch(ValueMismatchData {
+            native_type,
+            ty: ty.clone(),
+            value,
+        }));
+    }
+
+    if let Value::Object(fields) = value {
+        Ok(fields)
+    } else {
+        unreachable!()
+    }
+}
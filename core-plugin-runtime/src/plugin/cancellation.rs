@@ -0,0 +1,66 @@
+use std::{
+    sync::{Arc, Condvar, Mutex},
+    time::Duration,
+};
+
+// Cancelled when the component is terminated (or dropped).
+// Background threads spawned by a plugin must exit once cancelled:
+// the plugin library may be unloaded right after.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        let (cancelled, condvar) = &*self.inner;
+        *cancelled.lock().unwrap() = true;
+        condvar.notify_all();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.inner.0.lock().unwrap()
+    }
+
+    // sleep replacement for polling loops, returns early (true) once cancelled
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let (cancelled, condvar) = &*self.inner;
+        let guard = cancelled.lock().unwrap();
+        let (guard, _) = condvar
+            .wait_timeout_while(guard, timeout, |cancelled| !*cancelled)
+            .unwrap();
+        *guard
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Instant};
+
+    use super::*;
+
+    #[test]
+    fn test_wait_timeout() {
+        let token = CancellationToken::new();
+        assert!(!token.is_cancelled());
+        assert!(!token.wait_timeout(Duration::from_millis(10)));
+
+        let start = Instant::now();
+        let thread = {
+            let token = token.clone();
+            thread::spawn(move || token.wait_timeout(Duration::from_secs(10)))
+        };
+
+        thread::sleep(Duration::from_millis(20));
+        token.cancel();
+
+        assert!(thread.join().unwrap());
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(token.is_cancelled());
+        assert!(token.wait_timeout(Duration::from_secs(10)));
+    }
+}
@@ -1,15 +1,54 @@
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::SystemTime,
+};
+
 use crate::{
     metadata,
     runtime::{self, TypedInto, Value},
+    CancellationToken,
 };
 
 pub trait MylifePluginHooks: Sized {
     fn new(id: &str) -> Self;
 
+    // host resources used by the plugin, reported in metadata (see metadata::Capability)
+    fn required_capabilities() -> &'static [metadata::Capability] {
+        &[]
+    }
+
+    // called before init, the token is cancelled on terminate: keep it in background threads
+    fn set_cancellation(&mut self, _token: CancellationToken) {}
+
+    // called before init, a simulated plugin must not access its device and may emit synthetic states instead
+    fn set_simulated(&mut self, _simulated: bool) {}
+
+    // called before init, keep it to report failures outside of actions, config and init
+    fn set_fail_reporter(&mut self, _reporter: runtime::FailReporter) {}
+
     // called after config
     fn init(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         Ok(())
     }
+
+    // called on terminate, after the token is cancelled: join background threads here
+    fn terminate(&mut self) {}
+
+    // polled by the host once the component is Ready, eg: report an unreachable device as Degraded
+    fn health(&self) -> runtime::Health {
+        runtime::Health::Ok
+    }
+
+    // called after init and after each action if the plugin has derived states
+    fn recompute(&mut self) {}
+
+    // called when the host changes the component log level, plugins should not log above it
+    fn set_log_level(&mut self, _level: log::LevelFilter) {}
 }
 
 // Trait implemented by the plugin itself
@@ -23,9 +62,18 @@ struct StateRuntimeData {
     r#type: metadata::Type,
 }
 
+struct StateHistory<T> {
+    capacity: usize,
+    entries: VecDeque<(SystemTime, T)>, // oldest first
+}
+
 pub struct State<T: Default> {
     value: T,
     runtime: Option<StateRuntimeData>,
+    clamp: Option<fn(T) -> T>,        // range states declared with `clamp`
+    history: Option<StateHistory<T>>, // states declared with `history = N`
+    polled: Option<Arc<AtomicBool>>,  // states declared with `mode = "polled"`: changed flag
+    latch: Option<T>, // states declared with `latched`: last value set by the plugin (the underlying condition)
 }
 
 impl<T: Default> Default for State<T> {
@@ -33,18 +81,51 @@ impl<T: Default> Default for State<T> {
         State {
             value: T::default(),
             runtime: None,
+            clamp: None,
+            history: None,
+            polled: None,
+            latch: None,
         }
     }
 }
 
-impl<T: Default + Clone + TypedInto<Value>> State<T> {
+impl<T: Default + Clone + PartialEq + TypedInto<Value>> State<T> {
+    // Setting the current value again is not reported, use set_force to emit anyway
     pub fn set(&mut self, value: T) {
-        let StateRuntimeData { listener, r#type } =
-            self.runtime.as_ref().expect("Unbound state changed!");
+        let value = self.latched(self.clamped(value));
 
-        self.value = value;
-        let value = self.value.clone().typed_into(r#type);
-        listener(value);
+        if self.value == value {
+            return;
+        }
+
+        self.set_force(value);
+    }
+
+    pub fn set_force(&mut self, value: T) {
+        self.value = self.latched(self.clamped(value));
+
+        if let Some(history) = self.history.as_mut() {
+            if history.entries.len() == history.capacity {
+                history.entries.pop_front();
+            }
+
+            history
+                .entries
+                .push_back((SystemTime::now(), self.value.clone()));
+        }
+
+        // no listener call: the host reads the value when it drains the polled states
+        if let Some(changed) = self.polled.as_ref() {
+            changed.store(true, Ordering::Release);
+            return;
+        }
+
+        // Unbound state happens when set from the plugin `new()`, before the component registers it.
+        // No host handler can be set at this point anyway, so the value is only kept as initial value.
+        if let Some(StateRuntimeData { listener, r#type }) = self.runtime.as_ref() {
+            let value = self.value.clone().typed_into(r#type);
+            listener(value);
+        }
     }
 
     pub fn get(&self) -> &T {
@@ -54,4 +135,105 @@ impl<T: Default + Clone + TypedInto<Value>> State<T> {
     pub fn runtime_register(&mut self, listener: Box<dyn Fn(Value)>, r#type: metadata::Type) {
         self.runtime = Some(StateRuntimeData { listener, r#type });
     }
+
+    pub fn runtime_clamp(&mut self, clamp: fn(T) -> T) {
+        self.clamp = Some(clamp);
+    }
+
+    pub fn runtime_polled(&mut self, changed: Arc<AtomicBool>) {
+        self.polled = Some(changed);
+    }
+
+    pub fn runtime_latched(&mut self) {
+        if self.latch.is_none() {
+            self.latch = Some(self.value.clone());
+        }
+    }
+
+    // Releases a latched state: it takes the last value set by the plugin, so it stays set if the condition is still present.
+    // No effect on states not declared `latched`
+    pub fn acknowledge(&mut self) {
+        let Some(condition) = self.latch.take() else {
+            return;
+        };
+
+        self.set(condition.clone());
+        self.latch = Some(condition);
+    }
+
+    // records the last `capacity` changes, already recorded changes are kept
+    pub fn runtime_history(&mut self, capacity: usize) {
+        let history = self.history.get_or_insert_with(|| StateHistory {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        });
+
+        history.capacity = capacity;
+        while history.entries.len() > capacity {
+            history.entries.pop_front();
+        }
+    }
+
+    // last changes, oldest first (empty if history is not enabled)
+    pub fn history(&self) -> impl Iterator<Item = &(SystemTime, T)> {
+        self.history
+            .iter()
+            .flat_map(|history| history.entries.iter())
+    }
+
+    // once set to a value other than the default, the state holds it until acknowledged
+    fn latched(&mut self, value: T) -> T {
+        let Some(condition) = self.latch.as_mut() else {
+            return value;
+        };
+
+        *condition = value.clone();
+
+        if self.value != T::default() && value != self.value {
+            return self.value.clone();
+        }
+
+        value
+    }
+
+    fn clamped(&self, value: T) -> T {
+        let Some(clamp) = self.clamp else {
+            return value;
+        };
+
+        let clamped = clamp(value.clone());
+        if clamped != value {
+            log::warn!(target: "mylife:home:core:plugin-runtime:state", "State value out of range, clamped");
+        }
+
+        clamped
+    }
+}
+
+// Transient occurrence (button pressed, packet received), not retained like a state
+pub struct Event<T> {
+    runtime: Option<StateRuntimeData>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Default for Event<T> {
+    fn default() -> Self {
+        Event {
+            runtime: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: TypedInto<Value>> Event<T> {
+    // dropped if no host event handler is set
+    pub fn emit(&self, value: T) {
+        if let Some(StateRuntimeData { listener, r#type }) = self.runtime.as_ref() {
+            listener(value.typed_into(r#type));
+        }
+    }
+
+    pub fn runtime_register(&mut self, listener: Box<dyn Fn(Value)>, r#type: metadata::Type) {
+        self.runtime = Some(StateRuntimeData { listener, r#type });
+    }
 }
@@ -0,0 +1,717 @@
+use regex::Regex;
+use std::{collections::BTreeMap, fmt, time::Duration};
+
+use crate::{
+    metadata::Type,
+    runtime::{is_single_char, Rgb, Value},
+};
+
+// Bus wire format, compatible with the TypeScript mylife-home core.
+// The payload is untagged, the member type is needed on both sides.
+//
+// - range: smallest primitive containing [min;max], little endian
+//     uint8 (1 byte) if 0 <= min && max <= 255
+//     int8 (1 byte) if -128 <= min && max <= 127
+//     uint32 (4 bytes) if 0 <= min && max <= 4294967295
+//     int32 (4 bytes) if -2147483648 <= min && max <= 2147483647
+//     wider ranges cannot be sent on the bus
+// - float: float32 (4 bytes), little endian
+// - bool: uint8 (1 byte), 0 or 1
+// - text, enum, char: utf-8 string, no terminator
+// - color: utf-8 string "#rrggbb", lower case (as css colors in the UI)
+//
+// complex: not encoded directly, use the schema (object type) from PluginMetadata::complex_type
+//
+// Types unknown to the TypeScript core are sent as utf-8 JSON:
+// - list: array, nullable: value or null, object: object with one key per field
+// - duration: integer milliseconds
+// - timestamp: integer milliseconds since epoch (UTC), as JS Date.getTime()
+//
+// Bridges to external systems use the From<Value> for serde_json::Value / Value::from_json pair instead,
+// same mapping except timestamps as ISO 8601 strings (from_json accepts both forms)
+impl Value {
+    pub fn encode(&self, ty: &Type) -> Result<Vec<u8>, EncodingError> {
+        match (self, ty) {
+            (Value::Range(value), Type::Range(min, max)) => {
+                if value < min || value > max {
+                    return Err(EncodingError::new(format!(
+                        "Value {value} out of range [{min};{max}]"
+                    )));
+                }
+
+                match RangePrimitive::of(*min, *max)? {
+                    RangePrimitive::UInt8 => Ok(vec![*value as u8]),
+                    RangePrimitive::Int8 => Ok((*value as i8).to_le_bytes().to_vec()),
+                    RangePrimitive::UInt32 => Ok((*value as u32).to_le_bytes().to_vec()),
+                    RangePrimitive::Int32 => Ok((*value as i32).to_le_bytes().to_vec()),
+                }
+            }
+            (Value::Float(value), Type::Float) => Ok((*value as f32).to_le_bytes().to_vec()),
+            (Value::Bool(value), Type::Bool) => Ok(vec![u8::from(*value)]),
+            (Value::Text(value), Type::Text) | (Value::Enum(value), Type::Enum(_)) => {
+                Ok(value.as_bytes().to_vec())
+            }
+            (Value::Text(value), Type::Char) if is_single_char(value) => {
+                Ok(value.as_bytes().to_vec())
+            }
+            (Value::Color { .. }, Type::Color) => Ok(self.to_string().into_bytes()),
+            (
+                _,
+                Type::List(_)
+                | Type::Nullable(_)
+                | Type::Object(_)
+                | Type::Duration
+                | Type::Timestamp,
+            ) => Ok(to_json(self, ty)?.to_string().into_bytes()),
+            (_, Type::Complex(name)) => Err(complex_without_schema(name)),
+            _ => Err(EncodingError::new(format!(
+                "Cannot encode {self:?} as {ty:?}"
+            ))),
+        }
+    }
+
+    // Payloads come from the bus and are untrusted: any input gives a value or an error, never a panic
+    pub fn decode(payload: &[u8], ty: &Type) -> Result<Value, DecodeError> {
+        match ty {
+            Type::Range(min, max) => {
+                let primitive = RangePrimitive::of(*min, *max)
+                    .map_err(|err| DecodeError::UnsupportedType(err.message))?;
+                let value = match primitive {
+                    RangePrimitive::UInt8 => u8::from_le_bytes(fixed(payload)?) as i64,
+                    RangePrimitive::Int8 => i8::from_le_bytes(fixed(payload)?) as i64,
+                    RangePrimitive::UInt32 => u32::from_le_bytes(fixed(payload)?) as i64,
+                    RangePrimitive::Int32 => i32::from_le_bytes(fixed(payload)?) as i64,
+                };
+
+                if value < *min || value > *max {
+                    return Err(DecodeError::InvalidValue(format!(
+                        "Value {value} out of range [{min};{max}]"
+                    )));
+                }
+
+                Ok(Value::Range(value))
+            }
+            Type::Float => Ok(Value::Float(f32::from_le_bytes(fixed(payload)?) as f64)),
+            Type::Bool => match fixed(payload)? {
+                [0] => Ok(Value::Bool(false)),
+                [1] => Ok(Value::Bool(true)),
+                [value] => Err(DecodeError::InvalidValue(format!(
+                    "Invalid bool payload {value}"
+                ))),
+            },
+            Type::Text => Ok(Value::Text(utf8(payload)?)),
+            Type::Enum(_) => Ok(Value::Enum(utf8(payload)?)),
+            Type::Color => {
+                let value = utf8(payload)?;
+                Rgb::from_hex(&value).map(Value::from).ok_or_else(|| {
+                    DecodeError::InvalidValue(format!("Invalid color payload '{value}'"))
+                })
+            }
+            Type::Char => match utf8(payload)? {
+                value if is_single_char(&value) => Ok(Value::Text(value)),
+                value => Err(DecodeError::InvalidValue(format!(
+                    "Invalid char payload '{value}'"
+                ))),
+            },
+            Type::Complex(name) => Err(DecodeError::UnsupportedType(
+                complex_without_schema(name).message,
+            )),
+            Type::List(_)
+            | Type::Nullable(_)
+            | Type::Object(_)
+            | Type::Duration
+            | Type::Timestamp => from_json(&parse_json(payload)?, ty)
+                .map_err(|err| DecodeError::InvalidValue(err.message)),
+        }
+    }
+}
+
+// Whether values of the type can be sent on the bus at all, without a value at hand (eg: validation at startup).
+// Only a top level range is limited, inside JSON any range fits
+pub fn check_type(ty: &Type) -> Result<(), EncodingError> {
+    match ty {
+        Type::Range(min, max) => RangePrimitive::of(*min, *max).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+enum RangePrimitive {
+    UInt8,
+    Int8,
+    UInt32,
+    Int32,
+}
+
+impl RangePrimitive {
+    fn of(min: i64, max: i64) -> Result<Self, EncodingError> {
+        if min >= 0 && max <= u8::MAX as i64 {
+            Ok(RangePrimitive::UInt8)
+        } else if min >= i8::MIN as i64 && max <= i8::MAX as i64 {
+            Ok(RangePrimitive::Int8)
+        } else if min >= 0 && max <= u32::MAX as i64 {
+            Ok(RangePrimitive::UInt32)
+        } else if min >= i32::MIN as i64 && max <= i32::MAX as i64 {
+            Ok(RangePrimitive::Int32)
+        } else {
+            Err(EncodingError::new(format!(
+                "Range [{min};{max}] does not fit in 32 bits"
+            )))
+        }
+    }
+}
+
+fn fixed<const N: usize>(payload: &[u8]) -> Result<[u8; N], DecodeError> {
+    payload.try_into().map_err(|_| {
+        if payload.len() < N {
+            DecodeError::Truncated {
+                expected: N,
+                actual: payload.len(),
+            }
+        } else {
+            DecodeError::Oversized {
+                expected: N,
+                actual: payload.len(),
+            }
+        }
+    })
+}
+
+fn utf8(payload: &[u8]) -> Result<String, DecodeError> {
+    String::from_utf8(payload.to_vec()).map_err(|err| DecodeError::InvalidUtf8(err.to_string()))
+}
+
+// Note: serde_json limits the nesting depth, deeply nested payloads are rejected instead of overflowing the stack
+fn parse_json(payload: &[u8]) -> Result<serde_json::Value, DecodeError> {
+    serde_json::from_slice(payload).map_err(|err| DecodeError::InvalidJson(err.to_string()))
+}
+
+pub(crate) fn to_json(value: &Value, ty: &Type) -> Result<serde_json::Value, EncodingError> {
+    let json = match (value, ty) {
+        (Value::Range(value), Type::Range(min, max)) if min <= value && value <= max => {
+            serde_json::Value::from(*value)
+        }
+        (Value::Float(value), Type::Float) => serde_json::Value::from(*value),
+        (Value::Bool(value), Type::Bool) => serde_json::Value::from(*value),
+        (Value::Text(value), Type::Text) | (Value::Enum(value), Type::Enum(_)) => {
+            serde_json::Value::from(value.as_str())
+        }
+        (Value::Text(value), Type::Char) if is_single_char(value) => {
+            serde_json::Value::from(value.as_str())
+        }
+        (Value::Color { .. }, Type::Color) => serde_json::Value::from(value.to_string()),
+        (Value::Duration(value), Type::Duration) => {
+            serde_json::Value::from(value.as_millis() as u64)
+        }
+        (Value::Timestamp(millis), Type::Timestamp) => serde_json::Value::from(*millis),
+        (Value::List(items), Type::List(item_type)) => serde_json::Value::Array(
+            items
+                .iter()
+                .map(|item| to_json(item, item_type))
+                .collect::<Result<_, _>>()?,
+        ),
+        (Value::Null, Type::Nullable(_)) => serde_json::Value::Null,
+        (value, Type::Nullable(value_type)) => to_json(value, value_type)?,
+        (Value::Object(fields), Type::Object(field_types)) if fields.len() == field_types.len() => {
+            let mut object = serde_json::Map::new();
+            for (name, field_type) in field_types {
+                let field = fields
+                    .get(name)
+                    .ok_or_else(|| EncodingError::new(format!("Missing object field '{name}'")))?;
+                object.insert(name.clone(), to_json(field, field_type)?);
+            }
+
+            serde_json::Value::Object(object)
+        }
+        _ => {
+            return Err(EncodingError::new(format!(
+                "Cannot encode {value:?} as {ty:?}"
+            )))
+        }
+    };
+
+    Ok(json)
+}
+
+fn from_json(json: &serde_json::Value, ty: &Type) -> Result<Value, EncodingError> {
+    let value = match (json, ty) {
+        (serde_json::Value::Number(value), Type::Range(min, max)) => match value.as_i64() {
+            Some(value) if *min <= value && value <= *max => Value::Range(value),
+            _ => return Err(json_mismatch(json, ty)),
+        },
+        (serde_json::Value::Number(value), Type::Float) => {
+            Value::Float(value.as_f64().ok_or_else(|| json_mismatch(json, ty))?)
+        }
+        (serde_json::Value::Bool(value), Type::Bool) => Value::Bool(*value),
+        (serde_json::Value::String(value), Type::Text) => Value::Text(value.clone()),
+        (serde_json::Value::String(value), Type::Char) if is_single_char(value) => {
+            Value::Text(value.clone())
+        }
+        (serde_json::Value::String(value), Type::Color) => Rgb::from_hex(value)
+            .ok_or_else(|| json_mismatch(json, ty))?
+            .into(),
+        (serde_json::Value::String(value), Type::Enum(_)) => Value::Enum(value.clone()),
+        (serde_json::Value::Number(value), Type::Duration) => Value::Duration(
+            Duration::from_millis(value.as_u64().ok_or_else(|| json_mismatch(json, ty))?),
+        ),
+        (serde_json::Value::Number(value), Type::Timestamp) => {
+            Value::Timestamp(value.as_i64().ok_or_else(|| json_mismatch(json, ty))?)
+        }
+        (serde_json::Value::String(value), Type::Timestamp) => {
+            Value::Timestamp(parse_timestamp(value).ok_or_else(|| json_mismatch(json, ty))?)
+        }
+        (serde_json::Value::Array(items), Type::List(item_type)) => Value::List(
+            items
+                .iter()
+                .map(|item| from_json(item, item_type))
+                .collect::<Result<_, _>>()?,
+        ),
+        (serde_json::Value::Null, Type::Nullable(_)) => Value::Null,
+        (json, Type::Nullable(value_type)) => from_json(json, value_type)?,
+        (serde_json::Value::Object(object), Type::Object(field_types))
+            if object.len() == field_types.len() =>
+        {
+            let mut fields = BTreeMap::new();
+            for (name, field_type) in field_types {
+                let field = object.get(name).ok_or_else(|| json_mismatch(json, ty))?;
+                fields.insert(name.clone(), from_json(field, field_type)?);
+            }
+
+            Value::Object(fields)
+        }
+        _ => return Err(json_mismatch(json, ty)),
+    };
+
+    Ok(value)
+}
+
+impl Value {
+    // type needed: eg: a JSON string may be a text, an enum, a char, a color or a timestamp
+    pub fn from_json(json: &serde_json::Value, ty: &Type) -> Result<Value, EncodingError> {
+        from_json(json, ty)
+    }
+}
+
+// untyped: floats which are not finite become null
+impl From<Value> for serde_json::Value {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Range(value) => serde_json::Value::from(value),
+            Value::Float(value) => serde_json::Value::from(value),
+            Value::Bool(value) => serde_json::Value::from(value),
+            Value::Text(value) | Value::Enum(value) => serde_json::Value::from(value),
+            Value::List(items) => {
+                serde_json::Value::Array(items.into_iter().map(serde_json::Value::from).collect())
+            }
+            Value::Null => serde_json::Value::Null,
+            Value::Object(fields) => serde_json::Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(name, field)| (name, serde_json::Value::from(field)))
+                    .collect(),
+            ),
+            Value::Duration(value) => serde_json::Value::from(value.as_millis() as u64),
+            Value::Timestamp(millis) => serde_json::Value::from(format_timestamp(millis)),
+            Value::Color { .. } => serde_json::Value::from(value.to_string()),
+        }
+    }
+}
+
+// ISO 8601, UTC with milliseconds, eg: "2023-11-14T22:13:20.000Z"
+fn format_timestamp(millis: i64) -> String {
+    let seconds = millis.div_euclid(1000);
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+    let day_seconds = seconds.rem_euclid(86400);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        day_seconds / 3600,
+        day_seconds % 3600 / 60,
+        day_seconds % 60,
+        millis.rem_euclid(1000)
+    )
+}
+
+// ISO 8601 date and time with seconds, 'Z' or a '+hh:mm' offset, digits after milliseconds are truncated
+fn parse_timestamp(input: &str) -> Option<i64> {
+    lazy_static::lazy_static! {
+        static ref TIMESTAMP: Regex = Regex::new(
+            r"^([0-9]{4})-([0-9]{2})-([0-9]{2})T([0-9]{2}):([0-9]{2}):([0-9]{2})(?:\.([0-9]{1,9}))?(?:(Z)|([+-])([0-9]{2}):([0-9]{2}))$"
+        )
+        .unwrap();
+    }
+
+    let captures = TIMESTAMP.captures(input)?;
+    let number = |index: usize| -> i64 {
+        captures
+            .get(index)
+            .map_or(0, |m| m.as_str().parse().unwrap())
+    };
+
+    let (year, month, day) = (number(1), number(2), number(3));
+    let (hours, minutes, seconds) = (number(4), number(5), number(6));
+    if !(1..=12).contains(&month) || hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    if civil_from_days(days) != (year, month, day) {
+        return None; // eg: February 30
+    }
+
+    let millis = captures.get(7).map_or(0, |fraction| {
+        format!(
+            "{:0<3}",
+            &fraction.as_str()[..fraction.as_str().len().min(3)]
+        )
+        .parse()
+        .unwrap()
+    });
+
+    let offset = match captures.get(9) {
+        None => 0,
+        Some(sign) => {
+            let offset = (number(10) * 60 + number(11)) * 60;
+            if sign.as_str() == "-" {
+                -offset
+            } else {
+                offset
+            }
+        }
+    };
+
+    let total_seconds = days * 86400 + hours * 3600 + minutes * 60 + seconds - offset;
+    Some(total_seconds * 1000 + millis)
+}
+
+// days since 1970-01-01 to (year, month, day), proleptic gregorian calendar
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468; // from 0000-03-01
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_from_march = (month + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+fn complex_without_schema(name: &str) -> EncodingError {
+    EncodingError::new(format!(
+        "Complex type '{name}' must be encoded with its schema"
+    ))
+}
+
+fn json_mismatch(json: &serde_json::Value, ty: &Type) -> EncodingError {
+    EncodingError::new(format!("Cannot decode JSON {json} as {ty:?}"))
+}
+
+#[derive(Debug, Clone)]
+pub struct EncodingError {
+    message: String,
+}
+
+impl EncodingError {
+    fn new(message: String) -> Self {
+        EncodingError { message }
+    }
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Encoding error: {}", self.message)
+    }
+}
+
+impl std::error::Error for EncodingError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    Truncated { expected: usize, actual: usize }, // fixed size payload, in bytes
+    Oversized { expected: usize, actual: usize }, // same
+    InvalidUtf8(String),
+    InvalidJson(String),
+    InvalidValue(String),    // well formed payload, but not a value of the type
+    UnsupportedType(String), // the type cannot be decoded from the bus
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Truncated { expected, actual } => write!(
+                fmt,
+                "Decoding error: truncated payload, expected {expected} bytes, got {actual}"
+            ),
+            DecodeError::Oversized { expected, actual } => write!(
+                fmt,
+                "Decoding error: oversized payload, expected {expected} bytes, got {actual}"
+            ),
+            DecodeError::InvalidUtf8(reason) => {
+                write!(fmt, "Decoding error: invalid utf-8 payload: {reason}")
+            }
+            DecodeError::InvalidJson(reason) => {
+                write!(fmt, "Decoding error: invalid JSON payload: {reason}")
+            }
+            DecodeError::InvalidValue(reason) => write!(fmt, "Decoding error: {reason}"),
+            DecodeError::UnsupportedType(reason) => write!(fmt, "Decoding error: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_round_trip(value: Value, ty: Type, expected: &[u8]) {
+        let payload = value.encode(&ty).unwrap();
+        assert_eq!(payload, expected);
+        assert_eq!(Value::decode(&payload, &ty).unwrap(), value);
+    }
+
+    #[test]
+    fn test_primitives() {
+        test_round_trip(Value::Range(200), Type::Range(0, 255), &[200]);
+        test_round_trip(Value::Range(-2), Type::Range(-10, 10), &[0xfe]);
+        test_round_trip(Value::Range(256), Type::Range(0, 1000), &[0, 1, 0, 0]);
+        test_round_trip(
+            Value::Range(-2),
+            Type::Range(-1000, 1000),
+            &[0xfe, 0xff, 0xff, 0xff],
+        );
+        test_round_trip(Value::Float(1.5), Type::Float, &[0, 0, 0xc0, 0x3f]);
+        test_round_trip(Value::Bool(true), Type::Bool, &[1]);
+        test_round_trip(Value::Text("héllo".into()), Type::Text, "héllo".as_bytes());
+        test_round_trip(
+            Value::Enum("on".into()),
+            Type::Enum(vec!["on".into(), "off".into()]),
+            b"on",
+        );
+        test_round_trip(Value::Text("é".into()), Type::Char, "é".as_bytes());
+        test_round_trip(Rgb::new(255, 128, 0).into(), Type::Color, b"#ff8000");
+    }
+
+    #[test]
+    fn test_json() {
+        test_round_trip(
+            Value::List(vec![Value::Bool(true), Value::Bool(false)]),
+            Type::List(Box::new(Type::Bool)),
+            b"[true,false]",
+        );
+        test_round_trip(Value::Null, Type::Nullable(Box::new(Type::Text)), b"null");
+        test_round_trip(
+            Value::Text("x".into()),
+            Type::Nullable(Box::new(Type::Text)),
+            b"\"x\"",
+        );
+        test_round_trip(
+            Value::Duration(Duration::from_millis(1500)),
+            Type::Duration,
+            b"1500",
+        );
+        test_round_trip(
+            Value::Timestamp(1_700_000_000_000),
+            Type::Timestamp,
+            b"1700000000000",
+        );
+        test_round_trip(
+            Value::Object(BTreeMap::from([
+                ("a".to_string(), Value::Range(1)),
+                ("b".to_string(), Value::Float(0.5)),
+            ])),
+            Type::Object(vec![
+                ("a".to_string(), Type::Range(0, 10)),
+                ("b".to_string(), Type::Float),
+            ]),
+            b"{\"a\":1,\"b\":0.5}",
+        );
+    }
+
+    #[test]
+    fn test_value_json() {
+        let ty = Type::Object(vec![
+            ("level".to_string(), Type::Range(0, 100)),
+            (
+                "mode".to_string(),
+                Type::Enum(vec!["eco".to_string(), "comfort".to_string()]),
+            ),
+            ("since".to_string(), Type::Timestamp),
+            ("color".to_string(), Type::Nullable(Box::new(Type::Color))),
+        ]);
+        let value = Value::Object(BTreeMap::from([
+            ("level".to_string(), Value::Range(42)),
+            ("mode".to_string(), Value::Enum("eco".into())),
+            ("since".to_string(), Value::Timestamp(1_700_000_000_123)),
+            ("color".to_string(), Rgb::new(255, 128, 0).into()),
+        ]));
+
+        let json = serde_json::Value::from(value.clone());
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "level": 42,
+                "mode": "eco",
+                "since": "2023-11-14T22:13:20.123Z",
+                "color": "#ff8000",
+            })
+        );
+        assert_eq!(Value::from_json(&json, &ty).unwrap(), value);
+
+        let ty = Type::Timestamp;
+        for (input, millis) in [
+            ("1970-01-01T00:00:00Z", 0),
+            ("1969-12-31T23:59:59.999Z", -1),
+            ("2024-02-29T12:00:00.5+02:00", 1_709_200_800_500),
+            ("2000-03-01T00:00:00.123456Z", 951_868_800_123),
+        ] {
+            assert_eq!(
+                Value::from_json(&serde_json::json!(input), &ty).unwrap(),
+                Value::Timestamp(millis)
+            );
+        }
+
+        for input in [
+            "2023-02-29T00:00:00Z",
+            "2023-01-01T24:00:00Z",
+            "2023-01-01 00:00:00Z",
+            "2023-01-01T00:00:00",
+        ] {
+            assert!(Value::from_json(&serde_json::json!(input), &ty).is_err());
+        }
+
+        assert_eq!(
+            serde_json::Value::from(Value::Timestamp(-1)),
+            serde_json::json!("1969-12-31T23:59:59.999Z")
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            Value::decode(&[1, 2], &Type::Float),
+            Err(DecodeError::Truncated {
+                expected: 4,
+                actual: 2
+            })
+        );
+        assert_eq!(
+            Value::decode(&[1, 0], &Type::Bool),
+            Err(DecodeError::Oversized {
+                expected: 1,
+                actual: 2
+            })
+        );
+        assert!(matches!(
+            Value::decode(&[0xff], &Type::Text),
+            Err(DecodeError::InvalidUtf8(_))
+        ));
+        assert!(matches!(
+            Value::decode(b"[1,", &Type::List(Box::new(Type::Bool))),
+            Err(DecodeError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            Value::decode(b"[1]", &Type::List(Box::new(Type::Bool))),
+            Err(DecodeError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            Value::decode(&[0], &Type::Range(0, i64::MAX)),
+            Err(DecodeError::UnsupportedType(_))
+        ));
+
+        let nested = "[".repeat(10_000);
+        assert!(matches!(
+            Value::decode(nested.as_bytes(), &Type::List(Box::new(Type::Bool))),
+            Err(DecodeError::InvalidJson(_))
+        ));
+    }
+
+    // poor man's fuzzing: arbitrary payloads, including truncated and oversized ones, never panic
+    #[test]
+    fn test_decode_arbitrary() {
+        let types = [
+            Type::Range(0, 255),
+            Type::Range(-10, 10),
+            Type::Range(0, 100_000),
+            Type::Range(-100_000, 100_000),
+            Type::Float,
+            Type::Bool,
+            Type::Text,
+            Type::Enum(vec!["a".to_string()]),
+            Type::Char,
+            Type::Color,
+            Type::Duration,
+            Type::Timestamp,
+            Type::List(Box::new(Type::Nullable(Box::new(Type::Range(0, 10))))),
+            Type::Object(vec![
+                ("a".to_string(), Type::Timestamp),
+                ("b".to_string(), Type::Char),
+            ]),
+        ];
+
+        let samples: [&[u8]; 8] = [
+            b"",
+            b"\"2024-02-30T00:00:00Z\"",
+            b"{\"a\":\"1970-01-01T00:00:00.999999999Z\",\"b\":\"x\"}",
+            b"[null,1,11]",
+            b"-1",
+            b"18446744073709551616",
+            b"#fffffff",
+            &[0xf0, 0x9f, 0x92],
+        ];
+
+        // xorshift, deterministic
+        let mut state: u64 = 0x2545f4914f6cdd1d;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for ty in types.iter() {
+            for sample in samples {
+                let _ = Value::decode(sample, ty);
+            }
+
+            for _ in 0..1000 {
+                let len = (next() % 12) as usize;
+                let payload: Vec<u8> = (0..len).map(|_| next() as u8).collect();
+                let _ = Value::decode(&payload, ty);
+            }
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(Value::Range(300).encode(&Type::Range(0, 255)).is_err());
+        assert!(Value::Range(0).encode(&Type::Range(0, i64::MAX)).is_err());
+        assert!(Value::Bool(true).encode(&Type::Text).is_err());
+        assert!(Value::decode(&[0, 1], &Type::Range(0, 255)).is_err());
+        assert!(Value::decode(&[2], &Type::Bool).is_err());
+        assert!(Value::decode(&[200], &Type::Range(0, 100)).is_err());
+        assert!(check_type(&Type::Range(0, i64::MAX)).is_err());
+        assert!(check_type(&Type::List(Box::new(Type::Range(0, i64::MAX)))).is_ok());
+        assert!(Value::decode(b"ab", &Type::Char).is_err());
+        assert!(Value::Text("ab".into()).encode(&Type::Char).is_err());
+        assert!(Value::decode(b"[1]", &Type::List(Box::new(Type::Bool))).is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use regex::Regex;
-use std::{collections::HashMap, fmt, num::ParseIntError, str};
+use std::{collections::HashMap, fmt, num::ParseIntError, str, time::Duration};
+
+use crate::runtime::{parse_duration, Value};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum PluginUsage {
@@ -9,33 +11,82 @@ pub enum PluginUsage {
     Ui,
 }
 
+// UI organization
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PluginCategory {
+    Lighting,
+    Heating,
+    Shutters,
+    Security,
+    Energy,
+    Multimedia,
+    Network,
+    Other,
+}
+
+// Host resources a plugin needs, a sandboxed host may not grant all of them
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Capability {
+    Network,
+    Filesystem,
+    Time, // real-time clock
+    Gpio,
+    Serial,
+}
+
 #[derive(Debug)]
 pub struct PluginMetadata {
     // id
     name: String,
+    // stable id, independent of module and struct names
+    id: Option<String>,
     // module
     usage: PluginUsage,
-    // version
+    // plugin schema version, independent of the module version
+    version: Option<String>,
     description: Option<String>,
+    category: Option<PluginCategory>,
+    tags: Vec<String>,
+    required_capabilities: Vec<Capability>,
+    deprecated: Option<String>, // message, eg: the replacement to use
 
     members: HashMap<String, Member>,
     config: HashMap<String, ConfigItem>,
+    extra_config: bool,
+    complex_types: HashMap<String, Vec<(String, Type)>>,
 }
 
 impl PluginMetadata {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         name: String,
+        id: Option<String>,
         usage: PluginUsage,
+        version: Option<String>,
         description: Option<String>,
+        category: Option<PluginCategory>,
+        tags: Vec<String>,
+        required_capabilities: Vec<Capability>,
+        deprecated: Option<String>,
         members: HashMap<String, Member>,
         config: HashMap<String, ConfigItem>,
+        extra_config: bool,
+        complex_types: HashMap<String, Vec<(String, Type)>>,
     ) -> PluginMetadata {
         PluginMetadata {
             name,
+            id,
             usage,
+            version,
             description,
+            category,
+            tags,
+            required_capabilities,
+            deprecated,
             members,
             config,
+            extra_config,
+            complex_types,
         }
     }
 
@@ -43,14 +94,41 @@ impl PluginMetadata {
         &self.name
     }
 
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
     pub fn description(&self) -> Option<&str> {
         self.description.as_deref()
     }
 
+    // None: the plugin follows its module version
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
     pub fn usage(&self) -> PluginUsage {
         self.usage
     }
 
+    pub fn category(&self) -> Option<PluginCategory> {
+        self.category
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    // the host refuses to create components if one of them is not granted
+    pub fn required_capabilities(&self) -> &[Capability] {
+        &self.required_capabilities
+    }
+
+    // Some(message) if the plugin should no longer be used
+    pub fn deprecated(&self) -> Option<&str> {
+        self.deprecated.as_deref()
+    }
+
     pub fn members(&self) -> &HashMap<String, Member> {
         &self.members
     }
@@ -58,12 +136,30 @@ impl PluginMetadata {
     pub fn config(&self) -> &HashMap<String, ConfigItem> {
         &self.config
     }
+
+    // config keys not declared in config() are accepted and given to the plugin as is
+    pub fn accepts_extra_config(&self) -> bool {
+        self.extra_config
+    }
+
+    // schemas of the Complex types used by members
+    pub fn complex_types(&self) -> &HashMap<String, Vec<(String, Type)>> {
+        &self.complex_types
+    }
+
+    // Complex values are objects: the schema as an object type, to encode or check values
+    pub fn complex_type(&self, name: &str) -> Option<Type> {
+        self.complex_types
+            .get(name)
+            .map(|fields| Type::Object(fields.clone()))
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum MemberType {
     Action,
     State,
+    Event, // transient, not retained like states
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -73,7 +169,14 @@ pub enum Type {
     Float,
     Bool,
     Enum(Vec<String>),
-    Complex,
+    List(Box<Type>),
+    Nullable(Box<Type>),
+    Object(Vec<(String, Type)>),
+    Duration,
+    Timestamp,       // UTC
+    Char,            // text of exactly one char
+    Color,           // rgb
+    Complex(String), // schema name, see PluginMetadata::complex_type
 }
 
 impl str::FromStr for Type {
@@ -84,6 +187,10 @@ impl str::FromStr for Type {
             static ref PARSER: Regex = Regex::new(r"([a-z]+)(.*)").unwrap();
             static ref RANGE_PARSER: Regex = Regex::new(r"\[(-?\d+);(-?\d+)\]").unwrap();
             static ref ENUM_PARSER: Regex = Regex::new(r"\{(.[\w_\-,]+)\}").unwrap();
+            static ref INNER_TYPE_PARSER: Regex = Regex::new(r"^\[(.+)\]$").unwrap();
+            static ref OBJECT_PARSER: Regex = Regex::new(r"^\{(.+)\}$").unwrap();
+            static ref OBJECT_FIELD_PARSER: Regex = Regex::new(r"^(\w+):(.+)$").unwrap();
+            static ref COMPLEX_PARSER: Regex = Regex::new(r"^\[([\w\-]+)\]$").unwrap();
         }
 
         let matchs = PARSER
@@ -131,34 +238,70 @@ impl str::FromStr for Type {
                     return Err(TypeParseError::new(input, TypeParseErrorReason::MinMax));
                 }
 
-                return Ok(Type::Range(min, max));
+                Ok(Type::Range(min, max))
             }
             "text" => {
-                if args != "" {
+                if !args.is_empty() {
                     return Err(TypeParseError::new(
                         input,
                         TypeParseErrorReason::UnexpectedArgs,
                     ));
                 }
-                return Ok(Type::Text);
+                Ok(Type::Text)
             }
             "float" => {
-                if args != "" {
+                if !args.is_empty() {
                     return Err(TypeParseError::new(
                         input,
                         TypeParseErrorReason::UnexpectedArgs,
                     ));
                 }
-                return Ok(Type::Float);
+                Ok(Type::Float)
+            }
+            "duration" => {
+                if !args.is_empty() {
+                    return Err(TypeParseError::new(
+                        input,
+                        TypeParseErrorReason::UnexpectedArgs,
+                    ));
+                }
+                Ok(Type::Duration)
+            }
+            "timestamp" => {
+                if !args.is_empty() {
+                    return Err(TypeParseError::new(
+                        input,
+                        TypeParseErrorReason::UnexpectedArgs,
+                    ));
+                }
+                Ok(Type::Timestamp)
+            }
+            "char" => {
+                if !args.is_empty() {
+                    return Err(TypeParseError::new(
+                        input,
+                        TypeParseErrorReason::UnexpectedArgs,
+                    ));
+                }
+                Ok(Type::Char)
+            }
+            "color" => {
+                if !args.is_empty() {
+                    return Err(TypeParseError::new(
+                        input,
+                        TypeParseErrorReason::UnexpectedArgs,
+                    ));
+                }
+                Ok(Type::Color)
             }
             "bool" => {
-                if args != "" {
+                if !args.is_empty() {
                     return Err(TypeParseError::new(
                         input,
                         TypeParseErrorReason::UnexpectedArgs,
                     ));
                 }
-                return Ok(Type::Bool);
+                Ok(Type::Bool)
             }
             "enum" => {
                 let matchs = ENUM_PARSER
@@ -173,25 +316,86 @@ impl str::FromStr for Type {
                     return Err(TypeParseError::new(input, TypeParseErrorReason::BadArgs));
                 }
 
-                return Ok(Type::Enum(values));
+                Ok(Type::Enum(values))
             }
-            "complex" => {
-                if args != "" {
-                    return Err(TypeParseError::new(
-                        input,
-                        TypeParseErrorReason::UnexpectedArgs,
-                    ));
+            "list" => {
+                let matchs = INNER_TYPE_PARSER
+                    .captures(args)
+                    .ok_or_else(|| TypeParseError::new(input, TypeParseErrorReason::BadArgs))?;
+                if matchs.len() != 2 {
+                    return Err(TypeParseError::new(input, TypeParseErrorReason::BadArgs));
                 }
-                return Ok(Type::Complex);
+
+                let item_type = matchs[1].parse::<Type>()?;
+
+                Ok(Type::List(Box::new(item_type)))
             }
-            _ => {
-                return Err(TypeParseError::new(
-                    input,
-                    TypeParseErrorReason::UnknownType,
-                ));
+            "nullable" => {
+                let matchs = INNER_TYPE_PARSER
+                    .captures(args)
+                    .ok_or_else(|| TypeParseError::new(input, TypeParseErrorReason::BadArgs))?;
+                if matchs.len() != 2 {
+                    return Err(TypeParseError::new(input, TypeParseErrorReason::BadArgs));
+                }
+
+                let value_type = matchs[1].parse::<Type>()?;
+
+                Ok(Type::Nullable(Box::new(value_type)))
+            }
+            "object" => {
+                let matchs = OBJECT_PARSER
+                    .captures(args)
+                    .ok_or_else(|| TypeParseError::new(input, TypeParseErrorReason::BadArgs))?;
+                if matchs.len() != 2 {
+                    return Err(TypeParseError::new(input, TypeParseErrorReason::BadArgs));
+                }
+
+                let mut fields = Vec::new();
+                for field in split_top_level(&matchs[1]) {
+                    let matchs = OBJECT_FIELD_PARSER
+                        .captures(field)
+                        .ok_or_else(|| TypeParseError::new(input, TypeParseErrorReason::BadArgs))?;
+
+                    fields.push((String::from(&matchs[1]), matchs[2].parse::<Type>()?));
+                }
+
+                Ok(Type::Object(fields))
             }
+            "complex" => {
+                let matchs = COMPLEX_PARSER
+                    .captures(args)
+                    .ok_or_else(|| TypeParseError::new(input, TypeParseErrorReason::BadArgs))?;
+
+                Ok(Type::Complex(String::from(&matchs[1])))
+            }
+            _ => Err(TypeParseError::new(
+                input,
+                TypeParseErrorReason::UnknownType,
+            )),
+        }
+    }
+}
+
+// split on commas which are not nested in brackets or braces
+fn split_top_level(input: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, c) in input.char_indices() {
+        match c {
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(&input[start..index]);
+                start = index + 1;
+            }
+            _ => {}
         }
     }
+
+    items.push(&input[start..]);
+    items
 }
 
 impl fmt::Display for Type {
@@ -200,9 +404,22 @@ impl fmt::Display for Type {
             Type::Range(min, max) => write!(f, "range[{};{}]", min, max),
             Type::Text => write!(f, "text"),
             Type::Float => write!(f, "float"),
+            Type::Duration => write!(f, "duration"),
+            Type::Timestamp => write!(f, "timestamp"),
+            Type::Char => write!(f, "char"),
+            Type::Color => write!(f, "color"),
             Type::Bool => write!(f, "bool"),
             Type::Enum(list) => write!(f, "enum{{{}}}", list.join(",")),
-            Type::Complex => write!(f, "complex"),
+            Type::List(item_type) => write!(f, "list[{}]", item_type),
+            Type::Nullable(value_type) => write!(f, "nullable[{}]", value_type),
+            Type::Object(fields) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(name, field_type)| format!("{}:{}", name, field_type))
+                    .collect();
+                write!(f, "object{{{}}}", fields.join(","))
+            }
+            Type::Complex(name) => write!(f, "complex[{}]", name),
         }
     }
 }
@@ -282,11 +499,15 @@ mod tests {
     #[test]
     fn test_parse_text() {
         test_parse_type("text");
+        test_parse_type("char");
+        test_parse_type("color");
     }
 
     #[test]
     fn test_parse_float() {
         test_parse_type("float");
+        test_parse_type("duration");
+        test_parse_type("timestamp");
     }
 
     #[test]
@@ -299,9 +520,441 @@ mod tests {
         test_parse_type("enum{one,two,three}");
     }
 
+    #[test]
+    fn test_parse_list() {
+        test_parse_type("list[bool]");
+        test_parse_type("list[range[-12;42]]");
+        test_parse_type("list[list[enum{one,two,three}]]");
+    }
+
+    #[test]
+    fn test_parse_nullable() {
+        test_parse_type("nullable[float]");
+        test_parse_type("list[nullable[text]]");
+    }
+
+    #[test]
+    fn test_parse_object() {
+        test_parse_type("object{temperature:float,humidity:range[0;100]}");
+        test_parse_type("object{mode:enum{one,two},values:list[object{value:bool}]}");
+    }
+
     #[test]
     fn test_parse_complex() {
-        test_parse_type("complex");
+        test_parse_type("complex[rgb-color]");
+        test_parse_type("list[complex[geo]]");
+        assert!("complex".parse::<Type>().is_err());
+    }
+
+    #[test]
+    fn test_builder_category() {
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Actuator)
+            .category(Some(PluginCategory::Lighting))
+            .tags(&["actuator", "dimmable"])
+            .build()
+            .unwrap();
+
+        assert_eq!(meta.category(), Some(PluginCategory::Lighting));
+        assert_eq!(meta.tags(), &["actuator", "dimmable"]);
+    }
+
+    #[test]
+    fn test_builder() {
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .description(Some("plugin description"))
+            .usage(PluginUsage::Logic)
+            .add_config("configName", None, ConfigType::Bool)
+            .add_state("stateName", Some("state description"), Type::Bool)
+            .add_action("actionName", None, Type::Range(0, 42))
+            .build()
+            .unwrap();
+
+        assert_eq!(meta.name(), "plugin-name");
+        assert_eq!(meta.description(), Some("plugin description"));
+        assert_eq!(meta.usage(), PluginUsage::Logic);
+        assert_eq!(meta.category(), None);
+        assert!(meta.tags().is_empty());
+        assert!(meta.required_capabilities().is_empty());
+        assert_eq!(meta.version(), None);
+        assert_eq!(meta.config()["configName"].value_type(), ConfigType::Bool);
+        assert_eq!(meta.members()["stateName"].member_type(), MemberType::State);
+        assert_eq!(
+            meta.members()["actionName"].value_type(),
+            &Type::Range(0, 42)
+        );
+    }
+
+    #[test]
+    fn test_builder_duplicate_member() {
+        let err = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .add_state("value", None, Type::Bool)
+            .add_action("value", None, Type::Bool)
+            .build()
+            .unwrap_err();
+
+        assert!(matches!(err, MetadataBuildError::DuplicateMember(name) if name == "value"));
+    }
+
+    #[test]
+    fn test_builder_state_actions() {
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .add_state("value", None, Type::Bool)
+            .add_action("on", None, Type::Bool)
+            .add_action("off", None, Type::Bool)
+            .link_state_actions("value", &["on", "off"])
+            .build()
+            .unwrap();
+
+        assert_eq!(meta.members()["value"].actions(), &["on", "off"]);
+
+        let err = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .add_state("value", None, Type::Bool)
+            .link_state_actions("value", &["toggle"])
+            .build()
+            .unwrap_err();
+
+        assert!(matches!(err, MetadataBuildError::UnknownAction(_, action) if action == "toggle"));
+    }
+
+    #[test]
+    fn test_builder_config_pattern() {
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .add_config("host", None, ConfigType::String)
+            .set_config_pattern("host", "^[a-z0-9-]+$")
+            .build()
+            .unwrap();
+
+        assert_eq!(meta.config()["host"].pattern(), Some("^[a-z0-9-]+$"));
+
+        let err = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .add_config("port", None, ConfigType::Integer)
+            .set_config_pattern("port", "^[0-9]+$")
+            .build()
+            .unwrap_err();
+
+        assert!(matches!(err, MetadataBuildError::InvalidPattern(name, _) if name == "port"));
+    }
+
+    #[test]
+    fn test_builder_units() {
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Sensor)
+            .add_state("temperature", None, Type::Float)
+            .add_state("humidity", None, Type::Float)
+            .add_config("interval", None, ConfigType::Integer)
+            .set_state_unit("temperature", "°C")
+            .set_config_unit("interval", "ms")
+            .build()
+            .unwrap();
+
+        assert_eq!(meta.members()["temperature"].unit(), Some("°C"));
+        assert_eq!(meta.members()["humidity"].unit(), None);
+        assert_eq!(meta.config()["interval"].unit(), Some("ms"));
+        assert!(!meta.config()["interval"].nullable());
+
+        let err = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Sensor)
+            .add_action("setValue", None, Type::Float)
+            .set_state_unit("setValue", "°C")
+            .build()
+            .unwrap_err();
+
+        assert!(matches!(err, MetadataBuildError::UnknownState(name) if name == "setValue"));
+    }
+
+    #[test]
+    fn test_builder_config_range() {
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .add_config("level", None, ConfigType::Integer)
+            .add_config("port", None, ConfigType::Integer)
+            .set_config_range("level", 0, 100, true)
+            .set_config_range("port", 1, 65535, false)
+            .build()
+            .unwrap();
+
+        assert_eq!(meta.config()["level"].range(), Some((0, 100)));
+        assert!(meta.config()["level"].clamp());
+        assert_eq!(meta.config()["port"].range(), Some((1, 65535)));
+        assert!(!meta.config()["port"].clamp());
+
+        let err = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .add_config("host", None, ConfigType::String)
+            .set_config_range("host", 0, 100, true)
+            .build()
+            .unwrap_err();
+
+        assert!(matches!(err, MetadataBuildError::InvalidRange(name, _) if name == "host"));
+
+        let err = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .add_config("level", None, ConfigType::Integer)
+            .set_config_range("level", 100, 0, false)
+            .build()
+            .unwrap_err();
+
+        assert!(matches!(err, MetadataBuildError::InvalidRange(name, _) if name == "level"));
+    }
+
+    #[test]
+    fn test_builder_config_nullable() {
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .add_config("level", None, ConfigType::Integer)
+            .set_config_nullable("level")
+            .build()
+            .unwrap();
+
+        assert!(meta.config()["level"].nullable());
+
+        let err = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .set_config_nullable("level")
+            .build()
+            .unwrap_err();
+
+        assert!(matches!(err, MetadataBuildError::UnknownConfig(name) if name == "level"));
+    }
+
+    #[test]
+    fn test_builder_config_secret() {
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .add_config("api-key", None, ConfigType::String)
+            .add_config("host", None, ConfigType::String)
+            .set_config_secret("api-key")
+            .build()
+            .unwrap();
+
+        assert!(meta.config()["api-key"].secret());
+        assert!(!meta.config()["host"].secret());
+    }
+
+    #[test]
+    fn test_builder_complex_types() {
+        let rgb = vec![(String::from("red"), Type::Range(0, 255))];
+
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .add_state("color", None, Type::Complex(String::from("rgb")))
+            .register_complex_type("rgb", rgb.clone())
+            .register_complex_type("rgb", rgb.clone())
+            .build()
+            .unwrap();
+
+        assert_eq!(meta.complex_type("rgb"), Some(Type::Object(rgb.clone())));
+
+        let err = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .add_state("color", None, Type::Complex(String::from("rgb")))
+            .build()
+            .unwrap_err();
+
+        assert!(
+            matches!(err, MetadataBuildError::UnknownComplexType(member, _) if member == "color")
+        );
+
+        let err = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .register_complex_type("rgb", rgb)
+            .register_complex_type("rgb", vec![])
+            .build()
+            .unwrap_err();
+
+        assert!(matches!(err, MetadataBuildError::ConflictingComplexType(name) if name == "rgb"));
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let rate_limit: RateLimit = "10/s".parse().unwrap();
+        assert_eq!(rate_limit, RateLimit::new(10, Duration::from_secs(1)));
+        assert_eq!(
+            "3/10s".parse::<RateLimit>().unwrap(),
+            RateLimit::new(3, Duration::from_secs(10))
+        );
+        assert!("0/s".parse::<RateLimit>().is_err());
+        assert!("10".parse::<RateLimit>().is_err());
+        assert!("10/day".parse::<RateLimit>().is_err());
+
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Actuator)
+            .add_action("toggle", None, Type::Bool)
+            .set_action_rate_limit("toggle", rate_limit)
+            .build()
+            .unwrap();
+
+        assert_eq!(meta.members()["toggle"].rate_limit(), Some(rate_limit));
+
+        let err = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Actuator)
+            .add_state("value", None, Type::Bool)
+            .set_action_rate_limit("value", rate_limit)
+            .build()
+            .unwrap_err();
+
+        assert!(matches!(err, MetadataBuildError::InvalidRateLimit(name) if name == "value"));
+    }
+
+    #[test]
+    fn test_builder_action_default_arg() {
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .add_action("on", None, Type::Bool)
+            .set_action_default_arg("on", "true")
+            .build()
+            .unwrap();
+
+        assert_eq!(meta.members()["on"].default_arg(), Some(&Value::Bool(true)));
+
+        for (value_type, value) in [
+            (Type::Range(0, 10), "11"),
+            (Type::Bool, "yes"),
+            (Type::Nullable(Box::new(Type::Bool)), "true"),
+        ] {
+            let err = PluginMetadataBuilder::new()
+                .name("plugin-name")
+                .usage(PluginUsage::Logic)
+                .add_action("on", None, value_type)
+                .set_action_default_arg("on", value)
+                .build()
+                .unwrap_err();
+
+            assert!(matches!(err, MetadataBuildError::InvalidDefaultArg(name, _) if name == "on"));
+        }
+    }
+
+    #[test]
+    fn test_builder_id() {
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .build()
+            .unwrap();
+
+        assert_eq!(meta.id(), None);
+
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .id("2f1c7a7e-light")
+            .usage(PluginUsage::Logic)
+            .build()
+            .unwrap();
+
+        assert_eq!(meta.id(), Some("2f1c7a7e-light"));
+
+        let err = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .id("")
+            .usage(PluginUsage::Logic)
+            .build()
+            .unwrap_err();
+
+        assert!(matches!(err, MetadataBuildError::EmptyId));
+    }
+
+    #[test]
+    fn test_builder_version() {
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .version("2.1.0")
+            .usage(PluginUsage::Logic)
+            .build()
+            .unwrap();
+
+        assert_eq!(meta.version(), Some("2.1.0"));
+
+        let err = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .version("")
+            .usage(PluginUsage::Logic)
+            .build()
+            .unwrap_err();
+
+        assert!(matches!(err, MetadataBuildError::EmptyVersion));
+    }
+
+    #[test]
+    fn test_builder_deprecated() {
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Actuator)
+            .deprecated("use dimmer instead")
+            .add_state("level", None, Type::Range(0, 100))
+            .add_action("setValue", None, Type::Range(0, 100))
+            .add_action("setLevel", None, Type::Range(0, 100))
+            .set_member_deprecated("setValue", "use setLevel instead")
+            .add_config("address", None, ConfigType::Integer)
+            .set_config_deprecated("address", "use channel instead")
+            .build()
+            .unwrap();
+
+        assert_eq!(meta.deprecated(), Some("use dimmer instead"));
+        assert_eq!(
+            meta.members()["setValue"].deprecated(),
+            Some("use setLevel instead")
+        );
+        assert_eq!(meta.members()["setLevel"].deprecated(), None);
+        assert_eq!(
+            meta.config()["address"].deprecated(),
+            Some("use channel instead")
+        );
+
+        let err = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Actuator)
+            .set_member_deprecated("setValue", "use setLevel instead")
+            .build()
+            .unwrap_err();
+
+        assert!(matches!(err, MetadataBuildError::UnknownMember(name) if name == "setValue"));
+    }
+
+    #[test]
+    fn test_builder_extra_config() {
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .build()
+            .unwrap();
+
+        assert!(!meta.accepts_extra_config());
+
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Logic)
+            .accept_extra_config()
+            .build()
+            .unwrap();
+
+        assert!(meta.accepts_extra_config());
     }
 }
 
@@ -310,6 +963,12 @@ pub struct Member {
     description: Option<String>,
     member_type: MemberType,
     value_type: Type,
+    actions: Vec<String>,
+    result_type: Option<Type>,
+    default_arg: Option<Value>,
+    unit: Option<String>,
+    rate_limit: Option<RateLimit>,
+    deprecated: Option<String>,
 }
 
 impl Member {
@@ -322,6 +981,12 @@ impl Member {
             description,
             member_type,
             value_type,
+            actions: Vec::new(),
+            result_type: None,
+            default_arg: None,
+            unit: None,
+            rate_limit: None,
+            deprecated: None,
         }
     }
 
@@ -336,6 +1001,109 @@ impl Member {
     pub fn value_type(&self) -> &Type {
         &self.value_type
     }
+
+    // for states: actions that drive this state
+    pub fn actions(&self) -> &[String] {
+        &self.actions
+    }
+
+    // for actions: type of the value returned to the caller
+    pub fn result_type(&self) -> Option<&Type> {
+        self.result_type.as_ref()
+    }
+
+    // for actions: value used when the action is executed with Value::Null
+    pub fn default_arg(&self) -> Option<&Value> {
+        self.default_arg.as_ref()
+    }
+
+    // for states: display unit, eg: "°C"
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_deref()
+    }
+
+    // for actions: executions above it are dropped, clients may throttle themselves
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limit
+    }
+
+    // Some(message) if the member should no longer be used, the UI grays it out
+    pub fn deprecated(&self) -> Option<&str> {
+        self.deprecated.as_deref()
+    }
+}
+
+// At most count executions per period, with bursts up to count (token bucket)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RateLimit {
+    count: u32,
+    period: Duration,
+}
+
+impl RateLimit {
+    pub fn new(count: u32, period: Duration) -> Self {
+        RateLimit { count, period }
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+}
+
+// eg: "10/s", "3/10s", "100/m"
+impl str::FromStr for RateLimit {
+    type Err = RateLimitParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = || RateLimitParseError {
+            input: String::from(input),
+        };
+
+        let (count, period) = input.split_once('/').ok_or_else(error)?;
+        let count: u32 = count.trim().parse().map_err(|_| error())?;
+
+        // "s" is short for "1s"
+        let period = period.trim();
+        let period = if period.starts_with(|c: char| c.is_ascii_digit()) {
+            parse_duration(period)
+        } else {
+            parse_duration(&format!("1{period}"))
+        }
+        .map_err(|_| error())?;
+
+        if count == 0 || period.is_zero() {
+            return Err(error());
+        }
+
+        Ok(RateLimit { count, period })
+    }
+}
+
+impl fmt::Display for RateLimit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}ms", self.count, self.period.as_millis())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitParseError {
+    input: String,
+}
+
+impl std::error::Error for RateLimitParseError {}
+
+impl fmt::Display for RateLimitParseError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Invalid rate limit '{}', expected 'count/period', eg: '10/s' or '3/10s'",
+            self.input
+        )
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -344,12 +1112,20 @@ pub enum ConfigType {
     Bool,
     Integer,
     Float,
+    Duration, // provided as string: "500ms", "2s", "1m"
 }
 
 #[derive(Debug, Clone)]
 pub struct ConfigItem {
     description: Option<String>,
     value_type: ConfigType,
+    pattern: Option<String>,
+    range: Option<(i64, i64)>, // inclusive
+    clamp: bool,
+    unit: Option<String>,
+    nullable: bool,
+    secret: bool,
+    deprecated: Option<String>,
 }
 
 impl ConfigItem {
@@ -357,6 +1133,13 @@ impl ConfigItem {
         ConfigItem {
             description,
             value_type,
+            pattern: None,
+            range: None,
+            clamp: false,
+            unit: None,
+            nullable: false,
+            secret: false,
+            deprecated: None,
         }
     }
 
@@ -367,4 +1150,593 @@ impl ConfigItem {
     pub fn value_type(&self) -> ConfigType {
         self.value_type
     }
+
+    // for string configs: regex the value must match
+    pub fn pattern(&self) -> Option<&str> {
+        self.pattern.as_deref()
+    }
+
+    // for integer configs: inclusive bounds
+    pub fn range(&self) -> Option<(i64, i64)> {
+        self.range
+    }
+
+    // out of range values are clamped (with a warning) instead of rejected
+    pub fn clamp(&self) -> bool {
+        self.clamp
+    }
+
+    // display unit, eg: "ms"
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_deref()
+    }
+
+    // accepts ConfigValue::Null, eg: "auto"
+    pub fn nullable(&self) -> bool {
+        self.nullable
+    }
+
+    // credential (password, API key): the UI renders a password field, the runtime never prints the value
+    pub fn secret(&self) -> bool {
+        self.secret
+    }
+
+    // Some(message) if the config should no longer be set
+    pub fn deprecated(&self) -> Option<&str> {
+        self.deprecated.as_deref()
+    }
 }
+
+#[derive(Debug, Default)]
+pub struct PluginMetadataBuilder {
+    name: Option<String>,
+    id: Option<String>,
+    usage: Option<PluginUsage>,
+    version: Option<String>,
+    description: Option<String>,
+    category: Option<PluginCategory>,
+    tags: Vec<String>,
+    required_capabilities: Vec<Capability>,
+    deprecated: Option<String>,
+    members: Vec<(String, Member)>,
+    config: Vec<(String, ConfigItem)>,
+    state_actions: Vec<(String, Vec<String>)>,
+    config_patterns: Vec<(String, String)>,
+    config_ranges: Vec<(String, (i64, i64), bool)>, // name, range, clamp
+    state_units: Vec<(String, String)>,
+    config_units: Vec<(String, String)>,
+    config_nullables: Vec<String>,
+    config_secrets: Vec<String>,
+    extra_config: bool,
+    complex_types: Vec<(String, Vec<(String, Type)>)>,
+    action_defaults: Vec<(String, String)>,
+    action_rate_limits: Vec<(String, RateLimit)>,
+    member_deprecations: Vec<(String, String)>,
+    config_deprecations: Vec<(String, String)>,
+}
+
+impl PluginMetadataBuilder {
+    pub fn new() -> Self {
+        PluginMetadataBuilder::default()
+    }
+
+    pub fn name(&mut self, name: &str) -> &mut Self {
+        self.name = Some(String::from(name));
+        self
+    }
+
+    pub fn id(&mut self, id: &str) -> &mut Self {
+        self.id = Some(String::from(id));
+        self
+    }
+
+    pub fn version(&mut self, version: &str) -> &mut Self {
+        self.version = Some(String::from(version));
+        self
+    }
+
+    pub fn description(&mut self, description: Option<&str>) -> &mut Self {
+        self.description = description.map(String::from);
+        self
+    }
+
+    pub fn usage(&mut self, usage: PluginUsage) -> &mut Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    pub fn category(&mut self, category: Option<PluginCategory>) -> &mut Self {
+        self.category = category;
+        self
+    }
+
+    pub fn tags(&mut self, tags: &[&str]) -> &mut Self {
+        self.tags = tags.iter().map(|tag| String::from(*tag)).collect();
+        self
+    }
+
+    pub fn required_capabilities(&mut self, capabilities: &[Capability]) -> &mut Self {
+        self.required_capabilities = capabilities.to_vec();
+        self
+    }
+
+    pub fn deprecated(&mut self, message: &str) -> &mut Self {
+        self.deprecated = Some(String::from(message));
+        self
+    }
+
+    pub fn add_state(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        value_type: Type,
+    ) -> &mut Self {
+        let member = Member::new(description.map(String::from), MemberType::State, value_type);
+        self.members.push((String::from(name), member));
+        self
+    }
+
+    pub fn add_event(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        value_type: Type,
+    ) -> &mut Self {
+        let member = Member::new(description.map(String::from), MemberType::Event, value_type);
+        self.members.push((String::from(name), member));
+        self
+    }
+
+    pub fn add_action(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        value_type: Type,
+    ) -> &mut Self {
+        let member = Member::new(
+            description.map(String::from),
+            MemberType::Action,
+            value_type,
+        );
+        self.members.push((String::from(name), member));
+        self
+    }
+
+    pub fn add_action_with_result(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        value_type: Type,
+        result_type: Type,
+    ) -> &mut Self {
+        let mut member = Member::new(
+            description.map(String::from),
+            MemberType::Action,
+            value_type,
+        );
+        member.result_type = Some(result_type);
+        self.members.push((String::from(name), member));
+        self
+    }
+
+    pub fn add_config(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        value_type: ConfigType,
+    ) -> &mut Self {
+        let config_item = ConfigItem::new(description.map(String::from), value_type);
+        self.config.push((String::from(name), config_item));
+        self
+    }
+
+    // registering the same schema again is allowed (eg: used by several members)
+    pub fn register_complex_type(&mut self, name: &str, fields: Vec<(String, Type)>) -> &mut Self {
+        self.complex_types.push((String::from(name), fields));
+        self
+    }
+
+    pub fn accept_extra_config(&mut self) -> &mut Self {
+        self.extra_config = true;
+        self
+    }
+
+    pub fn link_state_actions(&mut self, state: &str, actions: &[&str]) -> &mut Self {
+        self.state_actions.push((
+            String::from(state),
+            actions.iter().map(|action| String::from(*action)).collect(),
+        ));
+        self
+    }
+
+    // parsed against the action type at build, see runtime::Value::parse
+    pub fn set_action_default_arg(&mut self, action: &str, value: &str) -> &mut Self {
+        self.action_defaults
+            .push((String::from(action), String::from(value)));
+        self
+    }
+
+    pub fn set_action_rate_limit(&mut self, action: &str, rate_limit: RateLimit) -> &mut Self {
+        self.action_rate_limits
+            .push((String::from(action), rate_limit));
+        self
+    }
+
+    // any member type: state, event or action
+    pub fn set_member_deprecated(&mut self, member: &str, message: &str) -> &mut Self {
+        self.member_deprecations
+            .push((String::from(member), String::from(message)));
+        self
+    }
+
+    pub fn set_config_deprecated(&mut self, config: &str, message: &str) -> &mut Self {
+        self.config_deprecations
+            .push((String::from(config), String::from(message)));
+        self
+    }
+
+    pub fn set_config_pattern(&mut self, config: &str, pattern: &str) -> &mut Self {
+        self.config_patterns
+            .push((String::from(config), String::from(pattern)));
+        self
+    }
+
+    pub fn set_state_unit(&mut self, state: &str, unit: &str) -> &mut Self {
+        self.state_units
+            .push((String::from(state), String::from(unit)));
+        self
+    }
+
+    pub fn set_config_unit(&mut self, config: &str, unit: &str) -> &mut Self {
+        self.config_units
+            .push((String::from(config), String::from(unit)));
+        self
+    }
+
+    pub fn set_config_nullable(&mut self, config: &str) -> &mut Self {
+        self.config_nullables.push(String::from(config));
+        self
+    }
+
+    pub fn set_config_secret(&mut self, config: &str) -> &mut Self {
+        self.config_secrets.push(String::from(config));
+        self
+    }
+
+    pub fn set_config_range(&mut self, config: &str, min: i64, max: i64, clamp: bool) -> &mut Self {
+        self.config_ranges
+            .push((String::from(config), (min, max), clamp));
+        self
+    }
+
+    pub fn build(&self) -> Result<PluginMetadata, MetadataBuildError> {
+        let name = self.name.clone().ok_or(MetadataBuildError::NameNotSet)?;
+        let usage = self.usage.ok_or(MetadataBuildError::UsageNotSet)?;
+
+        let mut members = HashMap::new();
+        for (name, member) in self.members.iter() {
+            if members.insert(name.clone(), member.clone()).is_some() {
+                return Err(MetadataBuildError::DuplicateMember(name.clone()));
+            }
+        }
+
+        for (state, actions) in self.state_actions.iter() {
+            for action in actions.iter() {
+                match members.get(action) {
+                    Some(member) if member.member_type() == MemberType::Action => {}
+                    _ => {
+                        return Err(MetadataBuildError::UnknownAction(
+                            state.clone(),
+                            action.clone(),
+                        ));
+                    }
+                }
+            }
+
+            match members.get_mut(state) {
+                Some(member) if member.member_type() == MemberType::State => {
+                    member.actions = actions.clone();
+                }
+                _ => {
+                    return Err(MetadataBuildError::UnknownState(state.clone()));
+                }
+            }
+        }
+
+        for (state, unit) in self.state_units.iter() {
+            match members.get_mut(state) {
+                Some(member) if member.member_type() == MemberType::State => {
+                    member.unit = Some(unit.clone());
+                }
+                _ => {
+                    return Err(MetadataBuildError::UnknownState(state.clone()));
+                }
+            }
+        }
+
+        for (action, value) in self.action_defaults.iter() {
+            let member = match members.get_mut(action) {
+                Some(member) if member.member_type() == MemberType::Action => member,
+                _ => {
+                    return Err(MetadataBuildError::InvalidDefaultArg(
+                        action.clone(),
+                        String::from("unknown action"),
+                    ));
+                }
+            };
+
+            // Null is the marker for 'no argument'
+            if let Type::Nullable(_) = member.value_type() {
+                return Err(MetadataBuildError::InvalidDefaultArg(
+                    action.clone(),
+                    String::from("not supported on nullable action"),
+                ));
+            }
+
+            let default_arg = Value::parse(value, member.value_type()).map_err(|err| {
+                MetadataBuildError::InvalidDefaultArg(action.clone(), err.to_string())
+            })?;
+            member.default_arg = Some(default_arg);
+        }
+
+        for (action, rate_limit) in self.action_rate_limits.iter() {
+            match members.get_mut(action) {
+                Some(member) if member.member_type() == MemberType::Action => {
+                    member.rate_limit = Some(*rate_limit);
+                }
+                _ => return Err(MetadataBuildError::InvalidRateLimit(action.clone())),
+            }
+        }
+
+        for (name, message) in self.member_deprecations.iter() {
+            let member = members
+                .get_mut(name)
+                .ok_or_else(|| MetadataBuildError::UnknownMember(name.clone()))?;
+
+            member.deprecated = Some(message.clone());
+        }
+
+        let mut config = HashMap::new();
+        for (name, config_item) in self.config.iter() {
+            if config.insert(name.clone(), config_item.clone()).is_some() {
+                return Err(MetadataBuildError::DuplicateConfig(name.clone()));
+            }
+        }
+
+        for (name, pattern) in self.config_patterns.iter() {
+            let config_item = config
+                .get_mut(name)
+                .ok_or_else(|| MetadataBuildError::UnknownConfig(name.clone()))?;
+
+            if config_item.value_type() != ConfigType::String {
+                return Err(MetadataBuildError::InvalidPattern(
+                    name.clone(),
+                    String::from("pattern can only be set on string config"),
+                ));
+            }
+
+            if let Err(err) = Regex::new(pattern) {
+                return Err(MetadataBuildError::InvalidPattern(
+                    name.clone(),
+                    err.to_string(),
+                ));
+            }
+
+            config_item.pattern = Some(pattern.clone());
+        }
+
+        for (name, unit) in self.config_units.iter() {
+            let config_item = config
+                .get_mut(name)
+                .ok_or_else(|| MetadataBuildError::UnknownConfig(name.clone()))?;
+
+            config_item.unit = Some(unit.clone());
+        }
+
+        for name in self.config_nullables.iter() {
+            let config_item = config
+                .get_mut(name)
+                .ok_or_else(|| MetadataBuildError::UnknownConfig(name.clone()))?;
+
+            config_item.nullable = true;
+        }
+
+        for name in self.config_secrets.iter() {
+            let config_item = config
+                .get_mut(name)
+                .ok_or_else(|| MetadataBuildError::UnknownConfig(name.clone()))?;
+
+            config_item.secret = true;
+        }
+
+        for (name, message) in self.config_deprecations.iter() {
+            let config_item = config
+                .get_mut(name)
+                .ok_or_else(|| MetadataBuildError::UnknownConfig(name.clone()))?;
+
+            config_item.deprecated = Some(message.clone());
+        }
+
+        for (name, (min, max), clamp) in self.config_ranges.iter() {
+            let config_item = config
+                .get_mut(name)
+                .ok_or_else(|| MetadataBuildError::UnknownConfig(name.clone()))?;
+
+            if config_item.value_type() != ConfigType::Integer {
+                return Err(MetadataBuildError::InvalidRange(
+                    name.clone(),
+                    String::from("range can only be set on integer config"),
+                ));
+            }
+
+            if min > max {
+                return Err(MetadataBuildError::InvalidRange(
+                    name.clone(),
+                    format!("min ({}) > max ({})", min, max),
+                ));
+            }
+
+            config_item.range = Some((*min, *max));
+            config_item.clamp = *clamp;
+        }
+
+        let mut complex_types = HashMap::new();
+        for (name, fields) in self.complex_types.iter() {
+            match complex_types.insert(name.clone(), fields.clone()) {
+                Some(previous) if previous != *fields => {
+                    return Err(MetadataBuildError::ConflictingComplexType(name.clone()));
+                }
+                _ => {}
+            }
+        }
+
+        for (name, fields) in complex_types.iter() {
+            for (_, field_type) in fields.iter() {
+                check_complex_types(name, field_type, &complex_types)?;
+            }
+        }
+
+        for (name, member) in members.iter() {
+            let types = [Some(member.value_type()), member.result_type()];
+            for value_type in types.into_iter().flatten() {
+                check_complex_types(name, value_type, &complex_types)?;
+            }
+        }
+
+        if self.id.as_deref() == Some("") {
+            return Err(MetadataBuildError::EmptyId);
+        }
+
+        if self.version.as_deref() == Some("") {
+            return Err(MetadataBuildError::EmptyVersion);
+        }
+
+        Ok(PluginMetadata::new(
+            name,
+            self.id.clone(),
+            usage,
+            self.version.clone(),
+            self.description.clone(),
+            self.category,
+            self.tags.clone(),
+            self.required_capabilities.clone(),
+            self.deprecated.clone(),
+            members,
+            config,
+            self.extra_config,
+            complex_types,
+        ))
+    }
+}
+
+fn check_complex_types(
+    member: &str,
+    value_type: &Type,
+    complex_types: &HashMap<String, Vec<(String, Type)>>,
+) -> Result<(), MetadataBuildError> {
+    match value_type {
+        // Note: schemas are checked on their own, they may be recursive
+        Type::Complex(name) if !complex_types.contains_key(name) => {
+            return Err(MetadataBuildError::UnknownComplexType(
+                String::from(member),
+                name.clone(),
+            ));
+        }
+        Type::List(item_type) | Type::Nullable(item_type) => {
+            check_complex_types(member, item_type, complex_types)?
+        }
+        Type::Object(fields) => {
+            for (_, field_type) in fields.iter() {
+                check_complex_types(member, field_type, complex_types)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub enum MetadataBuildError {
+    NameNotSet,
+    UsageNotSet,
+    EmptyId,
+    EmptyVersion,
+    DuplicateMember(String),
+    DuplicateConfig(String),
+    UnknownMember(String),
+    UnknownState(String),
+    UnknownAction(String, String),
+    UnknownConfig(String),
+    InvalidPattern(String, String),
+    InvalidRange(String, String),
+    ConflictingComplexType(String),
+    UnknownComplexType(String, String),
+    InvalidDefaultArg(String, String),
+    InvalidRateLimit(String), // not an action
+}
+
+impl fmt::Display for MetadataBuildError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MetadataBuildError::NameNotSet => write!(fmt, "Plugin name not set"),
+            MetadataBuildError::UsageNotSet => write!(fmt, "Plugin usage not set"),
+            MetadataBuildError::EmptyId => write!(fmt, "Plugin id is empty"),
+            MetadataBuildError::EmptyVersion => write!(fmt, "Plugin version is empty"),
+            MetadataBuildError::DuplicateMember(name) => {
+                write!(fmt, "Duplicate member name: '{}'", name)
+            }
+            MetadataBuildError::DuplicateConfig(name) => {
+                write!(fmt, "Duplicate config name: '{}'", name)
+            }
+            MetadataBuildError::UnknownMember(name) => {
+                write!(fmt, "Unknown member: '{}'", name)
+            }
+            MetadataBuildError::UnknownState(name) => {
+                write!(fmt, "Unknown state: '{}'", name)
+            }
+            MetadataBuildError::UnknownAction(state, action) => {
+                write!(
+                    fmt,
+                    "Unknown action '{}' linked to state '{}'",
+                    action, state
+                )
+            }
+            MetadataBuildError::UnknownConfig(name) => {
+                write!(fmt, "Unknown config: '{}'", name)
+            }
+            MetadataBuildError::InvalidPattern(name, reason) => {
+                write!(fmt, "Invalid pattern on config '{}': {}", name, reason)
+            }
+            MetadataBuildError::InvalidRange(name, reason) => {
+                write!(fmt, "Invalid range on config '{}': {}", name, reason)
+            }
+            MetadataBuildError::InvalidDefaultArg(action, reason) => {
+                write!(
+                    fmt,
+                    "Invalid default argument on action '{}': {}",
+                    action, reason
+                )
+            }
+            MetadataBuildError::InvalidRateLimit(action) => {
+                write!(fmt, "Rate limit on unknown action: '{}'", action)
+            }
+            MetadataBuildError::ConflictingComplexType(name) => {
+                write!(
+                    fmt,
+                    "Complex type '{}' registered with different schemas",
+                    name
+                )
+            }
+            MetadataBuildError::UnknownComplexType(member, name) => {
+                write!(
+                    fmt,
+                    "Unknown complex type '{}' used by member '{}'",
+                    name, member
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for MetadataBuildError {}
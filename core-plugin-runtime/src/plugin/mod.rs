@@ -1,7 +1,15 @@
+mod cancellation;
 mod definition;
+pub mod encoding;
 pub mod metadata;
 mod module;
 pub mod runtime;
+mod serialization;
+mod stream;
+pub mod worker;
 
+pub use cancellation::*;
 pub use definition::*;
 pub use module::*;
+pub use serialization::ConfigParseError;
+pub use stream::{StateStream, StateStreamRecv, StreamBackpressure};
@@ -1,17 +1,34 @@
+use std::fmt;
+
 use crate::runtime;
 
 pub static CORE_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub static RUSTC_VERSION: &str = env!("RUSTC_VERSION");
 pub static MYLIFE_RUNTIME_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// Not #[repr(C)]: the host only reads declarations of modules built with the same versions (see check_versions in the loader),
+// so any change of this struct must come with a version bump of this crate
 pub struct ModuleDeclaration {
     pub rustc_version: &'static str,
     pub core_version: &'static str,
     pub mylife_runtime_version: &'static str,
     pub module_version: &'static str,
+    pub description: Option<&'static str>,
+    pub author: Option<&'static str>,
+    pub homepage: Option<&'static str>,
     pub register: fn(registry: &mut dyn PluginRegistry),
 }
 
+// Cargo sets package metadata env vars to empty string when not provided
+#[doc(hidden)]
+pub const fn non_empty(value: &'static str) -> Option<&'static str> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
 #[macro_export]
 macro_rules! export_module {
     ($register:expr) => {
@@ -23,11 +40,104 @@ macro_rules! export_module {
                 core_version: $crate::CORE_VERSION,
                 mylife_runtime_version: $crate::MYLIFE_RUNTIME_VERSION,
                 module_version: env!("CARGO_PKG_VERSION"),
+                description: $crate::non_empty(env!("CARGO_PKG_DESCRIPTION")),
+                author: $crate::non_empty(env!("CARGO_PKG_AUTHORS")),
+                homepage: $crate::non_empty(env!("CARGO_PKG_HOMEPAGE")),
                 register: $register,
             };
     };
 }
 
+// Optional, lets the host read the plugins metadata without calling register (see Module::inspect)
+pub struct ModuleMetadataDeclaration {
+    pub plugins: fn() -> Vec<String>, // PluginMetadata::to_json of each plugin
+}
+
+// export_module_metadata!(PluginA, PluginB): same plugins as registered by export_module.
+// Plugins accept attributes, eg: #[cfg(feature = "gpio")] PluginB
+#[macro_export]
+macro_rules! export_module_metadata {
+    ($($(#[$attr:meta])* $plugin:ty),* $(,)?) => {
+        #[doc(hidden)]
+        #[no_mangle]
+        pub static mylife_home_core_module_metadata: $crate::ModuleMetadataDeclaration =
+            $crate::ModuleMetadataDeclaration {
+                plugins: || {
+                    #[allow(unused_mut)] // all plugins compiled out
+                    let mut plugins = Vec::new();
+                    $(
+                        $(#[$attr])*
+                        plugins.push(
+                            <$plugin as $crate::MylifePlugin>::runtime()
+                                .metadata()
+                                .to_json()
+                                .to_string()
+                        );
+                    )*
+                    plugins
+                },
+            };
+    };
+}
+
+// mylife_register_plugins!(PluginA, PluginB): export_module and export_module_metadata for these plugins,
+// with a register function which registers each of them.
+// Plugins accept attributes, so a plugin compiled out on some targets is neither registered nor listed:
+//   mylife_register_plugins!(PluginA, #[cfg(target_arch = "arm")] GpioPlugin)
+// The module is still exported when all its plugins are compiled out, with no plugin
+#[macro_export]
+macro_rules! mylife_register_plugins {
+    ($($(#[$attr:meta])* $plugin:ty),* $(,)?) => {
+        $crate::export_module!(|_registry: &mut dyn $crate::PluginRegistry| {
+            $(
+                $(#[$attr])*
+                {
+                    // errors are reported by the host
+                    let _ = _registry.register_plugin(<$plugin as $crate::MylifePlugin>::runtime());
+                }
+            )*
+        });
+
+        $crate::export_module_metadata!($($(#[$attr])* $plugin),*);
+    };
+}
+
+// Called across the module boundary: like ModuleDeclaration, any signature change needs a version bump of this crate
 pub trait PluginRegistry {
-    fn register_plugin(&mut self, plugin: Box<dyn runtime::MylifePluginRuntime>);
+    // rejected plugins are also reported by the host, so the module can ignore the result and go on registering the others
+    fn register_plugin(
+        &mut self,
+        plugin: Box<dyn runtime::MylifePluginRuntime>,
+    ) -> Result<(), RegistrationError>;
+}
+
+#[derive(Debug, Clone)]
+pub struct RegistrationError {
+    plugin: String,
+    reason: String,
 }
+
+impl RegistrationError {
+    pub fn new(plugin: &str, reason: &str) -> Self {
+        RegistrationError {
+            plugin: String::from(plugin),
+            reason: String::from(reason),
+        }
+    }
+
+    pub fn plugin(&self) -> &str {
+        &self.plugin
+    }
+}
+
+impl fmt::Display for RegistrationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Plugin '{}' registration failed: {}",
+            self.plugin, self.reason
+        )
+    }
+}
+
+impl std::error::Error for RegistrationError {}
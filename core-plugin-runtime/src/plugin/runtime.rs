@@ -1,27 +1,399 @@
-use std::{collections::HashMap, fmt};
+use std::{
+    any::{Any, TypeId},
+    collections::{BTreeMap, HashMap},
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
-use crate::metadata;
+use regex::Regex;
 
-pub trait MylifePluginRuntime {
+use super::stream;
+use crate::{metadata, MylifePlugin, StateStream, StreamBackpressure};
+
+// Shared between threads, components are created on the thread that uses them
+pub trait MylifePluginRuntime: Send + Sync {
     fn metadata(&self) -> &metadata::PluginMetadata;
     fn create(&self, id: &str) -> Box<dyn MylifeComponent>;
+    // same as create for each id, the setup common to all components is done once for the batch
+    fn create_components(&self, ids: &[&str]) -> Vec<Box<dyn MylifeComponent>> {
+        ids.iter().map(|id| self.create(id)).collect()
+    }
+
+    // the concrete MylifePlugin type the components are created from, if any
+    fn plugin_type_id(&self) -> Option<TypeId> {
+        None
+    }
 }
 
+pub type StateHandler = Box<dyn Fn(/*name:*/ &str, /*value:*/ Value)>;
+pub type StateChanges = Vec<(/*name:*/ String, /*value:*/ Value)>;
+pub type StatesBatchHandler = Box<dyn Fn(StateChanges)>;
+pub type FailHandler = Box<dyn Fn(&FailContext, &dyn std::error::Error)>;
+pub type ActionDroppedHandler = Box<dyn Fn(/*name:*/ &str)>;
+
+// Not Send: use worker::ComponentWorker to run a component off the main thread
 pub trait MylifeComponent {
     fn id(&self) -> &str;
-    fn set_on_state(&mut self, handler: Box<dyn Fn(/*name:*/ &str, /*value:*/ Value)>);
+    // the plugin instance, to downcast with TypedComponent
+    fn plugin_any(&self) -> &dyn Any;
+    fn plugin_any_mut(&mut self) -> &mut dyn Any;
+    // replaces all previous state handlers
+    fn set_on_state(&mut self, handler: StateHandler);
+    // additional state handler, called after the previous ones
+    fn add_on_state(&mut self, handler: StateHandler);
+    // events are transient: no getter, not part of describe. Emitted events are dropped while no handler is set
+    fn set_on_event(&mut self, handler: StateHandler);
+    // receives the state changes of a with_batch call at once, instead of the set_on_state handler.
+    // Handlers added with add_on_state are still called for each change
+    fn set_on_states_batch(&mut self, handler: StatesBatchHandler);
+    // state changes made during batch are coalesced (last value per state, first change order)
+    // and reported once it returns. Nested calls join the outer batch
+    fn with_batch(&mut self, batch: &mut dyn FnMut(&mut dyn MylifeComponent));
+    // state listeners are bound to the plugin with the first state handler,
+    // bind them again if the plugin replaced State fields (a new State is not bound)
+    fn rebind_states(&mut self);
     fn get_state(&self, name: &str) -> Result<Value, Box<dyn std::error::Error>>;
+    // last changes of a state declared with history = N, oldest first (empty otherwise)
+    fn state_history(
+        &self,
+        name: &str,
+    ) -> Result<Vec<(SystemTime, Value)>, Box<dyn std::error::Error>>;
+    // hold back state emissions until the value is stable for interval (zero disables),
+    // the host must call process_debounced_states periodically to publish them
+    fn set_state_debounce(
+        &mut self,
+        name: &str,
+        interval: Duration,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+    fn process_debounced_states(&mut self) {
+        self.process_debounced_states_at(Instant::now());
+    }
+    // same with an explicit clock, publishes the values held back until now
+    fn process_debounced_states_at(&mut self, now: Instant);
+    // states declared with mode = "polled" only flag their changes, the host drains them on its own cadence:
+    // each changed state is emitted once, with its current value
+    fn poll_states(&mut self);
+    // diagnostic snapshot of the component
+    fn describe(&self) -> ComponentSnapshot;
+    // cheap identification, used by the Debug implementation
+    fn debug_info(&self) -> ComponentDebug;
+    // member names, sorted: lets a dispatcher validate a name before execute_action or get_state
+    fn list_actions(&self) -> Vec<&str>;
+    fn list_states(&self) -> Vec<&str>;
+    // called once init succeeded
+    fn set_on_ready(&mut self, handler: Box<dyn Fn()>);
+    // called on each plugin failure (the error is still returned to the caller), with what triggered it
+    fn set_on_fail(&mut self, handler: FailHandler);
+    // called when an action is dropped by its rate limit (the error is still returned to the caller)
+    fn set_on_action_dropped(&mut self, handler: ActionDroppedHandler);
+    // per component log threshold, forwarded to the plugin (default: Trace)
+    fn set_log_level(&mut self, level: log::LevelFilter);
+    fn log_level(&self) -> log::LevelFilter;
+    // run the plugin without its device (tests, demos), only before init
+    fn set_simulated(&mut self, simulated: bool) -> Result<(), Box<dyn std::error::Error>>;
+    fn is_simulated(&self) -> bool;
+    // actions are only accepted once Ready
+    fn state(&self) -> ComponentState;
+    // from the component state, and from the plugin health hook once Ready
+    fn health(&self) -> Health;
     fn configure(&mut self, config: &Config) -> Result<(), Box<dyn std::error::Error>>;
+    // same as configure, but fails if config contains keys unknown to the plugin
+    fn configure_strict(&mut self, config: &Config) -> Result<(), Box<dyn std::error::Error>>;
+    // partial update of the last applied config (Configured, Ready or Failed), eg: a UI editing one field.
+    // Only the keys whose value differs are set, the others are no-ops. Nothing is applied if a key is invalid.
+    // Returns the changed keys, sorted. The plugin is not re-initialized, derived states are recomputed once Ready
+    fn apply_config_diff(
+        &mut self,
+        changes: &Config,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>>;
+    // dry-run: checks config without applying it, reporting every invalid key
+    fn validate_config(&self, config: &Config) -> Result<(), Vec<Box<dyn std::error::Error>>>;
     fn init(&mut self) -> Result<(), Box<dyn std::error::Error>>;
+    // retry init on a Failed component, keeping its config and state handlers.
+    // States set by init are emitted as usual, on_ready is called once init succeeds
+    fn reinit(&mut self) -> Result<(), Box<dyn std::error::Error>>;
+    // cancels the plugin background tasks and waits for them, must be called before the module is unloaded.
+    // The component cannot be used afterward
+    fn terminate(&mut self) -> Result<(), Box<dyn std::error::Error>>;
     fn execute_action(
         &mut self,
         name: &str,
         action: Value,
     ) -> Result<(), Box<dyn std::error::Error>>;
+    // result is None if the action does not return a value
+    fn execute_action_with_result(
+        &mut self,
+        name: &str,
+        action: Value,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error>>;
+
+    // bridges the state handlers to async hosts, added with add_on_state (previous handlers are kept)
+    fn state_stream(&mut self, backpressure: StreamBackpressure) -> StateStream {
+        let (sender, stream) = stream::state_stream_channel(backpressure);
+        self.add_on_state(Box::new(move |name, value| sender.send(name, value)));
+        stream
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailContext {
+    Action(String), // action name
+    Config(String), // config name
+    Init,
+    Custom(String), // reported by the plugin itself, see FailReporter
+}
+
+// Given to the plugin before init (see MylifePluginHooks::set_fail_reporter), reports to the component fail handler
+// the failures which are not the result of an action, config or init (eg: device lost, seen by a timer).
+// Not Send: report from the component thread
+#[derive(Clone)]
+pub struct FailReporter {
+    report: Rc<FailHandler>,
+}
+
+impl FailReporter {
+    pub fn new<F>(report: F) -> Self
+    where
+        F: Fn(&FailContext, &dyn std::error::Error) + 'static,
+    {
+        FailReporter {
+            report: Rc::new(Box::new(report)),
+        }
+    }
+
+    // context: what failed, eg: "device"
+    pub fn report(&self, context: &str, err: &dyn std::error::Error) {
+        (self.report)(&FailContext::Custom(String::from(context)), err);
+    }
+}
+
+// Failed: the component cannot work (init failure, terminated)
+// Degraded: it works partially or is not initialized yet, eg: device unreachable, retrying
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Health {
+    Ok,
+    Degraded(String), // reason
+    Failed(String),   // reason
+}
+
+// New -> Configured -> Ready, or Failed if init fails (configure can then be retried)
+// Terminated is final, from any other state
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ComponentState {
+    New,
+    Configured,
+    Ready,
+    Failed,
+    Terminated,
+}
+
+impl fmt::Debug for dyn MylifeComponent {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.debug_info().fmt(fmt)
+    }
+}
+
+// Component with direct access to its plugin, for hosts which link the plugin crate (in-process use, tests).
+// Still usable as a MylifeComponent through deref.
+// Note: type ids are not reliable across dynamic libraries built separately
+pub struct TypedComponent<PluginType: MylifePlugin + 'static> {
+    component: Box<dyn MylifeComponent>,
+    plugin_type: PhantomData<PluginType>,
+}
+
+impl<PluginType: MylifePlugin + 'static> TypedComponent<PluginType> {
+    // gives the component back if its plugin is not of type PluginType
+    pub fn new(component: Box<dyn MylifeComponent>) -> Result<Self, Box<dyn MylifeComponent>> {
+        if !component.plugin_any().is::<PluginType>() {
+            return Err(component);
+        }
+
+        Ok(TypedComponent {
+            component,
+            plugin_type: PhantomData,
+        })
+    }
+
+    pub fn plugin(&self) -> &PluginType {
+        self.component
+            .plugin_any()
+            .downcast_ref()
+            .expect("Plugin type checked on creation")
+    }
+
+    // Note: state changes made through the plugin are reported as usual, through its State fields
+    pub fn plugin_mut(&mut self) -> &mut PluginType {
+        self.component
+            .plugin_any_mut()
+            .downcast_mut()
+            .expect("Plugin type checked on creation")
+    }
+
+    pub fn into_inner(self) -> Box<dyn MylifeComponent> {
+        self.component
+    }
+}
+
+impl<PluginType: MylifePlugin + 'static> Deref for TypedComponent<PluginType> {
+    type Target = dyn MylifeComponent;
+
+    fn deref(&self) -> &Self::Target {
+        self.component.as_ref()
+    }
+}
+
+impl<PluginType: MylifePlugin + 'static> DerefMut for TypedComponent<PluginType> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.component.as_mut()
+    }
+}
+
+impl<PluginType: MylifePlugin + 'static> fmt::Debug for TypedComponent<PluginType> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.component.fmt(fmt)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentDebug {
+    id: String,
+    plugin: String,
+    state: ComponentState,
+}
+
+impl ComponentDebug {
+    pub(crate) fn new(id: &str, plugin: &str, state: ComponentState) -> Self {
+        ComponentDebug {
+            id: String::from(id),
+            plugin: String::from(plugin),
+            state,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    // plugin name from metadata
+    pub fn plugin(&self) -> &str {
+        &self.plugin
+    }
+
+    pub fn state(&self) -> ComponentState {
+        self.state
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ComponentSnapshot {
+    id: String,
+    plugin: String,
+    config: Config,
+    states: HashMap<String, Value>,
+    state: ComponentState,
+}
+
+impl ComponentSnapshot {
+    pub(crate) fn new(
+        id: &str,
+        plugin: &str,
+        config: Config,
+        states: HashMap<String, Value>,
+        state: ComponentState,
+    ) -> Self {
+        ComponentSnapshot {
+            id: String::from(id),
+            plugin: String::from(plugin),
+            config,
+            states,
+            state,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    // plugin name from metadata
+    pub fn plugin(&self) -> &str {
+        &self.plugin
+    }
+
+    // last applied config, empty if not configured yet
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn states(&self) -> &HashMap<String, Value> {
+        &self.states
+    }
+
+    pub fn state(&self) -> ComponentState {
+        self.state
+    }
+}
+
+// Transient failure: actions declared with `retries` are executed again on this error
+#[derive(Debug, Clone)]
+pub struct DeviceUnavailableError {
+    message: String,
+}
+
+impl DeviceUnavailableError {
+    pub fn new(message: &str) -> Self {
+        DeviceUnavailableError {
+            message: String::from(message),
+        }
+    }
+}
+
+impl fmt::Display for DeviceUnavailableError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Device unavailable: {}", self.message)
+    }
+}
+
+impl std::error::Error for DeviceUnavailableError {}
+
+#[derive(Debug, Clone)]
+pub struct ActionTimeoutError {
+    name: String,
+    elapsed: Duration,
+    timeout: Duration,
+}
+
+impl ActionTimeoutError {
+    pub fn new(name: &str, elapsed: Duration, timeout: Duration) -> Self {
+        ActionTimeoutError {
+            name: String::from(name),
+            elapsed,
+            timeout,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for ActionTimeoutError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Action '{}' took {:?} (timeout: {:?})",
+            self.name, self.elapsed, self.timeout
+        )
+    }
 }
 
+impl std::error::Error for ActionTimeoutError {}
+
 pub type Config = HashMap<String, ConfigValue>;
 
+// Equality: different variants are never equal, floats are compared with f64 `==` (exact, NaN never equals itself)
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     Range(i64),
@@ -29,7 +401,105 @@ pub enum Value {
     Float(f64),
     Bool(bool),
     Enum(String),
-    Complex, // unsupported for now
+    List(Vec<Value>),
+    Null,
+    Object(BTreeMap<String, Value>),
+    Duration(Duration),
+    Timestamp(i64), // epoch milliseconds, UTC
+    Color { r: u8, g: u8, b: u8 },
+}
+
+impl Value {
+    // Literal of a primitive type, eg: "true", "42", "2.5", "500ms"
+    pub fn parse(input: &str, ty: &metadata::Type) -> Result<Value, ValueParseError> {
+        let error = || ValueParseError {
+            input: String::from(input),
+            ty: ty.clone(),
+        };
+
+        let value = match ty {
+            metadata::Type::Range(_, _) => Value::Range(input.parse().map_err(|_| error())?),
+            metadata::Type::Float => Value::Float(input.parse().map_err(|_| error())?),
+            metadata::Type::Bool => Value::Bool(input.parse().map_err(|_| error())?),
+            metadata::Type::Text | metadata::Type::Char => Value::Text(String::from(input)),
+            metadata::Type::Enum(_) => Value::Enum(String::from(input)),
+            metadata::Type::Duration => {
+                Value::Duration(parse_duration(input).map_err(|_| error())?)
+            }
+            metadata::Type::Timestamp => Value::Timestamp(input.parse().map_err(|_| error())?),
+            metadata::Type::Color => Rgb::from_hex(input).ok_or_else(error)?.into(),
+            _ => return Err(error()),
+        };
+
+        if !value.is_of_type(ty) {
+            return Err(error());
+        }
+
+        Ok(value)
+    }
+
+    // Narrowest type containing the value (unnamed Complex where the value does not carry enough information, eg: empty list or null)
+    pub fn type_of(&self) -> metadata::Type {
+        match self {
+            Value::Range(value) => metadata::Type::Range(*value, *value),
+            Value::Text(_) => metadata::Type::Text,
+            Value::Float(_) => metadata::Type::Float,
+            Value::Bool(_) => metadata::Type::Bool,
+            Value::Enum(value) => metadata::Type::Enum(vec![value.clone()]),
+            Value::List(items) => metadata::Type::List(Box::new(
+                items
+                    .first()
+                    .map_or(metadata::Type::Complex(String::new()), Value::type_of),
+            )),
+            Value::Null => {
+                metadata::Type::Nullable(Box::new(metadata::Type::Complex(String::new())))
+            }
+            Value::Object(fields) => metadata::Type::Object(
+                fields
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.type_of()))
+                    .collect(),
+            ),
+            Value::Duration(_) => metadata::Type::Duration,
+            Value::Timestamp(_) => metadata::Type::Timestamp,
+            Value::Color { .. } => metadata::Type::Color,
+        }
+    }
+
+    pub fn is_of_type(&self, ty: &metadata::Type) -> bool {
+        match (self, ty) {
+            (Value::Range(value), metadata::Type::Range(min, max)) => min <= value && value <= max,
+            (Value::Text(_), metadata::Type::Text) => true,
+            (Value::Text(value), metadata::Type::Char) => is_single_char(value),
+            (Value::Float(_), metadata::Type::Float) => true,
+            (Value::Duration(_), metadata::Type::Duration) => true,
+            (Value::Timestamp(_), metadata::Type::Timestamp) => true,
+            (Value::Color { .. }, metadata::Type::Color) => true,
+            (Value::Bool(_), metadata::Type::Bool) => true,
+            (Value::Enum(value), metadata::Type::Enum(list)) => is_enum_member(list, value),
+            (Value::List(items), metadata::Type::List(item_type)) => {
+                items.iter().all(|item| item.is_of_type(item_type))
+            }
+            (Value::Object(fields), metadata::Type::Object(field_types)) => {
+                fields.len() == field_types.len()
+                    && field_types.iter().all(|(name, field_type)| {
+                        fields
+                            .get(name)
+                            .is_some_and(|value| value.is_of_type(field_type))
+                    })
+            }
+            (Value::Null, metadata::Type::Nullable(_)) => true,
+            (value, metadata::Type::Nullable(value_type)) => value.is_of_type(value_type),
+            // schema is only known by the plugin metadata
+            (Value::Object(_), metadata::Type::Complex(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+// Implemented by #[derive(MylifeValue)] on structs used as object values
+pub trait MylifeValue {
+    fn value_type() -> metadata::Type;
 }
 
 pub trait TypedFrom<T>: Sized {
@@ -107,6 +577,21 @@ fn is_enum_member(list: &Vec<String>, value: &String) -> bool {
     list.iter().any(|candidate| candidate == value)
 }
 
+pub(crate) fn is_single_char(value: &str) -> bool {
+    let mut chars = value.chars();
+    chars.next().is_some() && chars.next().is_none()
+}
+
+impl TypedFrom<char> for Value {
+    fn typed_from(value: char, ty: &metadata::Type) -> Self {
+        if let metadata::Type::Char = ty {
+            return Value::Text(String::from(value));
+        }
+
+        panic!("Cannot convert from char to Value of type {:?}", ty);
+    }
+}
+
 impl TypedFrom<f64> for Value {
     fn typed_from(value: f64, ty: &metadata::Type) -> Self {
         if let metadata::Type::Float = ty {
@@ -127,6 +612,139 @@ impl TypedFrom<bool> for Value {
     }
 }
 
+// Smaller integers are carried as Range, f32 as Float
+macro_rules! impl_typed_integer {
+    ($($native:ty),*) => {$(
+        impl TypedFrom<$native> for Value {
+            fn typed_from(value: $native, ty: &metadata::Type) -> Self {
+                let value = i64::try_from(value).unwrap_or_else(|_| {
+                    panic!("Cannot convert {} from {} to Value of type {:?}", value, stringify!($native), ty)
+                });
+                Value::typed_from(value, ty)
+            }
+        }
+
+        impl TypedTryFrom<Value> for $native {
+            type Error = ValueConversionError;
+
+            fn typed_try_from(value: Value, ty: &metadata::Type) -> Result<Self, Self::Error> {
+                if let metadata::Type::Range(_, _) = ty {
+                } else {
+                    return Err(ValueConversionError::TypeMismatch(TypeMismatchData {
+                        native_type: stringify!($native),
+                        ty: ty.clone(),
+                    }));
+                }
+
+                if let Value::Range(native_value) = value {
+                    if let Ok(native_value) = <$native>::try_from(native_value) {
+                        return Ok(native_value);
+                    }
+                }
+
+                Err(ValueConversionError::ValueMismatch(ValueMismatchData {
+                    native_type: stringify!($native),
+                    ty: ty.clone(),
+                    value,
+                }))
+            }
+        }
+    )*};
+}
+
+impl_typed_integer!(i8, i16, i32, u8, u16, u32, u64);
+
+impl TypedFrom<f32> for Value {
+    fn typed_from(value: f32, ty: &metadata::Type) -> Self {
+        Value::typed_from(value as f64, ty)
+    }
+}
+
+impl TypedTryFrom<Value> for f32 {
+    type Error = ValueConversionError;
+
+    fn typed_try_from(value: Value, ty: &metadata::Type) -> Result<Self, Self::Error> {
+        let value: f64 = value.typed_try_into(ty)?;
+        Ok(value as f32)
+    }
+}
+
+impl TypedFrom<Duration> for Value {
+    fn typed_from(value: Duration, ty: &metadata::Type) -> Self {
+        if let metadata::Type::Duration = ty {
+            return Value::Duration(value);
+        }
+
+        panic!("Cannot convert from Duration to Value of type {:?}", ty);
+    }
+}
+
+// Note: times beyond +/- 292 million years from the epoch are saturated to i64 milliseconds
+impl TypedFrom<SystemTime> for Value {
+    fn typed_from(value: SystemTime, ty: &metadata::Type) -> Self {
+        if let metadata::Type::Timestamp = ty {
+            let millis = match value.duration_since(UNIX_EPOCH) {
+                Ok(elapsed) => i64::try_from(elapsed.as_millis()).unwrap_or(i64::MAX),
+                Err(err) => i64::try_from(err.duration().as_millis())
+                    .map(|millis| -millis)
+                    .unwrap_or(i64::MIN),
+            };
+
+            return Value::Timestamp(millis);
+        }
+
+        panic!("Cannot convert from SystemTime to Value of type {:?}", ty);
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TypedFrom<chrono::DateTime<chrono::Utc>> for Value {
+    fn typed_from(value: chrono::DateTime<chrono::Utc>, ty: &metadata::Type) -> Self {
+        if let metadata::Type::Timestamp = ty {
+            return Value::Timestamp(value.timestamp_millis());
+        }
+
+        panic!(
+            "Cannot convert from DateTime<Utc> to Value of type {:?}",
+            ty
+        );
+    }
+}
+
+impl<T> TypedFrom<Vec<T>> for Value
+where
+    Value: TypedFrom<T>,
+{
+    fn typed_from(value: Vec<T>, ty: &metadata::Type) -> Self {
+        if let metadata::Type::List(item_type) = ty {
+            return Value::List(
+                value
+                    .into_iter()
+                    .map(|item| Value::typed_from(item, item_type))
+                    .collect(),
+            );
+        }
+
+        panic!("Cannot convert from Vec to Value of type {:?}", ty);
+    }
+}
+
+impl<T> TypedFrom<Option<T>> for Value
+where
+    Value: TypedFrom<T>,
+{
+    fn typed_from(value: Option<T>, ty: &metadata::Type) -> Self {
+        if let metadata::Type::Nullable(value_type) = ty {
+            return match value {
+                Some(value) => Value::typed_from(value, value_type),
+                None => Value::Null,
+            };
+        }
+
+        panic!("Cannot convert from Option to Value of type {:?}", ty);
+    }
+}
+
 impl TypedTryFrom<Value> for i64 {
     type Error = ValueConversionError;
 
@@ -229,54 +847,378 @@ impl TypedTryFrom<Value> for bool {
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum ValueConversionError {
-    TypeMismatch(TypeMismatchData),
-    ValueMismatch(ValueMismatchData),
-}
+impl TypedTryFrom<Value> for Duration {
+    type Error = ValueConversionError;
 
-#[derive(Debug, Clone)]
-pub struct TypeMismatchData {
-    native_type: &'static str,
-    ty: metadata::Type,
-}
+    fn typed_try_from(value: Value, ty: &metadata::Type) -> Result<Self, Self::Error> {
+        if let metadata::Type::Duration = ty {
+        } else {
+            return Err(ValueConversionError::TypeMismatch(TypeMismatchData {
+                native_type: "Duration",
+                ty: ty.clone(),
+            }));
+        }
 
-#[derive(Debug, Clone)]
-pub struct ValueMismatchData {
-    native_type: &'static str,
-    ty: metadata::Type,
-    value: Value,
+        if let Value::Duration(value) = value {
+            Ok(value)
+        } else {
+            Err(ValueConversionError::ValueMismatch(ValueMismatchData {
+                native_type: "Duration",
+                ty: ty.clone(),
+                value,
+            }))
+        }
+    }
 }
 
-impl fmt::Display for ValueConversionError {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            ValueConversionError::TypeMismatch(data) => {
-                write!(
-                    fmt,
-                    "Type mismatch: cannot convert {:?} into {}",
-                    data.ty, data.native_type
-                )
-            }
-            ValueConversionError::ValueMismatch(data) => {
-                write!(
-                    fmt,
-                    "Value mismatch: cannot convert value {:?} of type {:?} into {}",
-                    data.value, data.ty, data.native_type
-                )
-            }
+impl TypedFrom<Rgb> for Value {
+    fn typed_from(value: Rgb, ty: &metadata::Type) -> Self {
+        if let metadata::Type::Color = ty {
+            return value.into();
         }
+
+        panic!("Cannot convert from Rgb to Value of type {:?}", ty);
     }
 }
 
-impl std::error::Error for ValueConversionError {}
+impl TypedTryFrom<Value> for Rgb {
+    type Error = ValueConversionError;
 
-#[derive(Debug, Clone)]
-pub enum ConfigValue {
-    String(String),
-    Bool(bool),
-    Integer(i64),
+    fn typed_try_from(value: Value, ty: &metadata::Type) -> Result<Self, Self::Error> {
+        if let metadata::Type::Color = ty {
+        } else {
+            return Err(ValueConversionError::TypeMismatch(TypeMismatchData {
+                native_type: "Rgb",
+                ty: ty.clone(),
+            }));
+        }
+
+        if let Value::Color { r, g, b } = value {
+            Ok(Rgb::new(r, g, b))
+        } else {
+            Err(ValueConversionError::ValueMismatch(ValueMismatchData {
+                native_type: "Rgb",
+                ty: ty.clone(),
+                value,
+            }))
+        }
+    }
+}
+
+impl TypedTryFrom<Value> for char {
+    type Error = ValueConversionError;
+
+    fn typed_try_from(value: Value, ty: &metadata::Type) -> Result<Self, Self::Error> {
+        if let metadata::Type::Char = ty {
+        } else {
+            return Err(ValueConversionError::TypeMismatch(TypeMismatchData {
+                native_type: "char",
+                ty: ty.clone(),
+            }));
+        }
+
+        if let Value::Text(text) = &value {
+            if is_single_char(text) {
+                return Ok(text.chars().next().unwrap());
+            }
+        }
+
+        Err(ValueConversionError::ValueMismatch(ValueMismatchData {
+            native_type: "char",
+            ty: ty.clone(),
+            value,
+        }))
+    }
+}
+
+impl TypedTryFrom<Value> for SystemTime {
+    type Error = ValueConversionError;
+
+    fn typed_try_from(value: Value, ty: &metadata::Type) -> Result<Self, Self::Error> {
+        if let metadata::Type::Timestamp = ty {
+        } else {
+            return Err(ValueConversionError::TypeMismatch(TypeMismatchData {
+                native_type: "SystemTime",
+                ty: ty.clone(),
+            }));
+        }
+
+        // values come from the bus: a timestamp outside of the platform SystemTime range is a mismatch, not a panic
+        if let Value::Timestamp(millis) = value {
+            let offset = Duration::from_millis(millis.unsigned_abs());
+            let time = if millis >= 0 {
+                UNIX_EPOCH.checked_add(offset)
+            } else {
+                UNIX_EPOCH.checked_sub(offset)
+            };
+
+            if let Some(time) = time {
+                return Ok(time);
+            }
+        }
+
+        Err(ValueConversionError::ValueMismatch(ValueMismatchData {
+            native_type: "SystemTime",
+            ty: ty.clone(),
+            value,
+        }))
+    }
+}
+
+// Note: timestamps outside of the chrono range (about +/- 262 000 years) are a value mismatch
+#[cfg(feature = "chrono")]
+impl TypedTryFrom<Value> for chrono::DateTime<chrono::Utc> {
+    type Error = ValueConversionError;
+
+    fn typed_try_from(value: Value, ty: &metadata::Type) -> Result<Self, Self::Error> {
+        if let metadata::Type::Timestamp = ty {
+        } else {
+            return Err(ValueConversionError::TypeMismatch(TypeMismatchData {
+                native_type: "DateTime<Utc>",
+                ty: ty.clone(),
+            }));
+        }
+
+        if let Value::Timestamp(millis) = value {
+            if let Some(time) = chrono::DateTime::from_timestamp_millis(millis) {
+                return Ok(time);
+            }
+        }
+
+        Err(ValueConversionError::ValueMismatch(ValueMismatchData {
+            native_type: "DateTime<Utc>",
+            ty: ty.clone(),
+            value,
+        }))
+    }
+}
+
+impl<T> TypedTryFrom<Value> for Vec<T>
+where
+    T: TypedTryFrom<Value, Error = ValueConversionError>,
+{
+    type Error = ValueConversionError;
+
+    fn typed_try_from(value: Value, ty: &metadata::Type) -> Result<Self, Self::Error> {
+        let item_type = if let metadata::Type::List(item_type) = ty {
+            item_type
+        } else {
+            return Err(ValueConversionError::TypeMismatch(TypeMismatchData {
+                native_type: "Vec",
+                ty: ty.clone(),
+            }));
+        };
+
+        if let Value::List(items) = value {
+            items
+                .into_iter()
+                .map(|item| T::typed_try_from(item, item_type))
+                .collect()
+        } else {
+            Err(ValueConversionError::ValueMismatch(ValueMismatchData {
+                native_type: "Vec",
+                ty: ty.clone(),
+                value,
+            }))
+        }
+    }
+}
+
+impl<T> TypedTryFrom<Value> for Option<T>
+where
+    T: TypedTryFrom<Value, Error = ValueConversionError>,
+{
+    type Error = ValueConversionError;
+
+    fn typed_try_from(value: Value, ty: &metadata::Type) -> Result<Self, Self::Error> {
+        let value_type = if let metadata::Type::Nullable(value_type) = ty {
+            value_type
+        } else {
+            return Err(ValueConversionError::TypeMismatch(TypeMismatchData {
+                native_type: "Option",
+                ty: ty.clone(),
+            }));
+        };
+
+        if let Value::Null = value {
+            Ok(None)
+        } else {
+            T::typed_try_from(value, value_type).map(Some)
+        }
+    }
+}
+
+// Human readable form for logs, eg: true, 42, "hello", [1, 2], {a: on}
+impl fmt::Display for Value {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Range(value) => write!(fmt, "{}", value),
+            Value::Text(value) => write!(fmt, "{:?}", value),
+            Value::Float(value) => write!(fmt, "{}", value),
+            Value::Bool(value) => write!(fmt, "{}", value),
+            Value::Enum(value) => write!(fmt, "{}", value),
+            Value::List(items) => {
+                write!(fmt, "[")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        write!(fmt, ", ")?;
+                    }
+                    write!(fmt, "{}", item)?;
+                }
+                write!(fmt, "]")
+            }
+            Value::Null => write!(fmt, "null"),
+            Value::Object(fields) => {
+                write!(fmt, "{{")?;
+                for (index, (name, field)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        write!(fmt, ", ")?;
+                    }
+                    write!(fmt, "{}: {}", name, field)?;
+                }
+                write!(fmt, "}}")
+            }
+            Value::Duration(value) => write!(fmt, "{:?}", value),
+            Value::Timestamp(millis) => write!(fmt, "@{}ms", millis),
+            Value::Color { r, g, b } => write!(fmt, "{}", Rgb::new(*r, *g, *b)),
+        }
+    }
+}
+
+// Native side of Value::Color, eg: State<Rgb> for a light plugin
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Rgb { r, g, b }
+    }
+
+    // "#rrggbb", case insensitive
+    pub fn from_hex(input: &str) -> Option<Self> {
+        let hex = input.strip_prefix('#')?;
+        if hex.len() != 6 || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let component = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+        Some(Rgb::new(component(0)?, component(2)?, component(4)?))
+    }
+}
+
+// "#rrggbb", lower case
+impl fmt::Display for Rgb {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl From<Rgb> for Value {
+    fn from(color: Rgb) -> Self {
+        Value::Color {
+            r: color.r,
+            g: color.g,
+            b: color.b,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ValueConversionError {
+    TypeMismatch(TypeMismatchData),
+    ValueMismatch(ValueMismatchData),
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeMismatchData {
+    pub(crate) native_type: &'static str,
+    pub(crate) ty: metadata::Type,
+}
+
+#[derive(Debug, Clone)]
+pub struct ValueMismatchData {
+    pub(crate) native_type: &'static str,
+    pub(crate) ty: metadata::Type,
+    pub(crate) value: Value,
+}
+
+impl fmt::Display for ValueConversionError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValueConversionError::TypeMismatch(data) => {
+                write!(
+                    fmt,
+                    "Type mismatch: cannot convert {:?} into {}",
+                    data.ty, data.native_type
+                )
+            }
+            ValueConversionError::ValueMismatch(data) => {
+                write!(
+                    fmt,
+                    "Value mismatch: cannot convert value {:?} of type {:?} into {}",
+                    data.value, data.ty, data.native_type
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValueConversionError {}
+
+// Same equality semantics as Value
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigValue {
+    String(String),
+    Bool(bool),
+    Integer(i64),
     Float(f64),
+    Null, // explicitly unset (eg: "auto"), only accepted by nullable configs
+}
+
+// Typed accessors for plugins writing setters by hand
+impl ConfigValue {
+    pub fn as_string(&self) -> Result<&str, ConfigValueConversionError> {
+        if let ConfigValue::String(value) = self {
+            Ok(value)
+        } else {
+            Err(self.conversion_error(ConfigValue::String(String::default())))
+        }
+    }
+
+    pub fn as_bool(&self) -> Result<bool, ConfigValueConversionError> {
+        if let ConfigValue::Bool(value) = self {
+            Ok(*value)
+        } else {
+            Err(self.conversion_error(ConfigValue::Bool(bool::default())))
+        }
+    }
+
+    pub fn as_i64(&self) -> Result<i64, ConfigValueConversionError> {
+        if let ConfigValue::Integer(value) = self {
+            Ok(*value)
+        } else {
+            Err(self.conversion_error(ConfigValue::Integer(i64::default())))
+        }
+    }
+
+    pub fn as_f64(&self) -> Result<f64, ConfigValueConversionError> {
+        if let ConfigValue::Float(value) = self {
+            Ok(*value)
+        } else {
+            Err(self.conversion_error(ConfigValue::Float(f64::default())))
+        }
+    }
+
+    fn conversion_error(&self, expected: ConfigValue) -> ConfigValueConversionError {
+        ConfigValueConversionError {
+            expected,
+            actual: self.clone(),
+        }
+    }
 }
 
 impl From<String> for ConfigValue {
@@ -363,6 +1305,171 @@ impl TryFrom<ConfigValue> for f64 {
     }
 }
 
+impl TryFrom<ConfigValue> for Duration {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(value: ConfigValue) -> Result<Self, Self::Error> {
+        if let ConfigValue::String(value) = &value {
+            return Ok(parse_duration(value)?);
+        }
+
+        Err(Box::new(ConfigValueConversionError {
+            expected: ConfigValue::String(String::default()),
+            actual: value,
+        }))
+    }
+}
+
+// "500ms", "2s", "1m", "1h"
+pub fn parse_duration(input: &str) -> Result<Duration, DurationParseError> {
+    lazy_static::lazy_static! {
+        static ref PARSER: Regex = Regex::new(r"^(\d+)(ms|s|m|h)$").unwrap();
+    }
+
+    let matchs = PARSER
+        .captures(input.trim())
+        .ok_or_else(|| DurationParseError {
+            input: String::from(input),
+        })?;
+
+    let error = || DurationParseError {
+        input: String::from(input),
+    };
+
+    let value: u64 = matchs[1].parse().map_err(|_| error())?;
+
+    // input comes from config files: overflow is an error, not a panic
+    Ok(match &matchs[2] {
+        "ms" => Duration::from_millis(value),
+        "s" => Duration::from_secs(value),
+        "m" => Duration::from_secs(value.checked_mul(60).ok_or_else(error)?),
+        "h" => Duration::from_secs(value.checked_mul(3600).ok_or_else(error)?),
+        _ => unreachable!(),
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct ValueParseError {
+    input: String,
+    ty: metadata::Type,
+}
+
+impl fmt::Display for ValueParseError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Invalid value '{}' for type {}", self.input, self.ty)
+    }
+}
+
+impl std::error::Error for ValueParseError {}
+
+#[derive(Debug, Clone)]
+pub struct DurationParseError {
+    input: String,
+}
+
+impl fmt::Display for DurationParseError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Invalid duration '{}' (expected e.g. '500ms', '2s', '1m', '1h')",
+            self.input
+        )
+    }
+}
+
+impl std::error::Error for DurationParseError {}
+
+macro_rules! impl_config_integer {
+    ($($native:ty),*) => {$(
+        impl TryFrom<ConfigValue> for $native {
+            type Error = Box<dyn std::error::Error>;
+
+            fn try_from(value: ConfigValue) -> Result<Self, Self::Error> {
+                let int_value: i64 = value.clone().try_into()?;
+                <$native>::try_from(int_value).map_err(|_| {
+                    Box::new(ConfigValueRangeError {
+                        native_type: stringify!($native),
+                        value,
+                    }) as Box<dyn std::error::Error>
+                })
+            }
+        }
+    )*};
+}
+
+impl_config_integer!(i8, i16, i32, u8, u16, u32, u64);
+
+// Note: cannot be generic, it would conflict with the blanket TryFrom<ConfigValue> for Option<ConfigValue>
+macro_rules! impl_config_nullable {
+    ($($native:ty),*) => {$(
+        impl TryFrom<ConfigValue> for Option<$native> {
+            type Error = Box<dyn std::error::Error>;
+
+            fn try_from(value: ConfigValue) -> Result<Self, Self::Error> {
+                if let ConfigValue::Null = value {
+                    return Ok(None);
+                }
+
+                Ok(Some(<$native>::try_from(value)?))
+            }
+        }
+    )*};
+}
+
+impl_config_nullable!(String, bool, i8, i16, i32, i64, u8, u16, u32, u64, f32, f64, Duration);
+
+// Note: a finite value outside of f32 bounds would silently become infinite
+impl TryFrom<ConfigValue> for f32 {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(value: ConfigValue) -> Result<Self, Self::Error> {
+        let float_value: f64 = value.clone().try_into()?;
+        let result = float_value as f32;
+        if result.is_infinite() && float_value.is_finite() {
+            return Err(Box::new(ConfigValueRangeError {
+                native_type: "f32",
+                value,
+            }));
+        }
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigValueRangeError {
+    native_type: &'static str,
+    value: ConfigValue,
+}
+
+impl ConfigValueRangeError {
+    pub fn native_type(&self) -> &'static str {
+        self.native_type
+    }
+
+    pub fn value(&self) -> &ConfigValue {
+        &self.value
+    }
+}
+
+impl fmt::Display for ConfigValueRangeError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let value = match &self.value {
+            ConfigValue::Integer(value) => value.to_string(),
+            ConfigValue::Float(value) => value.to_string(),
+            value => format!("{:?}", value),
+        };
+
+        write!(
+            fmt,
+            "Config value {} does not fit in {}",
+            value, self.native_type
+        )
+    }
+}
+
+impl std::error::Error for ConfigValueRangeError {}
+
 #[derive(Debug, Clone)]
 pub struct ConfigValueConversionError {
     expected: ConfigValue,
@@ -376,6 +1483,7 @@ impl fmt::Display for ConfigValueConversionError {
             ConfigValue::Bool(_) => "Bool",
             ConfigValue::Integer(_) => "Integer",
             ConfigValue::Float(_) => "Float",
+            ConfigValue::Null => "Null",
         };
 
         let actual = match &self.actual {
@@ -383,6 +1491,7 @@ impl fmt::Display for ConfigValueConversionError {
             ConfigValue::Bool(value) => format!("Bool({})", value),
             ConfigValue::Integer(value) => format!("Integer({})", value),
             ConfigValue::Float(value) => format!("Float({})", value),
+            ConfigValue::Null => String::from("Null"),
         };
 
         write!(
@@ -394,3 +1503,324 @@ impl fmt::Display for ConfigValueConversionError {
 }
 
 impl std::error::Error for ConfigValueConversionError {}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use super::*;
+
+    fn test_round_trip<T>(native: T, ty: metadata::Type)
+    where
+        T: Clone + Debug + PartialEq + TypedInto<Value> + TypedTryFrom<Value>,
+        <T as TypedTryFrom<Value>>::Error: Debug,
+    {
+        let value: Value = native.clone().typed_into(&ty);
+        assert!(value.is_of_type(&ty));
+
+        let result: T = value.typed_try_into(&ty).unwrap();
+        assert_eq!(result, native);
+    }
+
+    #[test]
+    fn test_round_trip_scalars() {
+        test_round_trip(12i64, metadata::Type::Range(-12, 42));
+        test_round_trip(String::from("text"), metadata::Type::Text);
+        test_round_trip(4.2f64, metadata::Type::Float);
+        test_round_trip(Duration::from_millis(500), metadata::Type::Duration);
+        test_round_trip(true, metadata::Type::Bool);
+        test_round_trip(
+            String::from("two"),
+            metadata::Type::Enum(vec![
+                "one".to_string(),
+                "two".to_string(),
+                "three".to_string(),
+            ]),
+        );
+    }
+
+    #[test]
+    fn test_round_trip_compounds() {
+        test_round_trip(
+            vec![true, false],
+            metadata::Type::List(Box::new(metadata::Type::Bool)),
+        );
+        test_round_trip(
+            Some(4.2f64),
+            metadata::Type::Nullable(Box::new(metadata::Type::Float)),
+        );
+        test_round_trip(
+            None::<f64>,
+            metadata::Type::Nullable(Box::new(metadata::Type::Float)),
+        );
+    }
+
+    #[test]
+    fn test_type_of() {
+        assert_eq!(Value::Bool(true).type_of(), metadata::Type::Bool);
+        assert_eq!(Value::Range(12).type_of(), metadata::Type::Range(12, 12));
+        assert_eq!(
+            Value::List(vec![Value::Text(String::from("text"))]).type_of(),
+            metadata::Type::List(Box::new(metadata::Type::Text))
+        );
+    }
+
+    #[test]
+    fn test_is_of_type() {
+        assert!(!Value::Range(43).is_of_type(&metadata::Type::Range(-12, 42)));
+        assert!(
+            !Value::Enum(String::from("four")).is_of_type(&metadata::Type::Enum(vec![
+                "one".to_string(),
+                "two".to_string()
+            ]))
+        );
+        assert!(!Value::List(vec![Value::Bool(true), Value::Float(4.2)])
+            .is_of_type(&metadata::Type::List(Box::new(metadata::Type::Bool))));
+        assert!(!Value::Null.is_of_type(&metadata::Type::Bool));
+    }
+
+    #[test]
+    fn test_object_is_of_type() {
+        let ty = metadata::Type::Object(vec![
+            (String::from("temperature"), metadata::Type::Float),
+            (String::from("humidity"), metadata::Type::Range(0, 100)),
+        ]);
+
+        let mut fields = BTreeMap::new();
+        fields.insert(String::from("temperature"), Value::Float(21.5));
+        fields.insert(String::from("humidity"), Value::Range(40));
+        assert!(Value::Object(fields.clone()).is_of_type(&ty));
+
+        fields.insert(String::from("humidity"), Value::Range(140));
+        assert!(!Value::Object(fields.clone()).is_of_type(&ty));
+
+        fields.remove("humidity");
+        assert!(!Value::Object(fields).is_of_type(&ty));
+    }
+
+    #[test]
+    fn test_integer_types() {
+        test_round_trip(42u8, metadata::Type::Range(0, 255));
+        test_round_trip(-42i32, metadata::Type::Range(-100, 100));
+        test_round_trip(42u64, metadata::Type::Range(0, 100));
+        test_round_trip(4.5f32, metadata::Type::Float);
+
+        let result: Result<u8, _> =
+            Value::Range(300).typed_try_into(&metadata::Type::Range(0, 1000));
+        assert!(matches!(
+            result,
+            Err(ValueConversionError::ValueMismatch(_))
+        ));
+
+        let value: Result<u8, _> = ConfigValue::Integer(42).try_into();
+        assert_eq!(value.unwrap(), 42);
+        let value: Result<u8, _> = ConfigValue::Integer(-1).try_into();
+        assert!(value.is_err());
+    }
+
+    #[test]
+    fn test_config_value_overflow() {
+        let result: Result<u8, _> = ConfigValue::Integer(300).try_into();
+        let err = result.unwrap_err();
+        let err = err.downcast_ref::<ConfigValueRangeError>().unwrap();
+        assert_eq!(err.native_type(), "u8");
+        assert_eq!(err.value(), &ConfigValue::Integer(300));
+        assert_eq!(err.to_string(), "Config value 300 does not fit in u8");
+
+        let result: Result<f32, _> = ConfigValue::Float(1e300).try_into();
+        let err = result.unwrap_err();
+        let err = err.downcast_ref::<ConfigValueRangeError>().unwrap();
+        assert_eq!(err.native_type(), "f32");
+
+        let value: Result<f32, _> = ConfigValue::Float(f64::INFINITY).try_into();
+        assert_eq!(value.unwrap(), f32::INFINITY);
+
+        // wrong variant is still a conversion error
+        let result: Result<u8, _> = ConfigValue::Bool(true).try_into();
+        assert!(result
+            .unwrap_err()
+            .downcast_ref::<ConfigValueConversionError>()
+            .is_some());
+    }
+
+    #[test]
+    fn test_config_value_accessors() {
+        assert_eq!(
+            ConfigValue::String("text".into()).as_string().unwrap(),
+            "text"
+        );
+        assert!(ConfigValue::String("text".into()).as_bool().is_err());
+        assert!(ConfigValue::Bool(true).as_bool().unwrap());
+        assert_eq!(ConfigValue::Integer(42).as_i64().unwrap(), 42);
+        assert_eq!(ConfigValue::Float(4.2).as_f64().unwrap(), 4.2);
+
+        let err = ConfigValue::Integer(42).as_f64().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("expected type: Float, actual value: Integer(42)"));
+    }
+
+    #[test]
+    fn test_equality() {
+        assert_eq!(Value::Float(4.2), Value::Float(4.2));
+        assert_ne!(Value::Float(f64::NAN), Value::Float(f64::NAN));
+        assert_ne!(
+            Value::Text(String::from("a")),
+            Value::Enum(String::from("a"))
+        );
+        assert_ne!(Value::Range(1), Value::Float(1.0));
+
+        assert_eq!(ConfigValue::Integer(42), ConfigValue::Integer(42));
+        assert_ne!(ConfigValue::Integer(1), ConfigValue::Float(1.0));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Value::Bool(true).to_string(), "true");
+        assert_eq!(Value::Range(42).to_string(), "42");
+        assert_eq!(Value::Float(2.5).to_string(), "2.5");
+        assert_eq!(Value::Text(String::from("hello")).to_string(), "\"hello\"");
+        assert_eq!(Value::Enum(String::from("on")).to_string(), "on");
+        assert_eq!(Value::Null.to_string(), "null");
+        assert_eq!(
+            Value::Duration(Duration::from_millis(1500)).to_string(),
+            "1.5s"
+        );
+        assert_eq!(
+            Value::List(vec![Value::Range(1), Value::Null]).to_string(),
+            "[1, null]"
+        );
+        assert_eq!(Value::List(Vec::new()).to_string(), "[]");
+        assert_eq!(
+            Value::Object(BTreeMap::from([
+                (String::from("a"), Value::Bool(false)),
+                (String::from("b"), Value::Text(String::from("x"))),
+            ]))
+            .to_string(),
+            "{a: false, b: \"x\"}"
+        );
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let ty = metadata::Type::Timestamp;
+        let time = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+
+        let value: Value = time.typed_into(&ty);
+        assert_eq!(value, Value::Timestamp(1_700_000_000_123));
+        assert_eq!(value.to_string(), "@1700000000123ms");
+
+        let native: SystemTime = value.typed_try_into(&ty).unwrap();
+        assert_eq!(native, time);
+
+        let before: SystemTime = Value::Timestamp(-1000).typed_try_into(&ty).unwrap();
+        assert_eq!(before, UNIX_EPOCH - Duration::from_secs(1));
+        assert_eq!(Value::typed_from(before, &ty), Value::Timestamp(-1000));
+
+        // extreme values are accepted or rejected depending on the platform, but never panic
+        for millis in [i64::MIN, i64::MAX] {
+            let _: Result<SystemTime, _> = Value::Timestamp(millis).typed_try_into(&ty);
+        }
+
+        assert_eq!(Value::parse("42", &ty).unwrap(), Value::Timestamp(42));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_timestamp() {
+        use chrono::{DateTime, TimeZone, Utc};
+
+        let ty = metadata::Type::Timestamp;
+        let time = Utc.timestamp_millis_opt(1_700_000_000_123).unwrap();
+
+        let value: Value = time.typed_into(&ty);
+        assert_eq!(value, Value::Timestamp(1_700_000_000_123));
+
+        let native: DateTime<Utc> = value.typed_try_into(&ty).unwrap();
+        assert_eq!(native, time);
+
+        let before: DateTime<Utc> = Value::Timestamp(-1000).typed_try_into(&ty).unwrap();
+        assert_eq!(before, Utc.timestamp_opt(-1, 0).unwrap());
+
+        let result: Result<DateTime<Utc>, _> = Value::Timestamp(i64::MAX).typed_try_into(&ty);
+        assert!(matches!(
+            result,
+            Err(ValueConversionError::ValueMismatch(_))
+        ));
+
+        let result: Result<DateTime<Utc>, _> =
+            Value::Timestamp(0).typed_try_into(&metadata::Type::Duration);
+        assert!(matches!(result, Err(ValueConversionError::TypeMismatch(_))));
+    }
+
+    #[test]
+    fn test_color() {
+        let ty = metadata::Type::Color;
+
+        let value: Value = Rgb::new(255, 128, 0).typed_into(&ty);
+        assert_eq!(
+            value,
+            Value::Color {
+                r: 255,
+                g: 128,
+                b: 0
+            }
+        );
+        assert_eq!(value.to_string(), "#ff8000");
+
+        let native: Rgb = value.typed_try_into(&ty).unwrap();
+        assert_eq!(native, Rgb::new(255, 128, 0));
+
+        assert_eq!(Value::parse("#FF8000", &ty).unwrap(), native.into());
+        assert!(Value::parse("ff8000", &ty).is_err());
+        assert!(Value::parse("#ff80", &ty).is_err());
+        assert!(Value::parse("#ff800g", &ty).is_err());
+    }
+
+    #[test]
+    fn test_char() {
+        let ty = metadata::Type::Char;
+
+        let value: Value = '#'.typed_into(&ty);
+        assert_eq!(value, Value::Text("#".into()));
+
+        let native: char = value.typed_try_into(&ty).unwrap();
+        assert_eq!(native, '#');
+
+        let native: char = Value::Text("é".into()).typed_try_into(&ty).unwrap();
+        assert_eq!(native, 'é');
+
+        let result: Result<char, _> = Value::Text("12".into()).typed_try_into(&ty);
+        assert!(result.is_err());
+        let result: Result<char, _> = Value::Text("".into()).typed_try_into(&ty);
+        assert!(result.is_err());
+
+        assert!(Value::parse("ab", &ty).is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("2s").unwrap(), Duration::from_secs(2));
+        assert_eq!(parse_duration("1m").unwrap(), Duration::from_secs(60));
+        assert_eq!(parse_duration("1h").unwrap(), Duration::from_secs(3600));
+        assert!(parse_duration("2").is_err());
+        assert!(parse_duration("-2s").is_err());
+        assert!(parse_duration("1.5s").is_err());
+        assert!(parse_duration("6000000000000000000h").is_err());
+        assert!(parse_duration("600000000000000000m").is_err());
+        assert!(parse_duration("99999999999999999999s").is_err());
+    }
+
+    #[test]
+    fn test_try_from_mismatch() {
+        let result: Result<bool, _> = Value::Float(4.2).typed_try_into(&metadata::Type::Bool);
+        assert!(matches!(
+            result,
+            Err(ValueConversionError::ValueMismatch(_))
+        ));
+
+        let result: Result<bool, _> = Value::Bool(true).typed_try_into(&metadata::Type::Float);
+        assert!(matches!(result, Err(ValueConversionError::TypeMismatch(_))));
+    }
+}
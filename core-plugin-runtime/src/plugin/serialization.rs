@@ -0,0 +1,414 @@
+use std::fmt;
+
+use serde_json::{json, Map};
+
+use crate::{
+    encoding,
+    metadata::{
+        Capability, ConfigItem, ConfigType, Member, MemberType, PluginCategory, PluginMetadata,
+        PluginUsage,
+    },
+    runtime::{Config, ConfigValue},
+};
+
+impl PluginMetadata {
+    // Version of the JSON shape produced by to_json, consumers must check it before reading anything else.
+    // Migration policy:
+    // - adding a field keeps the version: consumers ignore fields they do not know
+    // - removing or renaming a field, or changing its format or meaning, increments it
+    // - a consumer only reads the versions it knows, and reports the plugin as incompatible otherwise
+    pub const SCHEMA_VERSION: u32 = 1;
+
+    // For the UI, types are serialized in their string form (see Type Display)
+    pub fn to_json(&self) -> serde_json::Value {
+        let members: Map<String, serde_json::Value> = self
+            .members()
+            .iter()
+            .map(|(name, member)| (name.clone(), member_to_json(member)))
+            .collect();
+
+        let config: Map<String, serde_json::Value> = self
+            .config()
+            .iter()
+            .map(|(name, item)| (name.clone(), config_to_json(item)))
+            .collect();
+
+        let complex_types: Map<String, serde_json::Value> = self
+            .complex_types()
+            .keys()
+            .map(|name| {
+                let schema = self.complex_type(name).unwrap();
+                (name.clone(), json!(schema.to_string()))
+            })
+            .collect();
+
+        json!({
+            "schemaVersion": Self::SCHEMA_VERSION,
+            "name": self.name(),
+            "id": self.id(),
+            "version": self.version(),
+            "description": self.description(),
+            "usage": usage_name(self.usage()),
+            "category": self.category().map(category_name),
+            "tags": self.tags(),
+            "requiredCapabilities": self
+                .required_capabilities()
+                .iter()
+                .map(|capability| capability_name(*capability))
+                .collect::<Vec<_>>(),
+            "deprecated": self.deprecated(),
+            "members": members,
+            "config": config,
+            "acceptsExtraConfig": self.accepts_extra_config(),
+            "complexTypes": complex_types,
+        })
+    }
+
+    // Component config file: a JSON object with one key per config item, eg: {"port": 8080, "delay": "2s"}
+    // Values are coerced to the declared config type, so "8080" is accepted for an integer.
+    // Undeclared keys are kept as is, configure or configure_strict decides what to do with them
+    pub fn config_from_json(&self, input: &str) -> Result<Config, ConfigParseError> {
+        let json: serde_json::Value = serde_json::from_str(input)
+            .map_err(|err| ConfigParseError::new(format!("Invalid JSON: {err}")))?;
+
+        let object = match json {
+            serde_json::Value::Object(object) => object,
+            _ => {
+                return Err(ConfigParseError::new(String::from(
+                    "Expected a JSON object",
+                )))
+            }
+        };
+
+        let mut config = Config::new();
+
+        for (name, json) in object {
+            let item = self.config().get(&name);
+            let value = match item {
+                Some(item) => config_from_json_typed(&json, item.value_type()),
+                None => config_from_json_untyped(&json),
+            }
+            .ok_or_else(|| {
+                // the error ends up in logs: secret values are not printed
+                let shown = if item.is_some_and(|item| item.secret()) {
+                    String::from("***")
+                } else {
+                    json.to_string()
+                };
+
+                ConfigParseError::new(format!("Invalid value {shown} for config '{name}'"))
+            })?;
+
+            config.insert(name, value);
+        }
+
+        Ok(config)
+    }
+}
+
+impl ConfigValue {
+    // With the declared type, the value is coerced as in config files (see config_from_json)
+    pub fn from_json(json: &serde_json::Value, ty: Option<ConfigType>) -> Option<ConfigValue> {
+        match ty {
+            Some(ty) => config_from_json_typed(json, ty),
+            None => config_from_json_untyped(json),
+        }
+    }
+}
+
+// floats which are not finite become null
+impl From<ConfigValue> for serde_json::Value {
+    fn from(value: ConfigValue) -> Self {
+        match value {
+            ConfigValue::String(value) => serde_json::Value::from(value),
+            ConfigValue::Bool(value) => serde_json::Value::from(value),
+            ConfigValue::Integer(value) => serde_json::Value::from(value),
+            ConfigValue::Float(value) => serde_json::Value::from(value),
+            ConfigValue::Null => serde_json::Value::Null,
+        }
+    }
+}
+
+fn config_from_json_typed(json: &serde_json::Value, ty: ConfigType) -> Option<ConfigValue> {
+    use serde_json::Value as Json;
+
+    match (ty, json) {
+        (ConfigType::String, Json::String(value)) => Some(ConfigValue::String(value.clone())),
+        (ConfigType::Bool, Json::Bool(value)) => Some(ConfigValue::Bool(*value)),
+        (ConfigType::Bool, Json::String(value)) => value.parse().ok().map(ConfigValue::Bool),
+        (ConfigType::Integer, Json::Number(value)) => value.as_i64().map(ConfigValue::Integer),
+        (ConfigType::Integer, Json::String(value)) => value.parse().ok().map(ConfigValue::Integer),
+        (ConfigType::Float, Json::Number(value)) => value.as_f64().map(ConfigValue::Float),
+        (ConfigType::Float, Json::String(value)) => value.parse().ok().map(ConfigValue::Float),
+        // numbers are milliseconds, as on the bus
+        (ConfigType::Duration, Json::String(value)) => Some(ConfigValue::String(value.clone())),
+        (ConfigType::Duration, Json::Number(value)) => value
+            .as_u64()
+            .map(|millis| ConfigValue::String(format!("{millis}ms"))),
+        // rejected on configure if the config is not nullable
+        (_, Json::Null) => Some(ConfigValue::Null),
+        _ => None,
+    }
+}
+
+fn config_from_json_untyped(json: &serde_json::Value) -> Option<ConfigValue> {
+    match json {
+        serde_json::Value::String(value) => Some(ConfigValue::String(value.clone())),
+        serde_json::Value::Bool(value) => Some(ConfigValue::Bool(*value)),
+        serde_json::Value::Number(value) => value
+            .as_i64()
+            .map(ConfigValue::Integer)
+            .or_else(|| value.as_f64().map(ConfigValue::Float)),
+        serde_json::Value::Null => Some(ConfigValue::Null),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigParseError {
+    message: String,
+}
+
+impl ConfigParseError {
+    fn new(message: String) -> Self {
+        ConfigParseError { message }
+    }
+}
+
+impl fmt::Display for ConfigParseError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Config parse error: {}", self.message)
+    }
+}
+
+impl std::error::Error for ConfigParseError {}
+
+fn member_to_json(member: &Member) -> serde_json::Value {
+    let member_type = match member.member_type() {
+        MemberType::Action => "action",
+        MemberType::State => "state",
+        MemberType::Event => "event",
+    };
+
+    // Note: default arg is checked against the value type at build
+    let default_arg = member
+        .default_arg()
+        .and_then(|value| encoding::to_json(value, member.value_type()).ok());
+
+    json!({
+        "memberType": member_type,
+        "description": member.description(),
+        "valueType": member.value_type().to_string(),
+        "actions": member.actions(),
+        "resultType": member.result_type().map(|ty| ty.to_string()),
+        "defaultArg": default_arg,
+        "unit": member.unit(),
+        // period in milliseconds, as durations on the bus
+        "rateLimit": member.rate_limit().map(|rate_limit| json!({
+            "count": rate_limit.count(),
+            "period": rate_limit.period().as_millis() as u64,
+        })),
+        "deprecated": member.deprecated(),
+    })
+}
+
+fn config_to_json(item: &ConfigItem) -> serde_json::Value {
+    let value_type = match item.value_type() {
+        ConfigType::String => "string",
+        ConfigType::Bool => "bool",
+        ConfigType::Integer => "integer",
+        ConfigType::Float => "float",
+        ConfigType::Duration => "duration",
+    };
+
+    json!({
+        "description": item.description(),
+        "valueType": value_type,
+        "pattern": item.pattern(),
+        "range": item.range().map(|(min, max)| [min, max]),
+        "clamp": item.clamp(),
+        "unit": item.unit(),
+        "nullable": item.nullable(),
+        "secret": item.secret(),
+        "deprecated": item.deprecated(),
+    })
+}
+
+fn usage_name(usage: PluginUsage) -> &'static str {
+    match usage {
+        PluginUsage::Sensor => "sensor",
+        PluginUsage::Actuator => "actuator",
+        PluginUsage::Logic => "logic",
+        PluginUsage::Ui => "ui",
+    }
+}
+
+fn capability_name(capability: Capability) -> &'static str {
+    match capability {
+        Capability::Network => "network",
+        Capability::Filesystem => "filesystem",
+        Capability::Time => "time",
+        Capability::Gpio => "gpio",
+        Capability::Serial => "serial",
+    }
+}
+
+fn category_name(category: PluginCategory) -> &'static str {
+    match category {
+        PluginCategory::Lighting => "lighting",
+        PluginCategory::Heating => "heating",
+        PluginCategory::Shutters => "shutters",
+        PluginCategory::Security => "security",
+        PluginCategory::Energy => "energy",
+        PluginCategory::Multimedia => "multimedia",
+        PluginCategory::Network => "network",
+        PluginCategory::Other => "other",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metadata::{PluginMetadataBuilder, RateLimit, Type};
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Actuator)
+            .category(Some(PluginCategory::Lighting))
+            .add_state("level", None, Type::Range(0, 100))
+            .add_action("setLevel", Some("set level"), Type::Range(0, 100))
+            .set_action_default_arg("setLevel", "50")
+            .set_action_rate_limit("setLevel", RateLimit::new(10, Duration::from_secs(1)))
+            .link_state_actions("level", &["setLevel"])
+            .set_state_unit("level", "%")
+            .add_config("port", None, ConfigType::Integer)
+            .set_config_range("port", 1, 65535, false)
+            .required_capabilities(&[Capability::Network])
+            .build()
+            .unwrap();
+
+        let json = meta.to_json();
+
+        assert_eq!(json["schemaVersion"], json!(PluginMetadata::SCHEMA_VERSION));
+        assert_eq!(json["name"], json!("plugin-name"));
+        assert_eq!(json["id"], serde_json::Value::Null);
+        assert_eq!(json["version"], serde_json::Value::Null);
+        assert_eq!(json["usage"], json!("actuator"));
+        assert_eq!(json["category"], json!("lighting"));
+        assert_eq!(json["requiredCapabilities"], json!(["network"]));
+        assert_eq!(
+            json["members"]["level"],
+            json!({
+                "memberType": "state",
+                "description": null,
+                "valueType": "range[0;100]",
+                "actions": ["setLevel"],
+                "resultType": null,
+                "defaultArg": null,
+                "unit": "%",
+                "rateLimit": null,
+                "deprecated": null,
+            })
+        );
+        assert_eq!(json["members"]["setLevel"]["defaultArg"], json!(50));
+        assert_eq!(
+            json["members"]["setLevel"]["rateLimit"],
+            json!({ "count": 10, "period": 1000 })
+        );
+        assert_eq!(
+            json["config"]["port"],
+            json!({
+                "description": null,
+                "valueType": "integer",
+                "pattern": null,
+                "range": [1, 65535],
+                "clamp": false,
+                "unit": null,
+                "nullable": false,
+                "secret": false,
+                "deprecated": null,
+            })
+        );
+        assert_eq!(json["acceptsExtraConfig"], json!(false));
+    }
+
+    #[test]
+    fn test_config_from_json() {
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Actuator)
+            .add_config("port", None, ConfigType::Integer)
+            .add_config("ratio", None, ConfigType::Float)
+            .add_config("enabled", None, ConfigType::Bool)
+            .add_config("delay", None, ConfigType::Duration)
+            .add_config("host", None, ConfigType::String)
+            .build()
+            .unwrap();
+
+        let config = meta
+            .config_from_json(
+                r#"{"port": "8080", "ratio": 1, "enabled": true, "delay": 1500, "host": "localhost", "other": 2}"#,
+            )
+            .unwrap();
+
+        assert_eq!(config["port"], ConfigValue::Integer(8080));
+        assert_eq!(config["ratio"], ConfigValue::Float(1.0));
+        assert_eq!(config["enabled"], ConfigValue::Bool(true));
+        assert_eq!(config["delay"], ConfigValue::String(String::from("1500ms")));
+        assert_eq!(
+            config["host"],
+            ConfigValue::String(String::from("localhost"))
+        );
+        assert_eq!(config["other"], ConfigValue::Integer(2));
+
+        assert!(meta.config_from_json(r#"{"port": 1.5}"#).is_err());
+        assert!(meta.config_from_json(r#"{"host": 1}"#).is_err());
+        assert!(meta.config_from_json(r#"{"other": [1]}"#).is_err());
+        assert!(meta.config_from_json("[]").is_err());
+        assert!(meta.config_from_json("{").is_err());
+    }
+
+    #[test]
+    fn test_config_from_json_secret() {
+        let meta = PluginMetadataBuilder::new()
+            .name("plugin-name")
+            .usage(PluginUsage::Actuator)
+            .add_config("apiKey", None, ConfigType::String)
+            .set_config_secret("apiKey")
+            .add_config("port", None, ConfigType::Integer)
+            .build()
+            .unwrap();
+
+        let err = meta.config_from_json(r#"{"apiKey": 424242}"#).unwrap_err();
+        assert!(!err.to_string().contains("424242"));
+        assert!(err.to_string().contains("***"));
+        assert!(err.to_string().contains("apiKey"));
+
+        let err = meta.config_from_json(r#"{"port": "abc"}"#).unwrap_err();
+        assert!(err.to_string().contains("abc"));
+    }
+
+    #[test]
+    fn test_config_value_json() {
+        for value in [
+            ConfigValue::String(String::from("localhost")),
+            ConfigValue::Bool(true),
+            ConfigValue::Integer(-2),
+            ConfigValue::Float(0.5),
+            ConfigValue::Null,
+        ] {
+            let json = serde_json::Value::from(value.clone());
+            assert_eq!(ConfigValue::from_json(&json, None), Some(value));
+        }
+
+        assert_eq!(
+            ConfigValue::from_json(&json!("8080"), Some(ConfigType::Integer)),
+            Some(ConfigValue::Integer(8080))
+        );
+        assert_eq!(ConfigValue::from_json(&json!([1]), None), None);
+    }
+}
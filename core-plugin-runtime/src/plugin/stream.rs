@@ -0,0 +1,175 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use crate::runtime::Value;
+
+// What to do with a state change while the stream buffer is full
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StreamBackpressure {
+    Unbounded,
+    DropNewest(usize), // capacity
+    DropOldest(usize), // capacity
+}
+
+#[derive(Debug)]
+struct StreamShared {
+    queue: VecDeque<(String, Value)>,
+    backpressure: StreamBackpressure,
+    dropped: usize,
+    waker: Option<Waker>,
+    closed: bool,
+}
+
+// Async side of MylifeComponent::state_stream, with no dependency on an async runtime.
+// poll_next follows futures::Stream, eg: futures::stream::poll_fn(move |cx| stream.poll_next(cx))
+// The stream ends once the component drops its state handlers (set_on_state, component dropped)
+#[derive(Debug)]
+pub struct StateStream {
+    shared: Arc<Mutex<StreamShared>>,
+}
+
+// Callback side, owned by the state handler
+pub(crate) struct StateStreamSender {
+    shared: Arc<Mutex<StreamShared>>,
+}
+
+pub(crate) fn state_stream_channel(
+    backpressure: StreamBackpressure,
+) -> (StateStreamSender, StateStream) {
+    let shared = Arc::new(Mutex::new(StreamShared {
+        queue: VecDeque::new(),
+        backpressure,
+        dropped: 0,
+        waker: None,
+        closed: false,
+    }));
+
+    (
+        StateStreamSender {
+            shared: shared.clone(),
+        },
+        StateStream { shared },
+    )
+}
+
+impl StateStreamSender {
+    pub(crate) fn send(&self, name: &str, value: Value) {
+        let mut shared = self.shared.lock().unwrap();
+
+        match shared.backpressure {
+            StreamBackpressure::DropNewest(capacity) if shared.queue.len() >= capacity => {
+                shared.dropped += 1;
+                return;
+            }
+            StreamBackpressure::DropOldest(capacity) if shared.queue.len() >= capacity => {
+                shared.queue.pop_front();
+                shared.dropped += 1;
+            }
+            _ => {}
+        }
+
+        shared.queue.push_back((String::from(name), value));
+
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Drop for StateStreamSender {
+    fn drop(&mut self) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.closed = true;
+
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl StateStream {
+    // Ready(None) once the stream is closed and drained
+    pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<(String, Value)>> {
+        let mut shared = self.shared.lock().unwrap();
+
+        if let Some(change) = shared.queue.pop_front() {
+            return Poll::Ready(Some(change));
+        }
+
+        if shared.closed {
+            return Poll::Ready(None);
+        }
+
+        shared.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    // eg: while let Some((name, value)) = stream.recv().await
+    pub fn recv(&mut self) -> StateStreamRecv<'_> {
+        StateStreamRecv { stream: self }
+    }
+
+    // non blocking, None if no change is pending
+    pub fn try_next(&mut self) -> Option<(String, Value)> {
+        self.shared.lock().unwrap().queue.pop_front()
+    }
+
+    // changes lost to the backpressure policy so far
+    pub fn dropped(&self) -> usize {
+        self.shared.lock().unwrap().dropped
+    }
+}
+
+pub struct StateStreamRecv<'a> {
+    stream: &'a mut StateStream,
+}
+
+impl Future for StateStreamRecv<'_> {
+    type Output = Option<(String, Value)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.stream.poll_next(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poll(stream: &mut StateStream) -> Poll<Option<(String, Value)>> {
+        stream.poll_next(&mut Context::from_waker(Waker::noop()))
+    }
+
+    #[test]
+    fn test_backpressure() {
+        let (sender, mut stream) = state_stream_channel(StreamBackpressure::DropOldest(2));
+        assert_eq!(poll(&mut stream), Poll::Pending);
+
+        sender.send("a", Value::Bool(true));
+        sender.send("b", Value::Bool(true));
+        sender.send("c", Value::Bool(true));
+
+        assert_eq!(stream.dropped(), 1);
+        assert_eq!(
+            poll(&mut stream),
+            Poll::Ready(Some(("b".into(), Value::Bool(true))))
+        );
+
+        drop(sender);
+        assert_eq!(stream.try_next(), Some(("c".into(), Value::Bool(true))));
+        assert_eq!(poll(&mut stream), Poll::Ready(None));
+
+        let (sender, mut stream) = state_stream_channel(StreamBackpressure::DropNewest(1));
+        sender.send("a", Value::Bool(true));
+        sender.send("b", Value::Bool(true));
+
+        assert_eq!(stream.dropped(), 1);
+        assert_eq!(stream.try_next(), Some(("a".into(), Value::Bool(true))));
+        assert_eq!(stream.try_next(), None);
+    }
+}
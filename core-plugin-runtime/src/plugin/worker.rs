@@ -0,0 +1,354 @@
+use std::{
+    fmt,
+    sync::{mpsc, Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::runtime::{
+    ActionTimeoutError, ComponentSnapshot, ComponentState, Config, FailContext, Health,
+    MylifeComponent, Value,
+};
+
+// How often pending debounced states and polled states are published
+const DEBOUNCE_TICK: Duration = Duration::from_millis(10);
+
+type Command = Box<dyn FnOnce(&mut dyn MylifeComponent) + Send>;
+
+// Same as runtime::FailHandler, called on the caller thread
+pub type WorkerFailHandler = Box<dyn Fn(&FailContext, &dyn std::error::Error) + Send>;
+
+// Shared by the callers of execute_action_with_timeout and the worker thread
+#[derive(Default)]
+struct Watchdog {
+    overrun: Option<String>, // reason, Degraded health until an action completes in time
+    running: bool,           // the overrunning action did not return yet
+}
+
+// Components are not Send (plugins and handlers are free to use Rc/RefCell).
+// A worker creates its component on a dedicated thread and runs every command there,
+// handlers set through a command are called on the worker thread.
+pub struct ComponentWorker {
+    sender: Option<mpsc::Sender<Command>>,
+    thread: Option<JoinHandle<()>>,
+    watchdog: Arc<Mutex<Watchdog>>,
+    fail_handler: Mutex<Option<WorkerFailHandler>>,
+}
+
+enum ActionMessage {
+    Started(Instant),
+    Done(Result<(), ComponentCallError>, Duration), // elapsed
+}
+
+impl ComponentWorker {
+    pub fn spawn<F>(create: F) -> Self
+    where
+        F: FnOnce() -> Box<dyn MylifeComponent> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel::<Command>();
+
+        let thread = thread::spawn(move || {
+            let mut component = create();
+
+            loop {
+                match receiver.recv_timeout(DEBOUNCE_TICK) {
+                    Ok(command) => command(component.as_mut()),
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+
+                component.process_debounced_states();
+                component.poll_states();
+            }
+        });
+
+        ComponentWorker {
+            sender: Some(sender),
+            thread: Some(thread),
+            watchdog: Arc::new(Mutex::new(Watchdog::default())),
+            fail_handler: Mutex::new(None),
+        }
+    }
+
+    // run on the worker thread without waiting
+    // fails if the worker thread exited (eg: a command panicked)
+    pub fn post<F>(&self, command: F) -> Result<(), WorkerStoppedError>
+    where
+        F: FnOnce(&mut dyn MylifeComponent) + Send + 'static,
+    {
+        self.sender
+            .as_ref()
+            .ok_or(WorkerStoppedError)?
+            .send(Box::new(command))
+            .map_err(|_| WorkerStoppedError)
+    }
+
+    // run on the worker thread and wait for the result
+    // Note: Box<dyn Error> is not Send, errors must be converted (eg: to_string) by the command
+    pub fn call<F, R>(&self, command: F) -> Result<R, WorkerStoppedError>
+    where
+        F: FnOnce(&mut dyn MylifeComponent) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (sender, receiver) = mpsc::sync_channel(1);
+
+        self.post(move |component| {
+            let _ = sender.send(command(component));
+        })?;
+
+        // the sender is dropped without result if the command panics
+        receiver.recv().map_err(|_| WorkerStoppedError)
+    }
+
+    // overruns of execute_action_with_timeout: the worker thread may be stuck in the action,
+    // the handler is called on the caller thread instead of the component fail handler
+    pub fn set_on_fail(&self, handler: WorkerFailHandler) {
+        *self.fail_handler.lock().unwrap() = Some(handler);
+    }
+
+    // Degraded while an action overran its timeout, without waiting for the worker thread which may still run it.
+    // The component health otherwise
+    pub fn health(&self) -> Result<Health, WorkerStoppedError> {
+        if let Some(reason) = &self.watchdog.lock().unwrap().overrun {
+            return Ok(Health::Degraded(reason.clone()));
+        }
+
+        self.call(|component| component.health())
+    }
+
+    // watchdog: the caller gets ActionTimeoutError once timeout elapsed since the action started (time spent queued
+    // behind other commands does not count), without waiting for the action.
+    // Synchronous code cannot be interrupted, the action still runs to completion on the worker thread.
+    // Overruns are reported to the worker fail handler (see set_on_fail), health is Degraded until an action completes in time
+    pub fn execute_action_with_timeout(
+        &self,
+        name: &str,
+        action: Value,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (sender, receiver) = mpsc::channel();
+        let action_name = String::from(name);
+        let watchdog = self.watchdog.clone();
+
+        self.post(move |component| {
+            let start = Instant::now();
+            let _ = sender.send(ActionMessage::Started(start));
+
+            let result = component
+                .execute_action(&action_name, action)
+                .map_err(ComponentCallError::from);
+            let elapsed = start.elapsed();
+
+            // the result is sent under the lock: the caller either receives it or marks the action as running
+            let mut watchdog = watchdog.lock().unwrap();
+            if watchdog.running || elapsed > timeout {
+                watchdog.running = false;
+                watchdog.overrun.get_or_insert_with(|| {
+                    ActionTimeoutError::new(&action_name, elapsed, timeout).to_string()
+                });
+            } else {
+                *watchdog = Watchdog::default();
+            }
+
+            let _ = sender.send(ActionMessage::Done(result, elapsed));
+        })?;
+
+        let start = match receiver.recv() {
+            Ok(ActionMessage::Started(start)) => start,
+            _ => return Err(Box::new(WorkerStoppedError)),
+        };
+
+        let message = match receiver.recv_timeout(timeout.saturating_sub(start.elapsed())) {
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                let mut watchdog = self.watchdog.lock().unwrap();
+
+                match receiver.try_recv() {
+                    Err(mpsc::TryRecvError::Empty) => {
+                        let err = ActionTimeoutError::new(name, start.elapsed(), timeout);
+                        watchdog.overrun = Some(err.to_string());
+                        watchdog.running = true;
+                        drop(watchdog);
+
+                        self.report_overrun(&err);
+                        return Err(Box::new(err));
+                    }
+                    message => message.ok(),
+                }
+            }
+            message => message.ok(),
+        };
+
+        match message {
+            Some(ActionMessage::Done(_, elapsed)) if elapsed > timeout => {
+                let err = ActionTimeoutError::new(name, elapsed, timeout);
+                self.report_overrun(&err);
+                Err(Box::new(err))
+            }
+            Some(ActionMessage::Done(result, _)) => Ok(result?),
+            // the sender is dropped without result if the action panics
+            _ => Err(Box::new(WorkerStoppedError)),
+        }
+    }
+
+    fn report_overrun(&self, err: &ActionTimeoutError) {
+        match self.fail_handler.lock().unwrap().as_ref() {
+            Some(handler) => handler(&FailContext::Action(String::from(err.name())), err),
+            None => {
+                log::warn!(target: "mylife:home:core:plugin-runtime:worker", "action overrun, no fail handler set: {err}");
+            }
+        }
+    }
+}
+
+impl Drop for ComponentWorker {
+    // pending commands are run, then the component is dropped on its thread.
+    // Not joined while an action overruns, it may never return: the thread is detached and exits once the action returns
+    fn drop(&mut self) {
+        self.sender.take();
+
+        if self.watchdog.lock().unwrap().running {
+            return;
+        }
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// Send + Sync handle over a worker, eg: shared by the request handlers of a web API (clone it, no Mutex needed).
+// Calls are serialized by the worker thread, plugins need no extra bound: their Rc/RefCell never leave it.
+// Handlers must be Send, they are moved to the worker thread and called there
+#[derive(Clone)]
+pub struct SyncComponent {
+    id: Arc<str>,
+    worker: Arc<ComponentWorker>,
+}
+
+impl SyncComponent {
+    pub fn spawn<F>(create: F) -> Result<Self, WorkerStoppedError>
+    where
+        F: FnOnce() -> Box<dyn MylifeComponent> + Send + 'static,
+    {
+        let worker = ComponentWorker::spawn(create);
+        let id: String = worker.call(|component| String::from(component.id()))?;
+
+        Ok(SyncComponent {
+            id: Arc::from(id),
+            worker: Arc::new(worker),
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn state(&self) -> Result<ComponentState, WorkerStoppedError> {
+        self.worker.call(|component| component.state())
+    }
+
+    pub fn describe(&self) -> Result<ComponentSnapshot, WorkerStoppedError> {
+        self.worker.call(|component| component.describe())
+    }
+
+    // see ComponentWorker::health
+    pub fn health(&self) -> Result<Health, WorkerStoppedError> {
+        self.worker.health()
+    }
+
+    pub fn get_state(&self, name: &str) -> Result<Value, ComponentCallError> {
+        let name = String::from(name);
+        self.worker
+            .call(move |component| component.get_state(&name).map_err(ComponentCallError::from))?
+    }
+
+    pub fn configure(&self, config: Config) -> Result<(), ComponentCallError> {
+        self.worker.call(move |component| {
+            component
+                .configure(&config)
+                .map_err(ComponentCallError::from)
+        })?
+    }
+
+    pub fn init(&self) -> Result<(), ComponentCallError> {
+        self.worker
+            .call(|component| component.init().map_err(ComponentCallError::from))?
+    }
+
+    pub fn terminate(&self) -> Result<(), ComponentCallError> {
+        self.worker
+            .call(|component| component.terminate().map_err(ComponentCallError::from))?
+    }
+
+    pub fn execute_action(
+        &self,
+        name: &str,
+        action: Value,
+    ) -> Result<Option<Value>, ComponentCallError> {
+        let name = String::from(name);
+        self.worker.call(move |component| {
+            component
+                .execute_action_with_result(&name, action)
+                .map_err(ComponentCallError::from)
+        })?
+    }
+
+    pub fn set_on_state<F>(&self, handler: F) -> Result<(), WorkerStoppedError>
+    where
+        F: Fn(/*name:*/ &str, /*value:*/ Value) + Send + 'static,
+    {
+        self.worker
+            .post(move |component| component.set_on_state(Box::new(handler)))
+    }
+
+    // anything else, see ComponentWorker::call
+    pub fn call<F, R>(&self, command: F) -> Result<R, WorkerStoppedError>
+    where
+        F: FnOnce(&mut dyn MylifeComponent) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        self.worker.call(command)
+    }
+}
+
+// Box<dyn Error> is not Send: the error is carried as its message
+#[derive(Debug, Clone)]
+pub struct ComponentCallError {
+    message: String,
+}
+
+impl From<Box<dyn std::error::Error>> for ComponentCallError {
+    fn from(err: Box<dyn std::error::Error>) -> Self {
+        ComponentCallError {
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<WorkerStoppedError> for ComponentCallError {
+    fn from(err: WorkerStoppedError) -> Self {
+        ComponentCallError {
+            message: err.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ComponentCallError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ComponentCallError {}
+
+// the worker thread exited (eg: a command panicked), the component is gone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkerStoppedError;
+
+impl fmt::Display for WorkerStoppedError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Component worker thread exited")
+    }
+}
+
+impl std::error::Error for WorkerStoppedError {}
@@ -1,8 +1,7 @@
 use std::alloc::System;
 
 use core_plugin_runtime::runtime::{Config, ConfigValue, Value};
-
-mod modules;
+use mylife_home_core::modules;
 
 #[global_allocator]
 static ALLOCATOR: System = System;
@@ -13,15 +12,28 @@ static ALLOCATOR: System = System;
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     pretty_env_logger::init();
 
-    modules::init("target/debug")?;
+    let mut core = modules::Core::load("target/debug");
+
+    for err in core.load_errors() {
+        println!("load error: {}", err);
+    }
+
+    for module in core.modules() {
+        println!("module: {} v{}", module.name(), module.version());
+    }
 
-    let mut component = modules::repository()
-        .get("logic-base.value-binary")
-        .unwrap()
-        .create_component("comp-id");
+    for plugin in core.plugins() {
+        println!("plugin: {}", plugin.id());
+    }
+
+    for issue in core.validate_all() {
+        println!("validation: {}", issue);
+    }
+
+    let component = core.create_component("logic-base.value-binary", "comp-id")?;
 
     component.set_on_state(Box::new(|name: &str, value: Value| {
-        println!("STATE: {} = {:?}", name, value);
+        println!("STATE: {} = {}", name, value);
     }));
 
     let mut config = Config::new();
@@ -33,7 +45,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("init");
     component.init()?;
     println!(
-        "after init: state = {:?}",
+        "after init: state = {}",
         component.get_state("state").expect("could not get state")
     );
 
@@ -43,5 +55,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("execute_action off");
     component.execute_action("off", Value::Bool(true))?;
 
+    println!("terminate");
+    core.remove_component("comp-id")?;
+
     Ok(())
 }
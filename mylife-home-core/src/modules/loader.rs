@@ -1,24 +1,53 @@
 use core_plugin_runtime::{
-  metadata::PluginMetadata, runtime::MylifeComponent, ModuleDeclaration, PluginRegistry,
+  metadata::PluginMetadata,
+  runtime::{MylifeComponent, TypedComponent},
+  ModuleDeclaration, ModuleMetadataDeclaration, MylifePlugin, PluginRegistry, RegistrationError,
 };
 use libloading::Library;
-use log::{debug, trace};
+use log::{debug, trace, warn};
 use regex::Regex;
-use std::{collections::HashMap, fmt, fs::read_dir, path::PathBuf, sync::Arc};
+use std::{
+  any::TypeId,
+  collections::{hash_map::Entry, HashMap},
+  fmt,
+  fs::{self, read_dir, OpenOptions},
+  io::Write,
+  path::{Path, PathBuf},
+  process,
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+  },
+};
 
 const LOG_TARGET: &str = "mylife:home:core:modules";
+const DECLARATION_SYMBOL: &str = "mylife_home_core_module_declaration";
+const METADATA_SYMBOL: &str = "mylife_home_core_module_metadata";
 
 struct PluginRegistryImpl<'registry> {
   module: Arc<Module>,
-  plugins: &'registry mut HashMap<String, Arc<Plugin>>,
+  plugins: &'registry mut Vec<Arc<Plugin>>,
+  errors: Vec<RegistrationError>,
 }
 
 impl<'registry> PluginRegistryImpl<'registry> {
   fn new(
       module: Arc<Module>,
-      plugins: &'registry mut HashMap<String, Arc<Plugin>>,
+      plugins: &'registry mut Vec<Arc<Plugin>>,
   ) -> PluginRegistryImpl<'registry> {
-      PluginRegistryImpl { module, plugins }
+      PluginRegistryImpl { module, plugins, errors: Vec::new() }
+  }
+
+  fn validate(&self, plugin: &Plugin) -> Result<(), RegistrationError> {
+      if plugin.metadata().name().is_empty() {
+          return Err(RegistrationError::new(plugin.id(), "empty plugin name"));
+      }
+
+      if self.plugins.iter().any(|other| other.id() == plugin.id()) {
+          return Err(RegistrationError::new(plugin.id(), "duplicate plugin id in module"));
+      }
+
+      Ok(())
   }
 }
 
@@ -26,9 +55,14 @@ impl PluginRegistry for PluginRegistryImpl<'_> {
   fn register_plugin(
       &mut self,
       plugin: Box<dyn core_plugin_runtime::runtime::MylifePluginRuntime>,
-  ) {
+  ) -> Result<(), RegistrationError> {
       let plugin = Arc::new(Plugin::new(self.module.clone(), plugin));
 
+      if let Err(err) = self.validate(&plugin) {
+          self.errors.push(err.clone());
+          return Err(err);
+      }
+
       debug!(
           target: LOG_TARGET,
           "Plugin loaded: {} v{}",
@@ -42,28 +76,74 @@ impl PluginRegistry for PluginRegistryImpl<'_> {
           plugin.metadata()
       );
 
-      let id = String::from(plugin.id());
+      // Note: cannot be checked by the derive, actions are declared in a separate impl
+      let metadata = plugin.metadata();
+      if metadata.members().is_empty() && metadata.config().is_empty() && !metadata.accepts_extra_config() {
+          warn!(
+              target: LOG_TARGET,
+              "Plugin '{}' has no state, action or config, are mylife attributes missing?",
+              plugin.id()
+          );
+      }
+
+      self.plugins.push(plugin);
+
+      Ok(())
+  }
+}
+
+// Library access, behind a trait so the loading logic can run without a shared object
+pub trait ModuleLoader: Send + Sync {
+  fn declaration(&self, symbol: &str) -> Result<ModuleDeclaration, ModuleLoadError>;
+  // optional symbol, see export_module_metadata
+  fn metadata_declaration(&self, symbol: &str) -> Option<ModuleMetadataDeclaration>;
+}
+
+impl ModuleLoader for Library {
+  // the library opened fine, so a lookup failure means the symbol is not exported
+  fn declaration(&self, symbol: &str) -> Result<ModuleDeclaration, ModuleLoadError> {
+      let declaration = unsafe { self.get::<*const ModuleDeclaration>(symbol.as_bytes()) };
+
+      match declaration {
+          Ok(declaration) => Ok(unsafe { declaration.read() }),
+          Err(err) => {
+              trace!(target: LOG_TARGET, "Symbol '{}' lookup failed: {}", symbol, err);
+              Err(ModuleLoadError::MissingDeclaration(String::from(symbol)))
+          }
+      }
+  }
 
-      if let Some(other) = self.plugins.insert(id, plugin) {
-          panic!("Plugin id duplicate '{}'", other.id());
+  fn metadata_declaration(&self, symbol: &str) -> Option<ModuleMetadataDeclaration> {
+      unsafe {
+          self.get::<*const ModuleMetadataDeclaration>(symbol.as_bytes())
+              .ok()
+              .map(|symbol| symbol.read())
       }
   }
 }
 
-struct Module {
-  _library: Library,
+pub struct Module {
+  _library: Box<dyn ModuleLoader>,
   name: String,
+  library_name: String,
   version: String,
+  description: Option<String>,
+  author: Option<String>,
+  homepage: Option<String>,
 }
 
 impl Module {
-  fn new(library: Library, base_name: &str, version: &str) -> Arc<Self> {
+  fn new(library: Box<dyn ModuleLoader>, base_name: &str, declaration: &ModuleDeclaration) -> Arc<Self> {
       use convert_case::{Case, Casing};
 
       Arc::new(Module {
           _library: library,
           name: base_name.to_case(Case::Kebab),
-          version: String::from(version),
+          library_name: String::from(base_name),
+          version: String::from(declaration.module_version),
+          description: declaration.description.map(String::from),
+          author: declaration.author.map(String::from),
+          homepage: declaration.homepage.map(String::from),
       })
   }
 
@@ -71,9 +151,132 @@ impl Module {
       &self.name
   }
 
+  // name as found in library file name, before casing
+  pub fn library_name(&self) -> &str {
+      &self.library_name
+  }
+
   pub fn version(&self) -> &str {
       &self.version
   }
+
+  pub fn description(&self) -> Option<&str> {
+      self.description.as_deref()
+  }
+
+  pub fn author(&self) -> Option<&str> {
+      self.author.as_deref()
+  }
+
+  pub fn homepage(&self) -> Option<&str> {
+      self.homepage.as_deref()
+  }
+
+  // Catalog: reads the module and plugins metadata without registering the plugins.
+  // The library is still opened (the declaration is a symbol), but register only runs
+  // as a fallback, for modules which do not export_module_metadata
+  pub fn inspect(file_path: &Path) -> Result<ModuleInfo, ModuleLoadError> {
+      let name_match = Regex::new(&library_file_name("(.*)")).unwrap();
+      let file_name = file_path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+      let name = match name_match.captures(&file_name) {
+          Some(matchs) => String::from(&matchs[1]),
+          None => return Err(ModuleLoadError::Library(format!("Not a module file name: '{}'", file_name))),
+      };
+
+      let library: Box<dyn ModuleLoader> = Box::new(
+          unsafe { Library::new(file_path) }.map_err(|err| ModuleLoadError::Library(err.to_string()))?,
+      );
+
+      let module_declaration = library.declaration(DECLARATION_SYMBOL)?;
+
+      check_versions(&module_declaration, LoadOptions::default())?;
+
+      let metadata_declaration = library.metadata_declaration(METADATA_SYMBOL);
+
+      let plugins = match metadata_declaration {
+          Some(metadata_declaration) => (metadata_declaration.plugins)()
+              .iter()
+              .map(|json| serde_json::from_str(json))
+              .collect::<Result<Vec<_>, _>>()
+              .map_err(|err| ModuleLoadError::Library(format!("Invalid module metadata: {}", err)))?,
+          None => {
+              debug!(
+                  target: LOG_TARGET,
+                  "Module '{}' does not export its metadata, loading it",
+                  name
+              );
+
+              load_with_symbol(file_path, &name, DECLARATION_SYMBOL)?
+                  .iter()
+                  .map(|plugin| plugin.metadata().to_json())
+                  .collect()
+          }
+      };
+
+      let module = Module::new(library, &name, &module_declaration);
+
+      Ok(ModuleInfo {
+          name: String::from(module.name()),
+          version: String::from(module.version()),
+          description: module.description().map(String::from),
+          author: module.author().map(String::from),
+          homepage: module.homepage().map(String::from),
+          plugins,
+      })
+  }
+
+  // Same as regular loading, with checks relaxed by the host (see LoadOptions)
+  pub fn load_with_options(file_path: &Path, name: &str, options: LoadOptions) -> Result<Vec<Arc<Plugin>>, ModuleLoadError> {
+      let library = unsafe { Library::new(file_path) }
+          .map_err(|err| ModuleLoadError::Library(err.to_string()))?;
+
+      load_with_loader(Box::new(library), name, DECLARATION_SYMBOL, options)
+  }
+}
+
+// Strict by default. Each option is an opt-in, at the host own risk
+#[derive(Debug, Copy, Clone, Default)]
+pub struct LoadOptions {
+  // the host verified that both compilers produce the same ABI (eg: two patch versions):
+  // a rustc version mismatch is only logged
+  pub allow_rustc_mismatch: bool,
+}
+
+// Module description which does not keep the library loaded
+#[derive(Debug, Clone)]
+pub struct ModuleInfo {
+  name: String,
+  version: String,
+  description: Option<String>,
+  author: Option<String>,
+  homepage: Option<String>,
+  plugins: Vec<serde_json::Value>, // PluginMetadata::to_json
+}
+
+impl ModuleInfo {
+  pub fn name(&self) -> &str {
+      &self.name
+  }
+
+  pub fn version(&self) -> &str {
+      &self.version
+  }
+
+  pub fn description(&self) -> Option<&str> {
+      self.description.as_deref()
+  }
+
+  pub fn author(&self) -> Option<&str> {
+      self.author.as_deref()
+  }
+
+  pub fn homepage(&self) -> Option<&str> {
+      self.homepage.as_deref()
+  }
+
+  pub fn plugins(&self) -> &[serde_json::Value] {
+      &self.plugins
+  }
 }
 
 pub struct Plugin {
@@ -87,7 +290,11 @@ impl Plugin {
       module: Arc<Module>,
       runtime: Box<dyn core_plugin_runtime::runtime::MylifePluginRuntime>,
   ) -> Plugin {
-      let id = format!("{}.{}", module.name(), runtime.metadata().name());
+      // explicit id survives module file renames
+      let id = match runtime.metadata().id() {
+          Some(id) => String::from(id),
+          None => format!("{}.{}", module.name(), runtime.metadata().name()),
+      };
 
       Plugin {
           module,
@@ -100,38 +307,127 @@ impl Plugin {
       &self.id
   }
 
+  // the plugin own version if declared, the module version otherwise
   pub fn version(&self) -> &str {
-      &self.module.version()
+      self.metadata().version().unwrap_or(self.module.version())
+  }
+
+  // eg: persisted components, which must know the plugin schema they were built against
+  pub fn versioned_id(&self) -> String {
+      format!("{}@{}", self.id, self.version())
   }
 
   pub fn metadata(&self) -> &PluginMetadata {
       self.runtime.metadata()
   }
 
+  // Catalog entry: the metadata alone is ambiguous when two modules declare plugins with the same name
+  pub fn to_json(&self) -> serde_json::Value {
+      serde_json::json!({
+          "id": self.id(),
+          "version": self.version(),
+          "module": {
+              "name": self.module.name(),
+              "version": self.module.version(),
+          },
+          "metadata": self.metadata().to_json(),
+      })
+  }
+
+  pub fn module(&self) -> &Arc<Module> {
+      &self.module
+  }
+
   pub fn create_component(&self, id: &str) -> Box<dyn MylifeComponent> {
       self.runtime.create(id)
   }
+
+  // faster than create_component for many components of this plugin
+  pub fn create_components(&self, ids: &[&str]) -> Vec<Box<dyn MylifeComponent>> {
+      self.runtime.create_components(ids)
+  }
+
+  // None if the plugin is not implemented by PluginType (eg: plugin crate linked statically)
+  pub fn create_typed<PluginType: MylifePlugin + 'static>(&self, id: &str) -> Option<TypedComponent<PluginType>> {
+      if self.runtime.plugin_type_id() != Some(TypeId::of::<PluginType>()) {
+          return None;
+      }
+
+      TypedComponent::new(self.runtime.create(id)).ok()
+  }
+
+  // the plugin runs without its device
+  pub fn create_simulated_component(&self, id: &str) -> Box<dyn MylifeComponent> {
+      let mut component = self.runtime.create(id);
+      component
+          .set_simulated(true)
+          .expect("New component can be simulated");
+      component
+  }
+}
+
+// A bad module or a duplicate plugin id does not prevent loading the others, errors are returned alongside the plugins
+pub fn load_modules(module_path: &str) -> (HashMap<String, Arc<Plugin>>, Vec<ModuleLoadError>) {
+  collect_plugins(load_all(Path::new(module_path)))
 }
 
-pub fn load_modules(
-  module_path: &str,
-) -> Result<HashMap<String, Arc<Plugin>>, Box<dyn std::error::Error>> {
+// On duplicate id, the first loaded plugin is kept
+fn collect_plugins(
+  results: Vec<Result<Vec<Arc<Plugin>>, ModuleLoadError>>,
+) -> (HashMap<String, Arc<Plugin>>, Vec<ModuleLoadError>) {
   let mut plugins: HashMap<String, Arc<Plugin>> = HashMap::new();
-  let name_match = Regex::new(&format!(
-      "{}{}(.*){}",
-      std::env::consts::DLL_PREFIX,
-      "plugin_",
-      std::env::consts::DLL_SUFFIX
-  ))
-  .unwrap();
+  let mut errors = Vec::new();
+
+  for result in results {
+      let loaded = match result {
+          Ok(loaded) => loaded,
+          Err(err) => {
+              errors.push(err);
+              continue;
+          }
+      };
+
+      for plugin in loaded {
+          match plugins.entry(String::from(plugin.id())) {
+              Entry::Occupied(_) => errors.push(ModuleLoadError::DuplicatePlugin {
+                  id: String::from(plugin.id()),
+                  module: String::from(plugin.module().name()),
+              }),
+              Entry::Vacant(entry) => {
+                  entry.insert(plugin);
+              }
+          }
+      }
+  }
+
+  (plugins, errors)
+}
+
+// Load every module found in the directory, one result per module so a bad module does not prevent loading the others
+pub fn load_all(module_path: &Path) -> Vec<Result<Vec<Arc<Plugin>>, ModuleLoadError>> {
+  let name_match = Regex::new(&library_file_name("(.*)")).unwrap();
+
+  let entries = match read_dir(module_path) {
+      Ok(entries) => entries,
+      Err(err) => return vec![Err(ModuleLoadError::Io(err.to_string()))],
+  };
+
+  let mut results = Vec::new();
+
+  for entry in entries {
+      let entry = match entry {
+          Ok(entry) => entry,
+          Err(err) => {
+              results.push(Err(ModuleLoadError::Io(err.to_string())));
+              continue;
+          }
+      };
 
-  for path in read_dir(module_path)? {
-      let entry = path?;
       let file_name = String::from(entry.file_name().to_string_lossy());
       if let Some(matchs) = name_match.captures(&file_name) {
           if matchs.len() == 2 {
               let name = &matchs[1];
-              load_module(entry.path(), name, &mut plugins)?;
+              results.push(load_module(entry.path(), name));
               continue;
           }
       }
@@ -143,69 +439,203 @@ pub fn load_modules(
       );
   }
 
-  Ok(plugins)
+  results
+}
+
+// dlopen needs a path: the bytes are written to a temporary file which is removed once loaded (or on failure)
+pub fn load_from_bytes(bytes: &[u8], name: &str) -> Result<Vec<Arc<Plugin>>, ModuleLoadError> {
+  static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+  let file_path = std::env::temp_dir().join(format!(
+      "mylife-home-{}-{}-{}",
+      process::id(),
+      COUNTER.fetch_add(1, Ordering::SeqCst),
+      library_file_name(name)
+  ));
+
+  let temp_file = TempFile::create(file_path, bytes)
+      .map_err(|err| ModuleLoadError::Io(err.to_string()))?;
+
+  load_module(temp_file.path.clone(), name)
+}
+
+struct TempFile {
+  path: PathBuf,
+}
+
+impl TempFile {
+  fn create(path: PathBuf, bytes: &[u8]) -> std::io::Result<Self> {
+      let mut options = OpenOptions::new();
+      options.write(true).create_new(true);
+
+      #[cfg(unix)]
+      {
+          use std::os::unix::fs::OpenOptionsExt;
+          options.mode(0o600);
+      }
+
+      let mut file = options.open(&path)?;
+      // from there the file is removed on drop, even on write failure
+      let temp_file = TempFile { path };
+      file.write_all(bytes)?;
+
+      Ok(temp_file)
+  }
+}
+
+impl Drop for TempFile {
+  fn drop(&mut self) {
+      let _ = fs::remove_file(&self.path);
+  }
+}
+
+pub fn library_file_name(library_name: &str) -> String {
+  format!(
+      "{}{}{}{}",
+      std::env::consts::DLL_PREFIX,
+      "plugin_",
+      library_name,
+      std::env::consts::DLL_SUFFIX
+  )
 }
 
-fn load_module(
-  file_path: PathBuf,
+fn load_module(file_path: PathBuf, name: &str) -> Result<Vec<Arc<Plugin>>, ModuleLoadError> {
+  load_with_symbol(&file_path, name, DECLARATION_SYMBOL)
+}
+
+// Same as regular loading, with a custom module declaration export symbol
+pub fn load_with_symbol(
+  file_path: &Path,
   name: &str,
-  plugins: &mut HashMap<String, Arc<Plugin>>,
-) -> Result<(), Box<dyn std::error::Error>> {
+  symbol: &str,
+) -> Result<Vec<Arc<Plugin>>, ModuleLoadError> {
   trace!(
       target: LOG_TARGET,
-      "Opening module from path '{}'",
-      file_path.display()
+      "Opening module from path '{}' (symbol: '{}')",
+      file_path.display(),
+      symbol
   );
 
-  let library = unsafe { Library::new(file_path)? };
+  let library = unsafe { Library::new(file_path) }
+      .map_err(|err| ModuleLoadError::Library(err.to_string()))?;
 
-  let module_declaration = unsafe {
-      library
-          .get::<*const ModuleDeclaration>(b"mylife_home_core_module_declaration\0")?
-          .read()
-  };
+  load_with_loader(Box::new(library), name, symbol, LoadOptions::default())
+}
+
+fn load_with_loader(
+  library: Box<dyn ModuleLoader>,
+  name: &str,
+  symbol: &str,
+  options: LoadOptions,
+) -> Result<Vec<Arc<Plugin>>, ModuleLoadError> {
+  let module_declaration = library.declaration(symbol)?;
 
-  if module_declaration.rustc_version != core_plugin_runtime::RUSTC_VERSION {
-      return Err(Box::new(ModuleLoadError::RustCompilerVersionMismatch(
-          module_declaration.rustc_version.into(),
-          core_plugin_runtime::RUSTC_VERSION.into(),
-      )));
-  } else if module_declaration.core_version != core_plugin_runtime::CORE_VERSION {
-      return Err(Box::new(ModuleLoadError::CoreVersionMismatch(
-          module_declaration.core_version.into(),
-          core_plugin_runtime::CORE_VERSION.into(),
-      )));
-  } else if module_declaration.mylife_runtime_version
-      != core_plugin_runtime::MYLIFE_RUNTIME_VERSION
-  {
-      return Err(Box::new(ModuleLoadError::MylifeRuntimeVersionMismatch(
-          module_declaration.mylife_runtime_version.into(),
-          core_plugin_runtime::MYLIFE_RUNTIME_VERSION.into(),
-      )));
-  }
-
-  let module = Module::new(library, name, module_declaration.module_version);
+  check_versions(&module_declaration, options)?;
+
+  let module = Module::new(library, name, &module_declaration);
 
   debug!(
       target: LOG_TARGET,
-      "Loading module '{}' v{}",
+      "Loading module '{}' v{} (file: '{}')",
+      module.name(),
+      module.version(),
+      library_file_name(module.library_name())
+  );
+
+  trace!(
+      target: LOG_TARGET,
+      "Module '{}' description: {:?}, author: {:?}, homepage: {:?}",
       module.name(),
-      module.version()
+      module.description(),
+      module.author(),
+      module.homepage()
   );
 
   let register = module_declaration.register;
 
-  let mut registry = PluginRegistryImpl::new(module, plugins);
+  let mut plugins = Vec::new();
+  let mut registry = PluginRegistryImpl::new(module.clone(), &mut plugins);
   register(&mut registry);
 
+  // the module still contributes its valid plugins
+  for err in registry.errors.iter() {
+      warn!(
+          target: LOG_TARGET,
+          "Module '{}': {}",
+          module.name(),
+          err
+      );
+  }
+
+  Ok(plugins)
+}
+
+fn check_versions(module_declaration: &ModuleDeclaration, options: LoadOptions) -> Result<(), ModuleLoadError> {
+  let versions = [
+      (VersionKind::RustCompiler, module_declaration.rustc_version, core_plugin_runtime::RUSTC_VERSION),
+      (VersionKind::Core, module_declaration.core_version, core_plugin_runtime::CORE_VERSION),
+      (
+          VersionKind::MylifeRuntime,
+          module_declaration.mylife_runtime_version,
+          core_plugin_runtime::MYLIFE_RUNTIME_VERSION,
+      ),
+  ];
+
+  for (kind, module, core) in versions {
+      if module != core {
+          if kind == VersionKind::RustCompiler && options.allow_rustc_mismatch {
+              warn!(
+                  target: LOG_TARGET,
+                  "Rust compiler version mismatch allowed by load options: module='{}', core='{}'",
+                  module,
+                  core
+              );
+              continue;
+          }
+
+          return Err(ModuleLoadError::VersionMismatch {
+              kind,
+              module: module.into(),
+              core: core.into(),
+          });
+      }
+  }
+
   Ok(())
 }
 
+// Which version check failed, eg: a host may tolerate a core mismatch but never a compiler mismatch
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VersionKind {
+  RustCompiler,
+  Core,
+  MylifeRuntime,
+}
+
+impl fmt::Display for VersionKind {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+      match self {
+          VersionKind::RustCompiler => write!(fmt, "Rust compiler"),
+          VersionKind::Core => write!(fmt, "Rust core"),
+          VersionKind::MylifeRuntime => write!(fmt, "Mylife runtime"),
+      }
+  }
+}
+
 #[derive(Debug, Clone)]
 pub enum ModuleLoadError {
-  RustCompilerVersionMismatch(String, String),
-  CoreVersionMismatch(String, String),
-  MylifeRuntimeVersionMismatch(String, String),
+  VersionMismatch {
+      kind: VersionKind,
+      module: String,
+      core: String,
+  },
+  Library(String),
+  MissingDeclaration(String), // symbol name
+  Io(String),
+  DuplicatePlugin {
+      id: String,
+      module: String, // module of the rejected plugin
+  },
 }
 
 impl std::error::Error for ModuleLoadError {}
@@ -213,21 +643,176 @@ impl std::error::Error for ModuleLoadError {}
 impl fmt::Display for ModuleLoadError {
   fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
       match self {
-          ModuleLoadError::RustCompilerVersionMismatch(module_version, core_version) => write!(
+          ModuleLoadError::VersionMismatch { kind, module, core } => write!(
               fmt,
-              "Rust compiler version mismatch: module='{}', core='{}'",
-              module_version, core_version
+              "{} version mismatch: module='{}', core='{}'",
+              kind, module, core
           ),
-          ModuleLoadError::CoreVersionMismatch(module_version, core_version) => write!(
+          ModuleLoadError::Library(message) => write!(fmt, "Could not load library: {}", message),
+          ModuleLoadError::MissingDeclaration(symbol) => write!(
               fmt,
-              "Rust core version mismatch: module='{}', core='{}'",
-              module_version, core_version
+              "Module declaration symbol '{}' not found: the module must call core_plugin_runtime::export_module!(register) in its lib.rs",
+              symbol
           ),
-          ModuleLoadError::MylifeRuntimeVersionMismatch(module_version, core_version) => write!(
+          ModuleLoadError::Io(message) => write!(fmt, "I/O error: {}", message),
+          ModuleLoadError::DuplicatePlugin { id, module } => write!(
               fmt,
-              "Mylife runtime version mismatch: module='{}', core='{}'",
-              module_version, core_version
+              "Plugin id '{}' of module '{}' is already registered by another module, plugin ignored",
+              id, module
           ),
       }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use core_plugin_runtime::{
+      metadata::{PluginMetadataBuilder, PluginUsage, Type},
+      runtime::MylifePluginRuntime,
+  };
+
+  // canned declaration, None behaves as a library which does not export the symbol
+  struct TestLoader(Option<fn() -> ModuleDeclaration>);
+
+  impl ModuleLoader for TestLoader {
+      fn declaration(&self, symbol: &str) -> Result<ModuleDeclaration, ModuleLoadError> {
+          self.0
+              .map(|declaration| declaration())
+              .ok_or_else(|| ModuleLoadError::MissingDeclaration(String::from(symbol)))
+      }
+
+      fn metadata_declaration(&self, _symbol: &str) -> Option<ModuleMetadataDeclaration> {
+          None
+      }
+  }
+
+  fn declaration() -> ModuleDeclaration {
+      ModuleDeclaration {
+          rustc_version: core_plugin_runtime::RUSTC_VERSION,
+          core_version: core_plugin_runtime::CORE_VERSION,
+          mylife_runtime_version: core_plugin_runtime::MYLIFE_RUNTIME_VERSION,
+          module_version: "1.0.0",
+          description: None,
+          author: None,
+          homepage: None,
+          register: |_registry| {},
+      }
+  }
+
+  struct TestRuntime(PluginMetadata);
+
+  impl MylifePluginRuntime for TestRuntime {
+      fn metadata(&self) -> &PluginMetadata {
+          &self.0
+      }
+
+      fn create(&self, _id: &str) -> Box<dyn MylifeComponent> {
+          panic!("not used by this test")
+      }
+  }
+
+  fn register(registry: &mut dyn PluginRegistry) {
+      let metadata = PluginMetadataBuilder::new()
+          .name("test-plugin")
+          .usage(PluginUsage::Logic)
+          .add_state("value", None, Type::Bool)
+          .build()
+          .unwrap();
+
+      registry.register_plugin(Box::new(TestRuntime(metadata))).unwrap();
+  }
+
+  fn load(loader: TestLoader) -> Result<Vec<Arc<Plugin>>, ModuleLoadError> {
+      load_with_loader(Box::new(loader), "test_module", DECLARATION_SYMBOL, LoadOptions::default())
+  }
+
+  #[test]
+  fn test_load() {
+      assert!(load(TestLoader(Some(declaration))).unwrap().is_empty());
+  }
+
+  #[test]
+  fn test_version_mismatch() {
+      let result = load(TestLoader(Some(|| ModuleDeclaration {
+          rustc_version: "0.0.0",
+          ..declaration()
+      })));
+      assert!(matches!(
+          result,
+          Err(ModuleLoadError::VersionMismatch { kind: VersionKind::RustCompiler, module, .. }) if module == "0.0.0"
+      ));
+
+      let result = load(TestLoader(Some(|| ModuleDeclaration {
+          core_version: "0.0.0",
+          ..declaration()
+      })));
+      assert!(matches!(
+          result,
+          Err(ModuleLoadError::VersionMismatch { kind: VersionKind::Core, module, .. }) if module == "0.0.0"
+      ));
+  }
+
+  #[test]
+  fn test_allow_rustc_mismatch() {
+      let options = LoadOptions {
+          allow_rustc_mismatch: true,
+      };
+
+      let loader = TestLoader(Some(|| ModuleDeclaration {
+          rustc_version: "0.0.0",
+          ..declaration()
+      }));
+      assert!(load_with_loader(Box::new(loader), "test_module", DECLARATION_SYMBOL, options).is_ok());
+
+      // only the compiler check is relaxed
+      let loader = TestLoader(Some(|| ModuleDeclaration {
+          rustc_version: "0.0.0",
+          core_version: "0.0.0",
+          ..declaration()
+      }));
+      let result = load_with_loader(Box::new(loader), "test_module", DECLARATION_SYMBOL, options);
+      assert!(matches!(result, Err(ModuleLoadError::VersionMismatch { kind: VersionKind::Core, .. })));
+  }
+
+  #[test]
+  fn test_plugin_to_json() {
+      let plugins = load(TestLoader(Some(|| ModuleDeclaration {
+          register,
+          ..declaration()
+      })))
+      .unwrap();
+
+      let json = plugins[0].to_json();
+      assert_eq!(json["id"], "test-module.test-plugin");
+      assert_eq!(json["version"], "1.0.0");
+      assert_eq!(json["module"], serde_json::json!({ "name": "test-module", "version": "1.0.0" }));
+      assert_eq!(json["metadata"]["name"], "test-plugin");
+  }
+
+  #[test]
+  fn test_collect_plugins() {
+      let loader = || TestLoader(Some(|| ModuleDeclaration {
+          register,
+          ..declaration()
+      }));
+
+      let (plugins, errors) = collect_plugins(vec![
+          load(loader()),
+          load(TestLoader(None)),
+          load(loader()),
+      ]);
+
+      assert_eq!(plugins.len(), 1);
+      assert!(plugins.contains_key("test-module.test-plugin"));
+      assert_eq!(errors.len(), 2);
+      assert!(matches!(&errors[0], ModuleLoadError::MissingDeclaration(_)));
+      assert!(matches!(&errors[1], ModuleLoadError::DuplicatePlugin { id, .. } if id == "test-module.test-plugin"));
+  }
+
+  #[test]
+  fn test_missing_declaration() {
+      let result = load(TestLoader(None));
+      assert!(matches!(result, Err(ModuleLoadError::MissingDeclaration(symbol)) if symbol == DECLARATION_SYMBOL));
+  }
+}
@@ -0,0 +1,426 @@
+use core_plugin_runtime::{
+  encoding,
+  metadata::Capability,
+  runtime::{ComponentState, Config, MylifeComponent, Value},
+};
+use log::{debug, warn};
+use std::{cell::RefCell, collections::HashMap, fmt, fs, path::Path, rc::Rc, sync::Arc};
+
+use super::loader::{load_modules, Module, ModuleLoadError, Plugin};
+
+const LOG_TARGET: &str = "mylife:home:core:modules:registry";
+
+struct ComponentEntry {
+  plugin: Arc<Plugin>,
+  component: Box<dyn MylifeComponent>,
+  // set on first subscribe, with the dispatching state handler
+  subscriptions: Option<Rc<RefCell<Vec<Subscription>>>>,
+}
+
+type SubscriptionCallback = Box<dyn Fn(/*value:*/ Value)>;
+
+struct Subscription {
+  id: SubscriptionId,
+  state: String,
+  callback: SubscriptionCallback,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+// Owns everything loaded in the process: modules -> plugins -> components
+pub struct Core {
+  // Note: components first, so they are dropped before the plugins and modules which hold their code
+  components: HashMap<String, ComponentEntry>,
+  plugins: HashMap<String, Arc<Plugin>>,
+  modules: Vec<Arc<Module>>,
+  // None: everything is granted
+  granted_capabilities: Option<Vec<Capability>>,
+  next_subscription: u64,
+  load_errors: Vec<ModuleLoadError>,
+}
+
+impl Core {
+  // Modules which cannot be loaded are skipped (see load_errors), so one bad module does not prevent startup
+  pub fn load(module_path: &str) -> Core {
+      let (plugins, load_errors) = load_modules(module_path);
+
+      for err in load_errors.iter() {
+          warn!(target: LOG_TARGET, "Module load error: {}", err);
+      }
+
+      // Note: modules without any plugin are not kept
+      let mut modules: Vec<Arc<Module>> = Vec::new();
+      for plugin in plugins.values() {
+          if !modules.iter().any(|module| Arc::ptr_eq(module, plugin.module())) {
+              modules.push(plugin.module().clone());
+          }
+      }
+
+      debug!(
+          target: LOG_TARGET,
+          "Core loaded: {} modules, {} plugins",
+          modules.len(),
+          plugins.len()
+      );
+
+      Core {
+          components: HashMap::new(),
+          plugins,
+          modules,
+          granted_capabilities: None,
+          next_subscription: 0,
+          load_errors,
+      }
+  }
+
+  pub fn load_errors(&self) -> &[ModuleLoadError] {
+      &self.load_errors
+  }
+
+  pub fn modules(&self) -> impl Iterator<Item = &Module> {
+      self.modules.iter().map(|module| module.as_ref())
+  }
+
+  pub fn plugins(&self) -> impl Iterator<Item = &Plugin> {
+      self.plugins.values().map(|plugin| plugin.as_ref())
+  }
+
+  pub fn plugin(&self, id: &str) -> Option<&Plugin> {
+      self.plugins.get(id).map(|plugin| plugin.as_ref())
+  }
+
+  // eg: sandboxed host running untrusted modules
+  pub fn set_granted_capabilities(&mut self, capabilities: &[Capability]) {
+      self.granted_capabilities = Some(capabilities.to_vec());
+  }
+
+  // Fast-fail startup: reports what would only fail once a component is created or used, without creating any.
+  // Metadata/runtime consistency is already checked when a module registers its plugins.
+  // Sorted by plugin id, empty if everything is fine
+  pub fn validate_all(&self) -> Vec<ValidationIssue> {
+      let mut plugins: Vec<&Arc<Plugin>> = self.plugins.values().collect();
+      plugins.sort_by(|a, b| a.id().cmp(b.id()));
+
+      let mut issues = Vec::new();
+
+      for plugin in plugins.iter() {
+          let metadata = plugin.metadata();
+          let mut issue = |message: String| {
+              issues.push(ValidationIssue {
+                  plugin: String::from(plugin.id()),
+                  message,
+              })
+          };
+
+          if let Some(granted) = &self.granted_capabilities {
+              let missing: Vec<&Capability> = metadata
+                  .required_capabilities()
+                  .iter()
+                  .filter(|capability| !granted.contains(capability))
+                  .collect();
+
+              if !missing.is_empty() {
+                  issue(format!("requires capabilities not granted by the host: {:?}", missing));
+              }
+          }
+
+          let mut members: Vec<_> = metadata.members().iter().collect();
+          members.sort_by_key(|(name, _)| *name);
+
+          for (name, member) in members {
+              if let Err(err) = encoding::check_type(member.value_type()) {
+                  issue(format!("member '{}' cannot be sent on the bus: {}", name, err));
+              }
+          }
+
+          // distinct ids, but the UI shows names
+          let others: Vec<&str> = plugins
+              .iter()
+              .filter(|other| other.id() != plugin.id() && other.metadata().name() == metadata.name())
+              .map(|other| other.id())
+              .collect();
+
+          if !others.is_empty() {
+              issue(format!("plugin name '{}' also used by: {}", metadata.name(), others.join(", ")));
+          }
+      }
+
+      debug!(target: LOG_TARGET, "Validated {} plugins: {} issues", plugins.len(), issues.len());
+
+      issues
+  }
+
+  pub fn create_component(
+      &mut self,
+      plugin_id: &str,
+      id: &str,
+  ) -> Result<&mut (dyn MylifeComponent + 'static), CoreError> {
+      let plugin = self
+          .plugins
+          .get(plugin_id)
+          .ok_or_else(|| CoreError::UnknownPlugin(String::from(plugin_id)))?
+          .clone();
+
+      if self.components.contains_key(id) {
+          return Err(CoreError::DuplicateComponent(String::from(id)));
+      }
+
+      if let Some(granted) = &self.granted_capabilities {
+          let missing: Vec<Capability> = plugin
+              .metadata()
+              .required_capabilities()
+              .iter()
+              .filter(|capability| !granted.contains(capability))
+              .copied()
+              .collect();
+
+          if !missing.is_empty() {
+              return Err(CoreError::MissingCapabilities(String::from(plugin_id), missing));
+          }
+      }
+
+      let component = plugin.create_component(id);
+      let entry = self
+          .components
+          .entry(String::from(id))
+          .or_insert(ComponentEntry {
+              plugin,
+              component,
+              subscriptions: None,
+          });
+
+      Ok(entry.component.as_mut())
+  }
+
+  // terminates the component before removing it
+  pub fn remove_component(&mut self, id: &str) -> Result<(), CoreError> {
+      let mut entry = self
+          .components
+          .remove(id)
+          .ok_or_else(|| CoreError::UnknownComponent(String::from(id)))?;
+
+      if let Err(err) = entry.component.terminate() {
+          warn!(target: LOG_TARGET, "Could not terminate component '{}': {}", id, err);
+      }
+
+      Ok(())
+  }
+
+  pub fn component(&self, id: &str) -> Option<&(dyn MylifeComponent + 'static)> {
+      self.components.get(id).map(|entry| entry.component.as_ref())
+  }
+
+  pub fn component_mut(&mut self, id: &str) -> Option<&mut (dyn MylifeComponent + 'static)> {
+      self.components.get_mut(id).map(|entry| entry.component.as_mut())
+  }
+
+  // Lets a component observe another one, eg: a group plugin aggregating its children states.
+  // The callback runs inside the observed component call (action, init...), so it must not call back into the Core.
+  // Lifecycle: a subscription ends with unsubscribe or when the observed component is removed
+  // (the dispatcher only holds a weak reference to the subscriptions of its entry).
+  // The dispatcher is added with add_on_state: a later set_on_state on the observed component drops it.
+  // Changes made in a with_batch call are dispatched once the batch ends, batch handler or not
+  pub fn subscribe(
+      &mut self,
+      component_id: &str,
+      state_name: &str,
+      callback: Box<dyn Fn(Value)>,
+  ) -> Result<SubscriptionId, CoreError> {
+      let entry = self
+          .components
+          .get_mut(component_id)
+          .ok_or_else(|| CoreError::UnknownComponent(String::from(component_id)))?;
+
+      if !entry.component.list_states().contains(&state_name) {
+          return Err(CoreError::UnknownState(String::from(component_id), String::from(state_name)));
+      }
+
+      let subscriptions = match &entry.subscriptions {
+          Some(subscriptions) => subscriptions.clone(),
+          None => {
+              let subscriptions = Rc::new(RefCell::new(Vec::<Subscription>::new()));
+              let dispatched = Rc::downgrade(&subscriptions);
+
+              entry.component.add_on_state(Box::new(move |name, value| {
+                  if let Some(subscriptions) = dispatched.upgrade() {
+                      for subscription in subscriptions.borrow().iter().filter(|subscription| subscription.state == name) {
+                          (subscription.callback)(value.clone());
+                      }
+                  }
+              }));
+
+              entry.subscriptions = Some(subscriptions.clone());
+              subscriptions
+          }
+      };
+
+      let id = SubscriptionId(self.next_subscription);
+      self.next_subscription += 1;
+
+      subscriptions.borrow_mut().push(Subscription {
+          id,
+          state: String::from(state_name),
+          callback,
+      });
+
+      Ok(id)
+  }
+
+  // false if the subscription already ended
+  pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+      for subscriptions in self.components.values().filter_map(|entry| entry.subscriptions.as_ref()) {
+          let mut subscriptions = subscriptions.borrow_mut();
+          if let Some(index) = subscriptions.iter().position(|subscription| subscription.id == id) {
+              subscriptions.remove(index);
+              return true;
+          }
+      }
+
+      false
+  }
+
+  // Operator workflow: the file (see PluginMetadata::config_from_json) is compared to the current config,
+  // then each changed key is applied on its own (apply_config_diff), so a bad key does not block the others
+  pub fn reload_component_config(&mut self, id: &str, path: &Path) -> Result<ConfigReloadReport, CoreError> {
+      let entry = self
+          .components
+          .get_mut(id)
+          .ok_or_else(|| CoreError::UnknownComponent(String::from(id)))?;
+
+      let content = fs::read_to_string(path)
+          .map_err(|err| CoreError::ConfigFile(path.display().to_string(), err.to_string()))?;
+
+      let config = entry
+          .plugin
+          .metadata()
+          .config_from_json(&content)
+          .map_err(|err| CoreError::ConfigFile(path.display().to_string(), err.to_string()))?;
+
+      // describe() redacts secret configs, apply_config_diff compares against the real values
+      let mut names: Vec<&String> = config.keys().collect();
+      names.sort();
+
+      let mut report = ConfigReloadReport::default();
+
+      for name in names {
+          let change = Config::from([(name.clone(), config[name].clone())]);
+          match entry.component.apply_config_diff(&change) {
+              Ok(changed) if changed.is_empty() => report.skipped.push(name.clone()),
+              Ok(_) => report.applied.push(name.clone()),
+              Err(err) => report.errored.push((name.clone(), err.to_string())),
+          }
+      }
+
+      debug!(
+          target: LOG_TARGET,
+          "Component '{}' config reloaded from '{}': {:?}",
+          id,
+          path.display(),
+          report
+      );
+
+      Ok(report)
+  }
+
+  // eg: reconfigure all components of a plugin
+  pub fn plugin_components_mut(
+      &mut self,
+      plugin_id: &str,
+  ) -> impl Iterator<Item = &mut (dyn MylifeComponent + 'static)> {
+      let plugin_id = String::from(plugin_id);
+
+      self.components
+          .values_mut()
+          .filter(move |entry| entry.plugin.id() == plugin_id)
+          .map(|entry| entry.component.as_mut())
+  }
+}
+
+impl Drop for Core {
+  // background tasks must be stopped before the libraries are unloaded
+  fn drop(&mut self) {
+      for (id, entry) in self.components.iter_mut() {
+          if entry.component.state() == ComponentState::Terminated {
+              continue;
+          }
+
+          if let Err(err) = entry.component.terminate() {
+              warn!(target: LOG_TARGET, "Could not terminate component '{}': {}", id, err);
+          }
+      }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct ValidationIssue {
+  plugin: String, // id
+  message: String,
+}
+
+impl ValidationIssue {
+  pub fn plugin(&self) -> &str {
+      &self.plugin
+  }
+
+  pub fn message(&self) -> &str {
+      &self.message
+  }
+}
+
+impl fmt::Display for ValidationIssue {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+      write!(fmt, "Plugin '{}': {}", self.plugin, self.message)
+  }
+}
+
+// Keys of the config file, sorted
+#[derive(Debug, Clone, Default)]
+pub struct ConfigReloadReport {
+  applied: Vec<String>,
+  skipped: Vec<String>,           // same value as the current config
+  errored: Vec<(String, String)>, // name, error
+}
+
+impl ConfigReloadReport {
+  pub fn applied(&self) -> &[String] {
+      &self.applied
+  }
+
+  pub fn skipped(&self) -> &[String] {
+      &self.skipped
+  }
+
+  pub fn errored(&self) -> &[(String, String)] {
+      &self.errored
+  }
+}
+
+#[derive(Debug, Clone)]
+pub enum CoreError {
+  UnknownPlugin(String),
+  UnknownComponent(String),
+  UnknownState(String, String), // component id, state name
+  DuplicateComponent(String),
+  MissingCapabilities(String, Vec<Capability>), // plugin id, not granted
+  ConfigFile(String, String), // path, error
+}
+
+impl std::error::Error for CoreError {}
+
+impl fmt::Display for CoreError {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+      match self {
+          CoreError::UnknownPlugin(id) => write!(fmt, "Unknown plugin: '{}'", id),
+          CoreError::UnknownComponent(id) => write!(fmt, "Unknown component: '{}'", id),
+          CoreError::UnknownState(id, name) => write!(fmt, "Unknown state '{}' on component '{}'", name, id),
+          CoreError::DuplicateComponent(id) => write!(fmt, "Duplicate component id: '{}'", id),
+          CoreError::MissingCapabilities(id, capabilities) => write!(
+              fmt,
+              "Plugin '{}' requires capabilities not granted by the host: {:?}",
+              id, capabilities
+          ),
+          CoreError::ConfigFile(path, message) => write!(fmt, "Invalid config file '{}': {}", path, message),
+      }
+  }
+}
@@ -0,0 +1,129 @@
+use core_plugin_runtime::runtime::ComponentState;
+use log::{debug, info, warn};
+use std::{
+  collections::HashMap,
+  time::{Duration, Instant},
+};
+
+use super::registry::{Core, CoreError};
+
+const LOG_TARGET: &str = "mylife:home:core:modules:supervisor";
+
+// the delay doubles on each attempt, up to backoff * 2^MAX_BACKOFF_SHIFT
+const MAX_BACKOFF_SHIFT: u32 = 5;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RestartPolicy {
+  Never,
+  OnFailure { max_retries: u32, backoff: Duration },
+  Always { backoff: Duration }, // retries without limit
+}
+
+#[derive(Debug)]
+struct Supervised {
+  policy: RestartPolicy,
+  attempts: u32, // since the last time the component was seen working
+  next_attempt: Option<Instant>,
+  gave_up: bool,
+}
+
+// Brings failed components (ComponentState::Failed, eg: device unreachable on init) back with reinit.
+// The component keeps its config, so reinit is enough: it is not recreated.
+// Driven by tick, on the thread which owns the Core: nothing runs in the background
+#[derive(Debug, Default)]
+pub struct Supervisor {
+  supervised: HashMap<String, Supervised>,
+}
+
+impl Supervisor {
+  pub fn new() -> Self {
+      Supervisor::default()
+  }
+
+  // replaces the policy if the component is already supervised
+  pub fn supervise(&mut self, core: &Core, component_id: &str, policy: RestartPolicy) -> Result<(), CoreError> {
+      if core.component(component_id).is_none() {
+          return Err(CoreError::UnknownComponent(String::from(component_id)));
+      }
+
+      self.supervised.insert(
+          String::from(component_id),
+          Supervised {
+              policy,
+              attempts: 0,
+              next_attempt: None,
+              gave_up: false,
+          },
+      );
+
+      Ok(())
+  }
+
+  pub fn unsupervise(&mut self, component_id: &str) -> bool {
+      self.supervised.remove(component_id).is_some()
+  }
+
+  // Components removed from the core are no longer supervised
+  pub fn tick(&mut self, core: &mut Core, now: Instant) {
+      self.supervised.retain(|id, _| match core.component(id) {
+          Some(_) => true,
+          None => {
+              debug!(target: LOG_TARGET, "Component '{}' removed, no longer supervised", id);
+              false
+          }
+      });
+
+      for (id, supervised) in self.supervised.iter_mut() {
+          let component = core.component_mut(id).expect("supervised component removed");
+
+          if component.state() != ComponentState::Failed {
+              if component.state() == ComponentState::Ready && supervised.attempts > 0 {
+                  info!(target: LOG_TARGET, "Component '{}' recovered after {} attempts", id, supervised.attempts);
+                  supervised.attempts = 0;
+                  supervised.next_attempt = None;
+                  supervised.gave_up = false;
+              }
+
+              continue;
+          }
+
+          let (max_retries, backoff) = match supervised.policy {
+              RestartPolicy::Never => continue,
+              RestartPolicy::OnFailure { max_retries, backoff } => (Some(max_retries), backoff),
+              RestartPolicy::Always { backoff } => (None, backoff),
+          };
+
+          if let Some(max_retries) = max_retries {
+              if supervised.attempts >= max_retries {
+                  if !supervised.gave_up {
+                      warn!(target: LOG_TARGET, "Component '{}' still failed after {} attempts, giving up", id, supervised.attempts);
+                      supervised.gave_up = true;
+                  }
+
+                  continue;
+              }
+          }
+
+          match supervised.next_attempt {
+              None => {
+                  let delay = backoff * 2u32.pow(supervised.attempts.min(MAX_BACKOFF_SHIFT));
+                  debug!(target: LOG_TARGET, "Component '{}' failed, restart in {:?}", id, delay);
+                  supervised.next_attempt = Some(now + delay);
+                  continue;
+              }
+              Some(next_attempt) if next_attempt > now => continue,
+              Some(_) => {}
+          }
+
+          supervised.attempts += 1;
+          supervised.next_attempt = None;
+
+          info!(target: LOG_TARGET, "Restarting component '{}' (attempt {})", id, supervised.attempts);
+
+          // on failure the component is Failed again, and the next attempt is scheduled on next tick
+          if let Err(err) = component.reinit() {
+              warn!(target: LOG_TARGET, "Restart of component '{}' failed: {}", id, err);
+          }
+      }
+  }
+}
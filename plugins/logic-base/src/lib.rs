@@ -1,10 +1,6 @@
 mod value_binary;
 
-use core_plugin_runtime::{export_module, MylifePlugin, PluginRegistry};
+use core_plugin_runtime::mylife_register_plugins;
 use value_binary::ValueBinary;
 
-export_module!(register);
-
-fn register(registry: &mut dyn PluginRegistry) {
-    registry.register_plugin(ValueBinary::runtime());
-}
+mylife_register_plugins!(ValueBinary);
@@ -6,7 +6,7 @@ use core_plugin_runtime::{MylifePluginHooks, State};
 const LOG_TARGET: &str = "mylife:home:core:plugins:logic-base:value-binary";
 
 #[derive(MylifePlugin)]
-#[mylife_plugin(description = "step relay", usage = "logic")] // name=
+#[mylife_plugin(name = "value-binary", description = "step relay", usage = "logic")]
 pub struct ValueBinary {
     id: String,
 
@@ -39,7 +39,7 @@ impl MylifePluginHooks for ValueBinary {
 #[mylife_actions]
 impl ValueBinary {
     // can return Result<(), Box<dyn std::error::Error>> or nothing
-    #[mylife_action(description = "set value to on")] // type=, name=
+    #[mylife_action(description = "set value to on", default_arg = "true")] // type=, name=
     fn on(&mut self, arg: bool) -> Result<(), Box<dyn std::error::Error>> {
         if arg {
             self.state.set(true);
@@ -48,14 +48,14 @@ impl ValueBinary {
         Ok(())
     }
 
-    #[mylife_action(description = "set value to off")]
+    #[mylife_action(description = "set value to off", default_arg = "true")]
     fn off(&mut self, arg: bool) {
         if arg {
             self.state.set(false);
         }
     }
 
-    #[mylife_action(description = "toggle value")]
+    #[mylife_action(description = "toggle value", default_arg = "true")]
     fn toggle(&mut self, arg: bool) {
         if arg {
             self.state.set(!self.state.get());